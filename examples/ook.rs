@@ -1,7 +1,7 @@
 use std::{
     io::stdout,
     sync::{
-        atomic::{AtomicBool, AtomicU64, Ordering},
+        atomic::{AtomicBool, Ordering},
         Arc,
     },
     thread,
@@ -10,8 +10,8 @@ use std::{
 
 use anyhow::Context;
 use bladerf::{
-    BladeRF, Channel, ChannelLayoutRx, ChannelLayoutTx, ComplexI16, RxChannel, StreamConfig,
-    TxChannel,
+    BladeRF, Channel, ChannelLayoutRx, ChannelLayoutTx, ComplexI16, OokConfig, OokDemodulator,
+    OokModulator, RxChannel, StreamConfig, TxChannel,
 };
 use crossterm::{
     cursor::{self},
@@ -51,8 +51,6 @@ impl Default for Config {
     }
 }
 
-// Constants for OOK modulation
-static OOK_THRESHOLD: AtomicU64 = AtomicU64::new(300_000);
 static RUNNING: AtomicBool = AtomicBool::new(true);
 
 // UI State struct to hold all UI elements
@@ -62,6 +60,7 @@ struct UIState {
     rx_stats: String,
     tx_stats: String,
     iq_samples: String,
+    ook_stats: String,
     // Add any other UI elements you need
 }
 
@@ -73,6 +72,7 @@ impl UIState {
             rx_stats: String::new(),
             tx_stats: String::new(),
             iq_samples: String::new(),
+            ook_stats: String::new(),
         }
     }
 }
@@ -82,7 +82,8 @@ fn rx(
     c: Config,
     ui_state: Arc<Mutex<UIState>>,
 ) -> anyhow::Result<()> {
-    let samples_per_bit = c.sample_rate_hz / c.bit_rate;
+    let ook_config = OokConfig::new((c.sample_rate_hz / c.bit_rate) as usize)?;
+    let mut demodulator = OokDemodulator::new(ook_config);
 
     let mut samples = vec![Complex::<i16>::ZERO; c.buffer_size as usize];
 
@@ -91,9 +92,6 @@ fn rx(
     let mut bytes = 0;
     let mut stream_power = 0;
 
-    let mut sample_buffer = Vec::new();
-    let mut bits = Vec::new();
-
     let config = StreamConfig::new(
         c.num_buffers,
         c.buffer_size,
@@ -150,45 +148,43 @@ fn rx(
         }
 
         // ===== Decode OOK =====
-        sample_buffer.extend_from_slice(&samples);
-
-        // Process samples to extract bits
-        while sample_buffer.len() >= samples_per_bit as usize {
-            let bit_samples: Vec<Complex<i16>> =
-                sample_buffer.drain(0..samples_per_bit as usize).collect();
-            // Calculate average magnitude
-            let mut sum_magnitude = 0u64;
-            for s in &bit_samples {
-                let magnitude = (s.re as i32).pow(2) + (s.im as i32).pow(2);
-                sum_magnitude += magnitude as u64;
+        let frames = demodulator.process(&samples);
+
+        let mut ui = ui_state.lock();
+        let stats = demodulator.stats();
+        ui.ook_stats = format!(
+            "threshold {:.0}, {} frames, {} CRC errors",
+            demodulator.threshold(),
+            stats.frames,
+            stats.crc_errors
+        );
+        for frame in frames {
+            let text: String = frame
+                .payload
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii() && !byte.is_ascii_control() {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            let hex: Vec<_> = frame
+                .payload
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect();
+            ui.text_output.insert_str(0, &format!("{text} | "));
+            ui.hex_output
+                .insert_str(0, &format!("{} | ", hex.join(" ")));
+            // Limit the length of the output strings
+            let max_length = 80; // Adjust as needed
+            if ui.text_output.len() > max_length {
+                ui.text_output.truncate(max_length);
             }
-            let avg_magnitude = sum_magnitude / bit_samples.len() as u64;
-
-            let threshold = OOK_THRESHOLD.load(Ordering::Acquire);
-
-            let bit = if avg_magnitude > threshold { 1 } else { 0 };
-            bits.push(bit);
-
-            // When we have 8 bits, convert to byte and update UI state
-            if bits.len() >= 8 {
-                let byte = bits_to_byte(&bits[0..8]);
-                bits.drain(0..8);
-
-                let mut ui = ui_state.lock();
-                if byte.is_ascii() && !byte.is_ascii_control() {
-                    ui.text_output.insert_str(0, &format!(" {} ", byte as char));
-                } else {
-                    ui.text_output.insert_str(0, "   ");
-                }
-                ui.hex_output.insert_str(0, &format!(" {:02X} ", byte));
-                // Limit the length of the output strings
-                let max_length = 80; // Adjust as needed
-                if ui.text_output.len() > max_length {
-                    ui.text_output.truncate(max_length);
-                }
-                if ui.hex_output.len() > max_length * 3 {
-                    ui.hex_output.truncate(max_length * 3);
-                }
+            if ui.hex_output.len() > max_length * 3 {
+                ui.hex_output.truncate(max_length * 3);
             }
         }
     }
@@ -196,41 +192,16 @@ fn rx(
     Ok(())
 }
 
-// Helper function to convert 8 bits to a byte
-fn bits_to_byte(bits: &[u8]) -> u8 {
-    bits.iter()
-        .enumerate()
-        .fold(0u8, |acc, (i, &b)| acc | (b << (7 - i)))
-}
-
 fn tx(
     device: Arc<bladerf::BladeRfAny>,
     c: Config,
     ui_state: Arc<Mutex<UIState>>,
 ) -> anyhow::Result<()> {
-    let samples_per_bit = c.sample_rate_hz / c.bit_rate;
-
-    // Define preamble and sync word
-    let preamble: &[u8] = &[
-        1, 0, 1, 0, 1, 0, 1, 0, // 0xAA
-        1, 0, 1, 0, 1, 0, 1, 0,
-    ];
-    let sync_word: &[u8] = &[0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1];
+    let ook_config = OokConfig::new((c.sample_rate_hz / c.bit_rate) as usize)?;
+    let mut modulator = OokModulator::new(ook_config);
 
-    // Define the bitstream to send (e.g., "Hello")
+    // The message to send (e.g., "Hello")
     let message = b"Hello";
-    let mut message_bits = Vec::new();
-    for &byte in message.iter() {
-        for i in (0..8).rev() {
-            let bit = (byte >> i) & 1;
-            message_bits.push(bit);
-        }
-    }
-
-    // Combine preamble, sync word, and message
-    let bitstream = [preamble, sync_word, &message_bits].concat();
-    let bitstream_len = bitstream.len();
-    let mut bit_index = 0;
 
     let mut samples = vec![Complex::<i16>::ZERO; c.buffer_size];
 
@@ -254,21 +225,11 @@ fn tx(
     tx.enable().context("Failed to enable tx")?;
 
     while RUNNING.load(Ordering::Acquire) {
-        // Fill the samples buffer according to the bitstream
-        let mut sample_pos = 0;
-        while sample_pos < samples.len() {
-            let bit = bitstream[bit_index % bitstream_len];
-            bit_index += 1;
-
-            // For the current bit, generate samples_per_bit samples
-            let end = std::cmp::min(sample_pos + samples_per_bit as usize, samples.len());
-            let amplitude = if bit == 1 { 2047 } else { 0 };
-            for s in &mut samples[sample_pos..end] {
-                s.re = amplitude;
-                s.im = 0;
-            }
-            sample_pos = end;
+        // Keep repeating the message, with the carrier off in between
+        if modulator.is_idle() {
+            modulator.send(message)?;
         }
+        modulator.modulate(&mut samples);
 
         tx.write(&samples, c.timeout)
             .context("Failed to write samples")?;
@@ -430,8 +391,6 @@ fn tui_app() -> anyhow::Result<()> {
     let receiver = thread::spawn(move || rx(device_rx, config_rx, ui_state_rx));
     let sender = thread::spawn(move || tx(device_tx, config_tx, ui_state_tx));
 
-    let mut threshold_textbox = String::new();
-
     // Main loop to display UI
     while RUNNING.load(Ordering::Acquire) {
         if receiver.is_finished() || sender.is_finished() {
@@ -453,14 +412,10 @@ fn tui_app() -> anyhow::Result<()> {
                 .queue(Clear(ClearType::All))
                 .unwrap();
 
-            let current_threshold = OOK_THRESHOLD.load(Ordering::Acquire);
             // Render UI elements
             let lines = [
                 "Press 'q' to exit.".to_string(),
-                format!(
-                    "Current Threshold: {} - {threshold_textbox}",
-                    current_threshold
-                ),
+                format!("OOK: {}", ui.ook_stats),
                 format!("TX Stats: {}", ui.tx_stats),
                 format!("RX Stats: {}", ui.rx_stats),
                 format!("IQ Samples: {}", ui.iq_samples),
@@ -483,31 +438,19 @@ fn tui_app() -> anyhow::Result<()> {
                 break;
             }
             while let Event::Key(key_event) = event::read().unwrap() {
-                match key_event.code {
-                    KeyCode::Char('q') => {
-                        RUNNING.store(false, Ordering::Release);
+                if key_event.code == KeyCode::Char('q') {
+                    RUNNING.store(false, Ordering::Release);
 
-                        use crossterm::{cursor, QueueableCommand};
-                        use std::io::{stdout, Write};
-                        let mut stdout = stdout();
+                    use crossterm::{cursor, QueueableCommand};
+                    use std::io::{stdout, Write};
+                    let mut stdout = stdout();
 
-                        stdout
-                            .queue(cursor::MoveTo(0, 0))?
-                            .queue(Clear(ClearType::All))?
-                            .queue(Print("Exiting (please wait)..."))?;
+                    stdout
+                        .queue(cursor::MoveTo(0, 0))?
+                        .queue(Clear(ClearType::All))?
+                        .queue(Print("Exiting (please wait)..."))?;
 
-                        stdout.flush().unwrap();
-                    }
-                    KeyCode::Enter => {
-                        if let Ok(threshold) = threshold_textbox.parse::<u64>() {
-                            OOK_THRESHOLD.store(threshold, Ordering::Release);
-                        }
-                        threshold_textbox.clear();
-                    }
-                    KeyCode::Char(c) if c.is_ascii_digit() => {
-                        threshold_textbox.push(c);
-                    }
-                    _ => {}
+                    stdout.flush().unwrap();
                 }
             }
         }
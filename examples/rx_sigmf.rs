@@ -0,0 +1,115 @@
+use anyhow::Context;
+use bladerf::{
+    BladeRF, BladeRfAny, ChannelLayoutRx, ComplexI16, RxChannel, SigMfRecorder, StreamConfig,
+};
+use std::{path::PathBuf, sync::mpsc::TryRecvError, time::Duration};
+
+use clap::{Parser, ValueEnum};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliChannel {
+    Ch0,
+    Ch1,
+}
+
+const SAMPLES_PER_BLOCK: usize = 8192;
+
+/// Simple program to receive samples from a bladeRF and record them as a SigMF recording.
+///
+/// Writes `<outfile>.sigmf-data` with the samples as `ci16_le` and `<outfile>.sigmf-meta` describing the recording.
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    /// The base name of the recording, without the `.sigmf-*` extension.
+    #[arg(short, long)]
+    outfile: PathBuf,
+
+    /// The device identifier.
+    ///
+    /// Valid options are described here: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___i_n_i_t.html#gab341ac98615f393da9158ea59cdb6a24>
+    #[arg(short, long)]
+    device: Option<String>,
+
+    /// The center frequency to tune to in Hz.
+    #[arg(short, long)]
+    frequency: u64,
+
+    /// The sample rate of the device in Hz (samples per second).
+    #[arg(short, long)]
+    samplerate: u32,
+
+    /// The channel/port to use
+    #[arg(short, long, default_value = "ch0")]
+    channel: CliChannel,
+
+    /// How long to recieve samples for in seconds. If not provided, will run until Ctrl-C is pressed.
+    #[arg(long, short = 't')]
+    duration: Option<f32>,
+
+    /// Description stored in the metadata.
+    #[arg(long)]
+    description: Option<String>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    pretty_env_logger::init();
+
+    let dev = if let Some(device) = &args.device {
+        BladeRfAny::open_identifier(device).with_context(|| "Cannot Open Device")?
+    } else {
+        BladeRfAny::open_first().with_context(|| "Cannot Open Device")?
+    };
+
+    let channel = match args.channel {
+        CliChannel::Ch0 => RxChannel::Rx0,
+        CliChannel::Ch1 => RxChannel::Rx1,
+    };
+
+    dev.set_frequency(channel.into(), args.frequency)
+        .with_context(|| format!("Unable to set frequency ({})", args.frequency))?;
+    dev.set_sample_rate(channel.into(), args.samplerate)
+        .with_context(|| format!("Unable to set sample rate ({})", args.samplerate))?;
+
+    let config = StreamConfig::new(16, SAMPLES_PER_BLOCK, 8, Duration::from_secs(3))
+        .with_context(|| "Cannot Create Sync Config")?;
+    let reciever = dev
+        .rx_streamer::<ComplexI16>(config, ChannelLayoutRx::SISO(channel))
+        .with_context(|| "Cannot Get Streamer")?;
+    reciever.enable().with_context(|| "Cannot Enable Stream")?;
+
+    let mut recorder = SigMfRecorder::create(reciever, &args.outfile)
+        .with_context(|| "Cannot Create Recording")?;
+    if let Some(description) = args.description {
+        recorder = recorder.with_description(description);
+    }
+
+    let (ctrlc_tx, ctrlc_rx) = std::sync::mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = ctrlc_tx.send(());
+    })
+    .with_context(|| "Cannot Set Ctrl-C Handler")?;
+
+    let sample_limit = args
+        .duration
+        .map(|duration| (args.samplerate as f64 * duration as f64) as u64);
+
+    log::info!("Starting to record samples");
+
+    let mut buffer = [ComplexI16::ZERO; SAMPLES_PER_BLOCK];
+    while sample_limit.is_none_or(|limit| recorder.sample_count() < limit) {
+        recorder
+            .record(&mut buffer, Duration::from_secs(1))
+            .with_context(|| "Cannot Record Samples")?;
+        if !matches!(ctrlc_rx.try_recv(), Err(TryRecvError::Empty)) {
+            break;
+        }
+    }
+
+    log::info!("Recorded {} samples", recorder.sample_count());
+    recorder
+        .finish()
+        .with_context(|| "Cannot Finish Recording")?;
+
+    Ok(())
+}
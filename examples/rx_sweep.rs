@@ -0,0 +1,109 @@
+use anyhow::Context;
+use bladerf::{BladeRfAny, ChannelLayoutRx, RxChannel, StreamConfig, SweepConfig, Sweeper};
+use num_complex::Complex32;
+use std::{sync::mpsc::TryRecvError, time::Duration};
+
+use clap::{Parser, ValueEnum};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliChannel {
+    Ch0,
+    Ch1,
+}
+
+/// Simple program to sweep the power spectrum of a wide span, printing it in the CSV format of `hackrf_sweep`.
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    /// The device identifier.
+    ///
+    /// Valid options are described here: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___i_n_i_t.html#gab341ac98615f393da9158ea59cdb6a24>
+    #[arg(short, long)]
+    device: Option<String>,
+
+    /// The lowest frequency of the sweep in MHz.
+    #[arg(long)]
+    start: f64,
+
+    /// The highest frequency of the sweep in MHz.
+    #[arg(long)]
+    stop: f64,
+
+    /// The sample rate of the device in Hz (samples per second), which bounds the width of each step.
+    #[arg(short, long, default_value_t = 20_000_000)]
+    samplerate: u32,
+
+    /// The number of FFT bins per step.
+    #[arg(short = 'n', long, default_value_t = 1024)]
+    fft_size: usize,
+
+    /// The number of FFTs averaged per step.
+    #[arg(short, long, default_value_t = 1)]
+    averages: usize,
+
+    /// The channel/port to use
+    #[arg(short, long, default_value = "ch0")]
+    channel: CliChannel,
+
+    /// Stop after this many sweeps. If not provided, will run until Ctrl-C is pressed.
+    #[arg(short = 'N', long)]
+    sweeps: Option<u64>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    pretty_env_logger::init();
+
+    let dev = if let Some(device) = &args.device {
+        BladeRfAny::open_identifier(device).with_context(|| "Cannot Open Device")?
+    } else {
+        BladeRfAny::open_first().with_context(|| "Cannot Open Device")?
+    };
+
+    let channel = match args.channel {
+        CliChannel::Ch0 => RxChannel::Rx0,
+        CliChannel::Ch1 => RxChannel::Rx1,
+    };
+
+    let config = StreamConfig::default().with_metadata(true);
+    let reciever = dev
+        .rx_streamer::<Complex32>(config, ChannelLayoutRx::SISO(channel))
+        .with_context(|| "Cannot Get Streamer")?;
+    reciever.enable().with_context(|| "Cannot Enable Stream")?;
+
+    let sweep_config = SweepConfig::new(
+        (args.start * 1e6) as u64,
+        (args.stop * 1e6) as u64,
+        args.samplerate,
+        args.fft_size,
+    )
+    .with_context(|| "Invalid Sweep")?
+    .with_averages(args.averages);
+    let mut sweeper = Sweeper::new(&reciever, sweep_config, Duration::from_secs(1))
+        .with_context(|| "Cannot Set Up Sweep")?;
+
+    let (ctrlc_tx, ctrlc_rx) = std::sync::mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = ctrlc_tx.send(());
+    })
+    .with_context(|| "Cannot Set Ctrl-C Handler")?;
+
+    log::info!(
+        "Sweeping in {} steps of {:.0} Hz bins",
+        sweeper.num_steps(),
+        sweeper.bin_width()
+    );
+
+    for line in sweeper.lines() {
+        let line = line.with_context(|| "Cannot Sweep")?;
+        if args.sweeps.is_some_and(|sweeps| line.sweep >= sweeps) {
+            break;
+        }
+        println!("{}", line.to_csv());
+        if !matches!(ctrlc_rx.try_recv(), Err(TryRecvError::Empty)) {
+            break;
+        }
+    }
+
+    Ok(())
+}
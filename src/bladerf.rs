@@ -1,5 +1,9 @@
-use crate::{error::*, sys::*, types::*, RxSyncStream, StreamConfig, TxSyncStream};
-use ffi::{c_char, CStr, CString};
+use crate::{
+    error::*, sys::*, types::*, AsyncRxStream, AsyncTxStream, RxSyncStream, StreamConfig,
+    TxSyncStream,
+};
+use ffi::{c_char, c_void, CStr, CString};
+use ops::ControlFlow;
 use path::Path;
 use std::{mem::ManuallyDrop, sync::Arc, *};
 use sync::atomic::{AtomicBool, Ordering};
@@ -13,6 +17,22 @@ macro_rules! check_res {
 	);
 }
 
+/// Marks the RX or TX stream of a device as `configured` while `open` creates it, so only one can exist at a time.
+///
+/// The mark is removed again if `open` fails, so a rejected stream does not keep the device from streaming.
+pub(crate) fn claim_stream<S>(
+    configured: &AtomicBool,
+    direction: &str,
+    open: impl FnOnce() -> Result<S>,
+) -> Result<S> {
+    // TODO: Decide Ordering
+    configured
+        .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+        .map_err(|_err| Error::Msg(format!("Already have an {direction} stream open").into()))?;
+
+    open().inspect_err(|_err| configured.store(false, Ordering::Relaxed))
+}
+
 /// Environment variable containing the path to the FPGA bitstream file
 pub const FPGA_BITSTREAM_VAR_NAME: &str = "BLADERF_RS_FPGA_BITSTREAM_PATH";
 
@@ -125,15 +145,10 @@ impl BladeRfAny {
         config: StreamConfig,
         layout: ChannelLayoutTx,
     ) -> Result<TxSyncStream<&Self, T, Self>> {
-        // TODO: Decide Ordering
-        self.tx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an TX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { TxSyncStream::new(self, config, layout) }
+        claim_stream(&self.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { TxSyncStream::new(self, config, layout) }
+        })
     }
 
     pub fn tx_streamer_arc<T: SampleFormat>(
@@ -141,16 +156,10 @@ impl BladeRfAny {
         config: StreamConfig,
         layout: ChannelLayoutTx,
     ) -> Result<TxSyncStream<Arc<Self>, T, Self>> {
-        // TODO: Decide Ordering
-        device
-            .tx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an TX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { TxSyncStream::new(device, config, layout) }
+        claim_stream(&device.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { TxSyncStream::new(device.clone(), config, layout) }
+        })
     }
 
     pub fn rx_streamer<T: SampleFormat>(
@@ -158,15 +167,10 @@ impl BladeRfAny {
         config: StreamConfig,
         layout: ChannelLayoutRx,
     ) -> Result<RxSyncStream<&Self, T, BladeRfAny>> {
-        // TODO: Decide Ordering
-        self.rx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an RX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { RxSyncStream::new(self, config, layout) }
+        claim_stream(&self.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { RxSyncStream::new(self, config, layout) }
+        })
     }
 
     pub fn rx_streamer_arc<T: SampleFormat>(
@@ -174,16 +178,46 @@ impl BladeRfAny {
         config: StreamConfig,
         layout: ChannelLayoutRx,
     ) -> Result<RxSyncStream<Arc<Self>, T, BladeRfAny>> {
-        // TODO: Decide Ordering
-        device
-            .rx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an RX stream open".to_owned().into_boxed_str())
-            })?;
+        claim_stream(&device.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { RxSyncStream::new(device.clone(), config, layout) }
+        })
+    }
 
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { RxSyncStream::new(device, config, layout) }
+    /// Starts an [AsyncRxStream] which hands every received buffer to `callback` on a dedicated thread.
+    ///
+    /// See the [AsyncRxStream] docs for usage example.
+    pub fn rx_async_streamer<T: SampleFormat, C>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+        callback: C,
+    ) -> Result<AsyncRxStream<T, Self>>
+    where
+        C: FnMut(&[T]) -> ControlFlow<()> + Send + 'static,
+    {
+        claim_stream(&device.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { AsyncRxStream::new(device.clone(), config, layout, Box::new(callback)) }
+        })
+    }
+
+    /// Starts an [AsyncTxStream] which asks `callback` to fill every buffer to transmit on a dedicated thread.
+    ///
+    /// See the [AsyncTxStream] docs for usage example.
+    pub fn tx_async_streamer<T: SampleFormat, C>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutTx,
+        callback: C,
+    ) -> Result<AsyncTxStream<T, Self>>
+    where
+        C: FnMut(&mut [T]) -> ControlFlow<()> + Send + 'static,
+    {
+        claim_stream(&device.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { AsyncTxStream::new(device.clone(), config, layout, Box::new(callback)) }
+        })
     }
 }
 
@@ -205,6 +239,20 @@ pub trait BladeRF: Sized + Drop {
     /// Gets a raw pointer to the device as used in `libbladerf`
     fn get_device_ptr(&self) -> *mut bladerf;
 
+    /// Gets the `libbladerf` handle of the device for the provided methods.
+    ///
+    /// Devices which are not backed by `libbladerf` override this to fail with [Error::Unsupported], so a method they
+    /// do not override fails instead of passing an invalid handle to `libbladerf`.
+    #[doc(hidden)]
+    fn device_ptr(&self) -> Result<*mut bladerf> {
+        Ok(self.get_device_ptr())
+    }
+
+    /// Identifies the device, e.g. to tell whether two handles refer to the same device.
+    fn device_id(&self) -> usize {
+        self.get_device_ptr() as usize
+    }
+
     /// Get info about the device
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___i_n_i_t.html#ga20dad2500cb682a8afa31afc56d8cd4f>
@@ -218,7 +266,7 @@ pub trait BladeRF: Sized + Drop {
             manufacturer: [0; 33],
             product: [0; 33],
         };
-        let res = unsafe { bladerf_get_devinfo(self.get_device_ptr(), &mut info as *mut _) };
+        let res = unsafe { bladerf_get_devinfo(self.device_ptr()?, &mut info as *mut _) };
         check_res!(res);
         Ok(info.into())
     }
@@ -234,7 +282,7 @@ pub trait BladeRF: Sized + Drop {
 
         // TODO: This method is now depricated, should instead use bladerf_get_serial_struct(). The documentation comment links to the new version
         let res =
-            unsafe { bladerf_get_serial(self.get_device_ptr(), serial_data.as_mut_ptr().cast()) };
+            unsafe { bladerf_get_serial(self.device_ptr()?, serial_data.as_mut_ptr().cast()) };
 
         check_res!(res);
         let serial_cstr = unsafe { CStr::from_ptr(serial_data.as_ptr().cast()) };
@@ -251,7 +299,7 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___i_n_f_o.html#gaffb76ef5b491e95584fc43d45e4ced14>
     fn get_device_speed(&self) -> Result<DeviceSpeed> {
-        let speed = unsafe { bladerf_device_speed(self.get_device_ptr()) };
+        let speed = unsafe { bladerf_device_speed(self.device_ptr()?) };
         speed.try_into()
     }
 
@@ -260,7 +308,7 @@ pub trait BladeRF: Sized + Drop {
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___i_n_f_o.html#gaaec5953b58fd9bca3c0cec9f9655b6a0>
     fn get_fpga_size(&self) -> Result<FpgaSize> {
         let mut fpga_size: bladerf_fpga_size = bladerf_fpga_size_BLADERF_FPGA_UNKNOWN;
-        let res = unsafe { bladerf_get_fpga_size(self.get_device_ptr(), &mut fpga_size) };
+        let res = unsafe { bladerf_get_fpga_size(self.device_ptr()?, &mut fpga_size) };
         check_res!(res);
         fpga_size.try_into()
     }
@@ -276,7 +324,7 @@ pub trait BladeRF: Sized + Drop {
             describe: std::ptr::null(),
         };
 
-        let res = unsafe { bladerf_fw_version(self.get_device_ptr(), &mut version) };
+        let res = unsafe { bladerf_fw_version(self.device_ptr()?, &mut version) };
         check_res!(res);
 
         // SAFETY: came from bladerf ffi
@@ -287,7 +335,7 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___i_n_f_o.html#ga6cf59976f738efde781dc676fb41f1fd>
     fn is_fpga_configured(&self) -> Result<bool> {
-        let res = unsafe { bladerf_is_fpga_configured(self.get_device_ptr()) };
+        let res = unsafe { bladerf_is_fpga_configured(self.device_ptr()?) };
         check_res!(res);
 
         match res {
@@ -308,7 +356,7 @@ pub trait BladeRF: Sized + Drop {
             describe: std::ptr::null(),
         };
 
-        let res = unsafe { bladerf_fpga_version(self.get_device_ptr(), &mut version) };
+        let res = unsafe { bladerf_fpga_version(self.device_ptr()?, &mut version) };
         check_res!(res);
 
         // SAFETY: came from bladerf ffi
@@ -325,7 +373,7 @@ pub trait BladeRF: Sized + Drop {
     #[doc(hidden)]
     fn set_enable_module(&self, channel: Channel, enable: bool) -> Result<()> {
         let res = unsafe {
-            bladerf_enable_module(self.get_device_ptr(), channel as bladerf_channel, enable)
+            bladerf_enable_module(self.device_ptr()?, channel as bladerf_channel, enable)
         };
         check_res!(res);
         Ok(())
@@ -338,19 +386,19 @@ pub trait BladeRF: Sized + Drop {
 
     /// Configure the channel's sample rate to the specified rate in Hz.
     ///
-    /// Returns the actual sample rate set.
+    /// Takes either a `u32` or a [SampleRate], such as `SampleRate(2_000_000)`. Returns the actual sample rate set.
     ///
     /// Once can use [set_rational_sample_rate][BladeRF::set_rational_sample_rate] to set a more arbitrary value
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_a_m_p_l_i_n_g.html#gaf118558cccf01ada2f2eeafed6f439e8>
-    fn set_sample_rate(&self, channel: Channel, rate: u32) -> Result<u32> {
+    fn set_sample_rate(&self, channel: Channel, rate: impl Into<SampleRate>) -> Result<u32> {
         let mut actual: u32 = 0;
 
         let res = unsafe {
             bladerf_set_sample_rate(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_module,
-                rate,
+                rate.into().0,
                 &mut actual,
             )
         };
@@ -378,7 +426,7 @@ pub trait BladeRF: Sized + Drop {
         };
         let res = unsafe {
             bladerf_set_rational_sample_rate(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_module,
                 &mut rate,
                 &mut actual,
@@ -395,7 +443,7 @@ pub trait BladeRF: Sized + Drop {
         let mut rate: u32 = 0;
 
         let res = unsafe {
-            bladerf_get_sample_rate(self.get_device_ptr(), channel as bladerf_channel, &mut rate)
+            bladerf_get_sample_rate(self.device_ptr()?, channel as bladerf_channel, &mut rate)
         };
         check_res!(res);
         Ok(rate)
@@ -413,7 +461,7 @@ pub trait BladeRF: Sized + Drop {
 
         let res = unsafe {
             bladerf_get_rational_sample_rate(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_module,
                 &mut rate,
             )
@@ -429,7 +477,7 @@ pub trait BladeRF: Sized + Drop {
         let mut range_ptr: *const bladerf_range = ptr::null();
         let res = unsafe {
             bladerf_get_sample_rate_range(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 &mut range_ptr,
             )
@@ -455,11 +503,16 @@ pub trait BladeRF: Sized + Drop {
         config: &StreamConfig,
         layout: ChannelLayout,
     ) -> Result<()> {
+        let format = if config.metadata {
+            T::META_FORMAT
+        } else {
+            T::FORMAT
+        };
         let res = unsafe {
             bladerf_sync_config(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 layout as bladerf_channel_layout,
-                T::FORMAT as bladerf_format,
+                format as bladerf_format,
                 config.num_buffers,
                 config.buffer_size,
                 config.num_transfers,
@@ -470,11 +523,63 @@ pub trait BladeRF: Sized + Drop {
         Ok(())
     }
 
+    /// # Safety
+    /// Intended for internal use only.
+    /// This is used to read samples by the [RxSyncStream] after it configured the device with [BladeRF::set_sync_config()].
+    ///
+    /// `samples` must point to a buffer of `num_samples` samples in the configured format and `metadata` must either be null or valid.
+    #[doc(hidden)]
+    unsafe fn sync_rx(
+        &self,
+        samples: *mut c_void,
+        num_samples: u32,
+        metadata: *mut bladerf_metadata,
+        timeout_ms: u32,
+    ) -> Result<()> {
+        let res = unsafe {
+            bladerf_sync_rx(
+                self.device_ptr()?,
+                samples,
+                num_samples,
+                metadata,
+                timeout_ms,
+            )
+        };
+        check_res!(res);
+        Ok(())
+    }
+
+    /// # Safety
+    /// Intended for internal use only.
+    /// This is used to write samples by the [TxSyncStream] after it configured the device with [BladeRF::set_sync_config()].
+    ///
+    /// `samples` must point to `num_samples` samples in the configured format and `metadata` must either be null or valid.
+    #[doc(hidden)]
+    unsafe fn sync_tx(
+        &self,
+        samples: *const c_void,
+        num_samples: u32,
+        metadata: *mut bladerf_metadata,
+        timeout_ms: u32,
+    ) -> Result<()> {
+        let res = unsafe {
+            bladerf_sync_tx(
+                self.device_ptr()?,
+                samples,
+                num_samples,
+                metadata,
+                timeout_ms,
+            )
+        };
+        check_res!(res);
+        Ok(())
+    }
+
     /// Set the current RX Mux mode
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___r_e_c_e_i_v_e___m_u_x.html#ga9cc18ba58d0cdf3bc311c6bdf5e99a00>
     fn set_rx_mux(&self, mux: RxMux) -> Result<()> {
-        let res = unsafe { bladerf_set_rx_mux(self.get_device_ptr(), mux as bladerf_rx_mux) };
+        let res = unsafe { bladerf_set_rx_mux(self.device_ptr()?, mux as bladerf_rx_mux) };
         check_res!(res);
         Ok(())
     }
@@ -484,7 +589,7 @@ pub trait BladeRF: Sized + Drop {
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___r_e_c_e_i_v_e___m_u_x.html#ga9833afff874c98b4d021d0acad6cbc54>
     fn get_rx_mux(&self) -> Result<RxMux> {
         let mut mux = bladerf_rx_mux_BLADERF_RX_MUX_INVALID;
-        let res = unsafe { bladerf_get_rx_mux(self.get_device_ptr(), &mut mux) };
+        let res = unsafe { bladerf_get_rx_mux(self.device_ptr()?, &mut mux) };
         check_res!(res);
         RxMux::try_from(mux)
     }
@@ -494,16 +599,17 @@ pub trait BladeRF: Sized + Drop {
     /// Set the bandwidth of the channel to the specified value in Hz
     ///
     /// The underlying device is capable of a discrete set of bandwidth values, the actual bandwidth set is returned.
-    /// Use [get_bandwidth_range][BladeRF::get_bandwidth_range] to see valid bandwidth values.
+    /// Use [get_bandwidth_range][BladeRF::get_bandwidth_range] to see valid bandwidth values, and [Range::round()] to
+    /// pick one of them. Takes either a `u32` or a [Bandwidth].
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_a_n_d_w_i_d_t_h.html#ga0990053e727a23e03785d3802f55c0b3>
-    fn set_bandwidth(&self, channel: Channel, bandwidth: u32) -> Result<u32> {
+    fn set_bandwidth(&self, channel: Channel, bandwidth: impl Into<Bandwidth>) -> Result<u32> {
         let mut actual: u32 = 0;
         let res = unsafe {
             bladerf_set_bandwidth(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
-                bandwidth,
+                bandwidth.into().0,
                 &mut actual,
             )
         };
@@ -517,7 +623,7 @@ pub trait BladeRF: Sized + Drop {
     fn get_bandwidth(&self, ch: Channel) -> Result<u32> {
         let mut bandwidth: u32 = 0;
         let res = unsafe {
-            bladerf_get_bandwidth(self.get_device_ptr(), ch as bladerf_channel, &mut bandwidth)
+            bladerf_get_bandwidth(self.device_ptr()?, ch as bladerf_channel, &mut bandwidth)
         };
         check_res!(res);
         Ok(bandwidth)
@@ -530,7 +636,7 @@ pub trait BladeRF: Sized + Drop {
         let mut range_ptr: *const bladerf_range = ptr::null();
         let res = unsafe {
             bladerf_get_bandwidth_range(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 &mut range_ptr,
             )
@@ -556,9 +662,10 @@ pub trait BladeRF: Sized + Drop {
     /// The high band is used for frequency above 1.5 GHz on bladeRF1 and above 3.0 GHz on bladeRF2. Otherwise, the low band is used.
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_u_n_i_n_g.html#ga6e1bbb67b270d7c7b91779f0bcac655e>
-    fn select_band(&self, channel: Channel, frequency: u64) -> Result<()> {
+    fn select_band(&self, channel: Channel, frequency: impl Into<Hz>) -> Result<()> {
+        let frequency = frequency.into().0;
         let res = unsafe {
-            bladerf_select_band(self.get_device_ptr(), channel as bladerf_channel, frequency)
+            bladerf_select_band(self.device_ptr()?, channel as bladerf_channel, frequency)
         };
         check_res!(res);
         Ok(())
@@ -578,10 +685,13 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// See also [get_frequency_range()][BladeRF::get_frequency_range] to see the valid frequency range and steps for the device.
     ///
+    /// Takes either a `u64` or a [Hz], such as [MHz(915.0)][MHz()].
+    ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_u_n_i_n_g.html#ga4e9b635f18a9531bcd3c6b4d2dd8a4e0>
-    fn set_frequency(&self, channel: Channel, frequency: u64) -> Result<()> {
+    fn set_frequency(&self, channel: Channel, frequency: impl Into<Hz>) -> Result<()> {
+        let frequency = frequency.into().0;
         let res = unsafe {
-            bladerf_set_frequency(self.get_device_ptr(), channel as bladerf_channel, frequency)
+            bladerf_set_frequency(self.device_ptr()?, channel as bladerf_channel, frequency)
         };
         check_res!(res);
         Ok(())
@@ -593,7 +703,7 @@ pub trait BladeRF: Sized + Drop {
     fn get_frequency(&self, channel: Channel) -> Result<u64> {
         let mut freq: u64 = 0;
         let res = unsafe {
-            bladerf_get_frequency(self.get_device_ptr(), channel as bladerf_channel, &mut freq)
+            bladerf_get_frequency(self.device_ptr()?, channel as bladerf_channel, &mut freq)
         };
         check_res!(res);
         Ok(freq)
@@ -606,7 +716,7 @@ pub trait BladeRF: Sized + Drop {
         let mut range_ptr: *const bladerf_range = ptr::null();
         let res = unsafe {
             bladerf_get_frequency_range(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 &mut range_ptr,
             )
@@ -625,20 +735,21 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// <div class="warning">
     ///
-    /// A [TxSyncStream] or [RxSyncStream] must be configured with metadata (Currently cannot be used with our bindings).
+    /// A [TxSyncStream] or [RxSyncStream] must be configured with metadata, see [StreamConfig::with_metadata()].
     ///
     /// If the underlying queue of scheduled retune requests becomes full, [Error::QueueFull] will be returned. In this case, it should be possible to schedule a retune after the timestamp of one of the earlier requests occurs.
     ///
     /// </div>
     ///
-    /// TODO: Get this moved over as a method to the streamer structs once we add the ability to do metadata
+    /// The streams offer this as [RxSyncStream::schedule_retune()] and [TxSyncStream::schedule_retune()], and
+    /// [FrequencyHopper](crate::FrequencyHopper) keeps the queue filled while hopping through a list of frequencies.
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_c_h_e_d_u_l_e_d___t_u_n_i_n_g.html#gad7bd11c5784e78af7ae8fab26f4605fa>
     fn schedule_retune(
         &self,
         channel: Channel,
         time: u64,
-        frequency: u64,
+        frequency: impl Into<Hz>,
         quick_tune: Option<&mut QuickTune>,
     ) -> Result<()> {
         let quick_tune_ptr = quick_tune
@@ -646,10 +757,10 @@ pub trait BladeRF: Sized + Drop {
             .unwrap_or(ptr::null_mut());
         let res = unsafe {
             bladerf_schedule_retune(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 time,
-                frequency,
+                frequency.into().0,
                 quick_tune_ptr,
             )
         };
@@ -664,7 +775,7 @@ pub trait BladeRF: Sized + Drop {
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_c_h_e_d_u_l_e_d___t_u_n_i_n_g.html#gae6b42de62294072ffb63058001b89a42>
     fn cancel_scheduled_retune(&self, channel: Channel) -> Result<()> {
         let res = unsafe {
-            bladerf_cancel_scheduled_retunes(self.get_device_ptr(), channel as bladerf_channel)
+            bladerf_cancel_scheduled_retunes(self.device_ptr()?, channel as bladerf_channel)
         };
         check_res!(res);
         Ok(())
@@ -689,7 +800,7 @@ pub trait BladeRF: Sized + Drop {
         };
         let res = unsafe {
             bladerf_get_quick_tune(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 &mut quick_tune as *mut QuickTune as *mut bladerf_quick_tune,
             )
@@ -703,7 +814,7 @@ pub trait BladeRF: Sized + Drop {
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_u_n_i_n_g___m_o_d_e.html#ga0fcddbdffebc03da8f96781b0b6d096b>
     fn set_tuning_mode(&self, mode: TuningMode) -> Result<()> {
         let res =
-            unsafe { bladerf_set_tuning_mode(self.get_device_ptr(), mode as bladerf_tuning_mode) };
+            unsafe { bladerf_set_tuning_mode(self.device_ptr()?, mode as bladerf_tuning_mode) };
         check_res!(res);
         Ok(())
     }
@@ -713,7 +824,7 @@ pub trait BladeRF: Sized + Drop {
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_u_n_i_n_g___m_o_d_e.html#ga9f33fa7b48ea563fd2f371b583e421a9>
     fn get_tuning_mode(&self) -> Result<TuningMode> {
         let mut mode = bladerf_tuning_mode_BLADERF_TUNING_MODE_INVALID;
-        let res = unsafe { bladerf_get_tuning_mode(self.get_device_ptr(), &mut mode) };
+        let res = unsafe { bladerf_get_tuning_mode(self.device_ptr()?, &mut mode) };
         check_res!(res);
         TuningMode::try_from(mode)
     }
@@ -727,8 +838,7 @@ pub trait BladeRF: Sized + Drop {
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___l_o_o_p_b_a_c_k.html#gae16e66fde699468c4641b767fe6c29ba>
     fn get_loopback_modes(&self) -> Result<Vec<LoopbackModeInfo>> {
         let mut modes_ptr: *const bladerf_loopback_modes = ptr::null();
-        let num_modes =
-            unsafe { bladerf_get_loopback_modes(self.get_device_ptr(), &mut modes_ptr) };
+        let num_modes = unsafe { bladerf_get_loopback_modes(self.device_ptr()?, &mut modes_ptr) };
         if num_modes < 0 {
             return Err(Error::from_bladerf_code(num_modes as isize));
         }
@@ -749,7 +859,7 @@ pub trait BladeRF: Sized + Drop {
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___l_o_o_p_b_a_c_k.html#ga8d1c68e7de9492c18fa9a3c1af1f6a98>
     fn is_loopback_mode_supported(&self, mode: Loopback) -> Result<bool> {
         let supported = unsafe {
-            bladerf_is_loopback_mode_supported(self.get_device_ptr(), mode as bladerf_loopback)
+            bladerf_is_loopback_mode_supported(self.device_ptr()?, mode as bladerf_loopback)
         };
         Ok(supported)
     }
@@ -761,8 +871,7 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___l_o_o_p_b_a_c_k.html#ga8d6398bfafd7541cabc9cab5ab2bd709>
     unsafe fn set_loopback(&self, loopback: Loopback) -> Result<()> {
-        let res =
-            unsafe { bladerf_set_loopback(self.get_device_ptr(), loopback as bladerf_loopback) };
+        let res = unsafe { bladerf_set_loopback(self.device_ptr()?, loopback as bladerf_loopback) };
         check_res!(res);
         Ok(())
     }
@@ -774,7 +883,7 @@ pub trait BladeRF: Sized + Drop {
         unsafe {
             let mut loopback = bladerf_loopback_BLADERF_LB_NONE;
 
-            let res = bladerf_get_loopback(self.get_device_ptr(), &mut loopback);
+            let res = bladerf_get_loopback(self.device_ptr()?, &mut loopback);
             check_res!(res);
 
             Loopback::try_from(loopback)
@@ -787,16 +896,17 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// This sets an overall system gain, optimally proportioning the gain between multiple gain stages if applicable.
     ///
-    /// Use [get_gain_range()][BladeRF::get_gain_range] to see the valid gain range for the device.
+    /// Use [get_gain_range()][BladeRF::get_gain_range] to see the valid gain range for the device. Takes either a [Gain]
+    /// or a [Db].
     ///
     /// On receive channels, 60 dB is the maximum gain level.
     ///
     /// On transmit channels, 60 dB is defined as approximately 0 dBm. Note that this is not a calibrated value, and the actual output power will vary based on a multitude of factors.
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___g_a_i_n.html#gade4256dc2bd29d9c9e69c39beb9e12ff>
-    fn set_gain(&self, channel: Channel, gain: Gain) -> Result<()> {
-        let res =
-            unsafe { bladerf_set_gain(self.get_device_ptr(), channel as bladerf_channel, gain) };
+    fn set_gain(&self, channel: Channel, gain: impl Into<Db>) -> Result<()> {
+        let gain = gain.into().0;
+        let res = unsafe { bladerf_set_gain(self.device_ptr()?, channel as bladerf_channel, gain) };
         check_res!(res);
         Ok(())
     }
@@ -806,9 +916,8 @@ pub trait BladeRF: Sized + Drop {
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___g_a_i_n.html#gaff3b110dc02420b6234252861680c987>
     fn get_gain(&self, channel: Channel) -> Result<Gain> {
         let mut gain: Gain = 0;
-        let res = unsafe {
-            bladerf_get_gain(self.get_device_ptr(), channel as bladerf_channel, &mut gain)
-        };
+        let res =
+            unsafe { bladerf_get_gain(self.device_ptr()?, channel as bladerf_channel, &mut gain) };
         check_res!(res);
         Ok(gain)
     }
@@ -823,7 +932,7 @@ pub trait BladeRF: Sized + Drop {
     fn set_gain_mode(&self, channel: Channel, mode: GainMode) -> Result<()> {
         let res = unsafe {
             bladerf_set_gain_mode(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 mode as bladerf_gain_mode,
             )
@@ -840,7 +949,7 @@ pub trait BladeRF: Sized + Drop {
     fn get_gain_mode(&self, channel: Channel) -> Result<GainMode> {
         let mut mode = bladerf_gain_mode_BLADERF_GAIN_DEFAULT;
         let res = unsafe {
-            bladerf_get_gain_mode(self.get_device_ptr(), channel as bladerf_channel, &mut mode)
+            bladerf_get_gain_mode(self.device_ptr()?, channel as bladerf_channel, &mut mode)
         };
         check_res!(res);
         GainMode::try_from(mode)
@@ -855,7 +964,7 @@ pub trait BladeRF: Sized + Drop {
         let mut modes_ptr: *const bladerf_gain_modes = ptr::null();
         let num_modes = unsafe {
             bladerf_get_gain_modes(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 &mut modes_ptr,
             )
@@ -884,7 +993,7 @@ pub trait BladeRF: Sized + Drop {
         let mut range_ptr: *const bladerf_range = ptr::null();
         let res = unsafe {
             bladerf_get_gain_range(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 &mut range_ptr,
             )
@@ -906,14 +1015,14 @@ pub trait BladeRF: Sized + Drop {
     /// </div>
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___g_a_i_n.html#ga58e4c9ced3c0b0e80260a5fc5ec870cf>
-    fn set_gain_stage(&self, channel: Channel, stage: &str, gain: Gain) -> Result<()> {
+    fn set_gain_stage(&self, channel: Channel, stage: &str, gain: impl Into<Db>) -> Result<()> {
         let stage_cstr = CString::new(stage).map_err(|_| Error::msg("Invalid stage string"))?;
         let res = unsafe {
             bladerf_set_gain_stage(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 stage_cstr.as_ptr(),
-                gain,
+                gain.into().0,
             )
         };
         check_res!(res);
@@ -930,7 +1039,7 @@ pub trait BladeRF: Sized + Drop {
         let mut gain: Gain = 0;
         let res = unsafe {
             bladerf_get_gain_stage(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 stage_cstr.as_ptr(),
                 &mut gain as *mut bladerf_gain,
@@ -954,7 +1063,7 @@ pub trait BladeRF: Sized + Drop {
         let mut range_ptr: *const bladerf_range = ptr::null();
         let res = unsafe {
             bladerf_get_gain_stage_range(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 stage_cstr.as_ptr(),
                 &mut range_ptr,
@@ -974,7 +1083,7 @@ pub trait BladeRF: Sized + Drop {
         // First, call with count = 0 to get the number of stages
         let num_stages = unsafe {
             bladerf_get_gain_stages(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 ptr::null_mut(),
                 0,
@@ -990,7 +1099,7 @@ pub trait BladeRF: Sized + Drop {
         let mut stages: Vec<*const c_char> = vec![ptr::null(); num_stages];
         let res = unsafe {
             bladerf_get_gain_stages(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 stages.as_mut_ptr(),
                 num_stages,
@@ -1015,10 +1124,113 @@ pub trait BladeRF: Sized + Drop {
         Ok(stages)
     }
 
+    // **Bias Tee Functions**
+
+    /// Get current bias tee state
+    ///
+    /// Only the bladeRF 2.0 has bias tees, other boards return [Error::Unsupported].
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___b_i_a_s___t_e_e.html#ga308bc82fca6eaea01c714a772fd945db>
+    fn get_bias_tee(&self, channel: Channel) -> Result<bool> {
+        let mut enable = false;
+        let res = unsafe {
+            bladerf_get_bias_tee(self.device_ptr()?, channel as bladerf_channel, &mut enable)
+        };
+        check_res!(res);
+        Ok(enable)
+    }
+
+    /// Enable or disable the bias tee on the specified channel.
+    ///
+    /// Only the bladeRF 2.0 has bias tees, other boards return [Error::Unsupported].
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___b_i_a_s___t_e_e.html#ga6289800def08a0e8f6ef77ae628e70a1>
+    fn set_bias_tee(&self, channel: Channel, enable: bool) -> Result<()> {
+        let res =
+            unsafe { bladerf_set_bias_tee(self.device_ptr()?, channel as bladerf_channel, enable) };
+        check_res!(res);
+        Ok(())
+    }
+
+    // **SMB Clock Functions**
+
+    /// Set the current mode of operation of the SMB clock port
+    ///
+    /// Only the bladeRF 1 has an SMB clock port, other boards return [Error::Unsupported].
+    /// See [BladeRf1::set_smb_mode()][crate::BladeRf1::set_smb_mode()] for a MIMO configuration.
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_m_b___c_l_o_c_k.html#ga42184eb5678f687c7542b3e2abe3bb71>
+    fn set_smb_mode(&self, mode: SmbMode) -> Result<()> {
+        let res = unsafe { bladerf_set_smb_mode(self.device_ptr()?, mode as bladerf_smb_mode) };
+        check_res!(res);
+        Ok(())
+    }
+
+    /// Get the current mode of operation of the SMB clock port
+    ///
+    /// Only the bladeRF 1 has an SMB clock port, other boards return [Error::Unsupported].
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_m_b___c_l_o_c_k.html#ga622fcc384ac9192576c95b5fd6318d25>
+    fn get_smb_mode(&self) -> Result<SmbMode> {
+        let mut mode = bladerf_smb_mode_BLADERF_SMB_MODE_INVALID;
+        let res = unsafe { bladerf_get_smb_mode(self.device_ptr()?, &mut mode) };
+        check_res!(res);
+        SmbMode::try_from(mode)
+    }
+
+    // **Reference Clock Functions**
+
+    /// Selects the source of the reference clock, either the onboard VCTCXO or the CLKIN connector.
+    ///
+    /// Only the bladeRF 2.0 has a CLKIN connector, other boards return [Error::Unsupported].
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___c_l_o_c_k___s_e_l_e_c_t.html>
+    fn set_clock_select(&self, select: ClockSelect) -> Result<()> {
+        let res =
+            unsafe { bladerf_set_clock_select(self.device_ptr()?, select as bladerf_clock_select) };
+        check_res!(res);
+        Ok(())
+    }
+
+    /// Get the current source of the reference clock.
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___c_l_o_c_k___s_e_l_e_c_t.html>
+    fn get_clock_select(&self) -> Result<ClockSelect> {
+        let mut select = bladerf_clock_select_CLOCK_SELECT_ONBOARD;
+        let res = unsafe { bladerf_get_clock_select(self.device_ptr()?, &mut select) };
+        check_res!(res);
+        select.try_into()
+    }
+
+    /// Enable or disable the 38.4 MHz reference clock output on the CLKOUT connector.
+    ///
+    /// Only the bladeRF 2.0 has a CLKOUT connector, other boards return [Error::Unsupported].
+    ///
+    /// The clock output of one device can drive the CLKIN connector of other devices using [ClockSelect::External].
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___c_l_o_c_k___o_u_t_p_u_t.html>
+    fn set_clock_output(&self, enable: bool) -> Result<()> {
+        let res = unsafe { bladerf_set_clock_output(self.device_ptr()?, enable) };
+        check_res!(res);
+        Ok(())
+    }
+
+    /// Get the state of the reference clock output.
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___c_l_o_c_k___o_u_t_p_u_t.html>
+    fn get_clock_output(&self) -> Result<bool> {
+        let mut enabled = false;
+        let res = unsafe { bladerf_get_clock_output(self.device_ptr()?, &mut enabled) };
+        check_res!(res);
+        Ok(enabled)
+    }
+
     // **Trigger Functions**
 
     /// Initialize a trigger
     ///
+    /// See [TriggerMaster](crate::TriggerMaster) and [TriggerSlave](crate::TriggerSlave) for a safe interface to triggers.
+    ///
     /// # Safety
     /// See the BladeRF Docs here: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_r_i_g.html>
     ///     
@@ -1035,7 +1247,7 @@ pub trait BladeRF: Sized + Drop {
         };
         let res = unsafe {
             bladerf_trigger_init(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 signal as bladerf_trigger_signal,
                 &mut trigger as *mut bladerf_trigger,
@@ -1063,15 +1275,8 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_r_i_g.html#ga14afff57873c8ae591a4142d7851a869>
     unsafe fn trigger_arm(&self, trigger: &Trigger, arm: bool) -> Result<()> {
-        let res = unsafe {
-            bladerf_trigger_arm(
-                self.get_device_ptr(),
-                trigger as *const Trigger as *const bladerf_trigger,
-                arm,
-                0,
-                0,
-            )
-        };
+        let trigger = bladerf_trigger::from(trigger);
+        let res = unsafe { bladerf_trigger_arm(self.device_ptr()?, &trigger, arm, 0, 0) };
         check_res!(res);
         Ok(())
     }
@@ -1088,18 +1293,16 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_r_i_g.html#gaaa2b932a3b810203952bb49c1673c124>
     unsafe fn trigger_fire(&self, trigger: &Trigger) -> Result<()> {
-        let res = unsafe {
-            bladerf_trigger_fire(
-                self.get_device_ptr(),
-                trigger as *const Trigger as *const bladerf_trigger,
-            )
-        };
+        let trigger = bladerf_trigger::from(trigger);
+        let res = unsafe { bladerf_trigger_fire(self.device_ptr()?, &trigger) };
         check_res!(res);
         Ok(())
     }
 
     /// Query the fire request status of a master trigger
     ///
+    /// Returns `(armed, fired, fire_requested)`, see [TriggerState] for the typed equivalent.
+    ///
     /// # Safety
     /// See the BladeRF Docs here: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_r_i_g.html>
     ///     
@@ -1112,10 +1315,11 @@ pub trait BladeRF: Sized + Drop {
         let mut fire_requested = false;
         let mut resv1 = 0u64;
         let mut resv2 = 0u64;
+        let trigger = bladerf_trigger::from(trigger);
         let res = unsafe {
             bladerf_trigger_state(
-                self.get_device_ptr(),
-                trigger as *const Trigger as *const bladerf_trigger,
+                self.device_ptr()?,
+                &trigger,
                 &mut is_armed,
                 &mut has_fired,
                 &mut fire_requested,
@@ -1136,7 +1340,7 @@ pub trait BladeRF: Sized + Drop {
         let correction_type: Correction = T::TYPE;
         let res = unsafe {
             bladerf_set_correction(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 correction_type as bladerf_correction,
                 corr.value(),
@@ -1154,7 +1358,7 @@ pub trait BladeRF: Sized + Drop {
         let mut value: i16 = 0;
         let res = unsafe {
             bladerf_get_correction(
-                self.get_device_ptr(),
+                self.device_ptr()?,
                 channel as bladerf_channel,
                 corr as bladerf_correction,
                 &mut value,
@@ -1183,8 +1387,7 @@ pub trait BladeRF: Sized + Drop {
     // TODO Finish documentation comment once we add the metadata capability
     fn get_timestamp(&self, dir: Direction) -> Result<u64> {
         let mut timestamp: u64 = 0;
-        let res =
-            unsafe { bladerf_get_timestamp(self.get_device_ptr(), dir.into(), &mut timestamp) };
+        let res = unsafe { bladerf_get_timestamp(self.device_ptr()?, dir.into(), &mut timestamp) };
         check_res!(res);
         Ok(timestamp)
     }
@@ -1199,7 +1402,7 @@ pub trait BladeRF: Sized + Drop {
         let bitstream_path = CString::new(firmware_path.as_ref().as_os_str().as_encoded_bytes())
             .map_err(|e| Error::msg(format!("Invalid path for cstring: {e:?}")))?;
 
-        let res = unsafe { bladerf_flash_firmware(self.get_device_ptr(), bitstream_path.as_ptr()) };
+        let res = unsafe { bladerf_flash_firmware(self.device_ptr()?, bitstream_path.as_ptr()) };
         check_res!(res);
         Ok(())
     }
@@ -1234,7 +1437,7 @@ pub trait BladeRF: Sized + Drop {
         let bitstream_path = CString::new(bitstream_path.as_ref().as_os_str().as_encoded_bytes())
             .map_err(|e| Error::msg(format!("Invalid path for cstring: {e:?}")))?;
 
-        let res = unsafe { bladerf_load_fpga(self.get_device_ptr(), bitstream_path.as_ptr()) };
+        let res = unsafe { bladerf_load_fpga(self.device_ptr()?, bitstream_path.as_ptr()) };
         check_res!(res);
         Ok(())
     }
@@ -1246,7 +1449,7 @@ pub trait BladeRF: Sized + Drop {
         let bitstream_path = CString::new(bitstream_path.as_ref().as_os_str().as_encoded_bytes())
             .map_err(|e| Error::msg(format!("Invalid path for cstring: {e:?}")))?;
 
-        let res = unsafe { bladerf_flash_fpga(self.get_device_ptr(), bitstream_path.as_ptr()) };
+        let res = unsafe { bladerf_flash_fpga(self.device_ptr()?, bitstream_path.as_ptr()) };
         check_res!(res);
         Ok(())
     }
@@ -1255,7 +1458,7 @@ pub trait BladeRF: Sized + Drop {
     ///
     /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___p_r_o_g.html#gad346e1ea98c82dde2d3c963fe6fec6e2>
     fn erase_stored_fpga(&self) -> Result<()> {
-        let res = unsafe { bladerf_erase_stored_fpga(self.get_device_ptr()) };
+        let res = unsafe { bladerf_erase_stored_fpga(self.device_ptr()?) };
         check_res!(res);
         Ok(())
     }
@@ -1267,7 +1470,7 @@ pub trait BladeRF: Sized + Drop {
     fn get_fw_log(&self, path: impl AsRef<Path>) -> Result<()> {
         let log_path = CString::new(path.as_ref().as_os_str().as_encoded_bytes())
             .map_err(|e| Error::msg(format!("Invalid path for cstring: {e:?}")))?;
-        let res = unsafe { bladerf_get_fw_log(self.get_device_ptr(), log_path.as_ptr()) };
+        let res = unsafe { bladerf_get_fw_log(self.device_ptr()?, log_path.as_ptr()) };
         check_res!(res);
         Ok(())
     }
@@ -1,10 +1,12 @@
+use crate::bladerf::claim_stream;
 use crate::expansion_boards::Xb200;
-use crate::streamers::{RxSyncStream, StreamConfig, TxSyncStream};
+use crate::streamers::{AsyncRxStream, AsyncTxStream, RxSyncStream, StreamConfig, TxSyncStream};
 use crate::{error::*, sys::*, types::*, BladeRF, BladeRfAny};
 use mem::ManuallyDrop;
+use ops::ControlFlow;
 use std::sync::Arc;
 use std::*;
-use sync::atomic::{AtomicBool, Ordering};
+use sync::atomic::AtomicBool;
 
 pub struct BladeRf1 {
     pub(crate) device: *mut bladerf,
@@ -111,21 +113,16 @@ impl BladeRf1 {
     /// device.set_smb_mode(SmbMode::Input).unwrap();
     /// ```
     ///
-    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_m_b___c_l_o_c_k.html#ga42184eb5678f687c7542b3e2abe3bb71>
+    /// Same as [BladeRF::set_smb_mode()], usable without the trait in scope.
     pub fn set_smb_mode(&self, mode: SmbMode) -> Result<()> {
-        let res = unsafe { bladerf_set_smb_mode(self.device, mode as bladerf_smb_mode) };
-        check_res!(res);
-        Ok(())
+        BladeRF::set_smb_mode(self, mode)
     }
 
     /// Get the current mode of operation of the SMB clock port
     ///
-    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_m_b___c_l_o_c_k.html#ga622fcc384ac9192576c95b5fd6318d25>
+    /// Same as [BladeRF::get_smb_mode()], usable without the trait in scope.
     pub fn get_smb_mode(&self) -> Result<SmbMode> {
-        let mut mode = bladerf_smb_mode_BLADERF_SMB_MODE_INVALID;
-        let res = unsafe { bladerf_get_smb_mode(self.device, &mut mode) };
-        check_res!(res);
-        SmbMode::try_from(mode)
+        BladeRF::get_smb_mode(self)
     }
 
     /// Set the SMB clock port frequency in rational Hz
@@ -200,62 +197,100 @@ impl BladeRf1 {
         &self,
         config: StreamConfig,
     ) -> Result<TxSyncStream<&Self, T, BladeRf1>> {
-        // TODO: Decide Ordering
-        self.tx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an TX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { TxSyncStream::new(self, config, ChannelLayoutTx::SISO(TxChannel::Tx0)) }
+        claim_stream(&self.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { TxSyncStream::new(self, config, ChannelLayoutTx::SISO(TxChannel::Tx0)) }
+        })
     }
 
     pub fn tx_streamer_arc<T: SampleFormat>(
         device: Arc<Self>,
         config: StreamConfig,
     ) -> Result<TxSyncStream<Arc<Self>, T, Self>> {
-        // TODO: Decide Ordering
-        device
-            .tx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an TX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { TxSyncStream::new(device, config, ChannelLayoutTx::SISO(TxChannel::Tx0)) }
+        claim_stream(&device.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe {
+                TxSyncStream::new(
+                    device.clone(),
+                    config,
+                    ChannelLayoutTx::SISO(TxChannel::Tx0),
+                )
+            }
+        })
     }
 
     pub fn rx_streamer<T: SampleFormat>(
         &self,
         config: StreamConfig,
     ) -> Result<RxSyncStream<&Self, T, BladeRf1>> {
-        // TODO: Decide Ordering
-        self.rx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an RX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { RxSyncStream::new(self, config, ChannelLayoutRx::SISO(RxChannel::Rx0)) }
+        claim_stream(&self.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { RxSyncStream::new(self, config, ChannelLayoutRx::SISO(RxChannel::Rx0)) }
+        })
     }
 
     pub fn rx_streamer_arc<T: SampleFormat>(
         device: Arc<Self>,
         config: StreamConfig,
     ) -> Result<RxSyncStream<Arc<Self>, T, BladeRf1>> {
-        // TODO: Decide Ordering
-        device
-            .rx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an RX stream open".to_owned().into_boxed_str())
-            })?;
+        claim_stream(&device.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe {
+                RxSyncStream::new(
+                    device.clone(),
+                    config,
+                    ChannelLayoutRx::SISO(RxChannel::Rx0),
+                )
+            }
+        })
+    }
 
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { RxSyncStream::new(device, config, ChannelLayoutRx::SISO(RxChannel::Rx0)) }
+    /// Starts an [AsyncRxStream] which hands every received buffer to `callback` on a dedicated thread.
+    ///
+    /// See the [AsyncRxStream] docs for usage example.
+    pub fn rx_async_streamer<T: SampleFormat, C>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        callback: C,
+    ) -> Result<AsyncRxStream<T, Self>>
+    where
+        C: FnMut(&[T]) -> ControlFlow<()> + Send + 'static,
+    {
+        claim_stream(&device.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe {
+                AsyncRxStream::new(
+                    device.clone(),
+                    config,
+                    ChannelLayoutRx::SISO(RxChannel::Rx0),
+                    Box::new(callback),
+                )
+            }
+        })
+    }
+
+    /// Starts an [AsyncTxStream] which asks `callback` to fill every buffer to transmit on a dedicated thread.
+    ///
+    /// See the [AsyncTxStream] docs for usage example.
+    pub fn tx_async_streamer<T: SampleFormat, C>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        callback: C,
+    ) -> Result<AsyncTxStream<T, Self>>
+    where
+        C: FnMut(&mut [T]) -> ControlFlow<()> + Send + 'static,
+    {
+        claim_stream(&device.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe {
+                AsyncTxStream::new(
+                    device.clone(),
+                    config,
+                    ChannelLayoutTx::SISO(TxChannel::Tx0),
+                    Box::new(callback),
+                )
+            }
+        })
     }
 
     // TODO move to BladeRF trait
@@ -1,9 +1,11 @@
-use crate::streamers::{RxSyncStream, StreamConfig, TxSyncStream};
+use crate::bladerf::claim_stream;
+use crate::streamers::{AsyncRxStream, AsyncTxStream, RxSyncStream, StreamConfig, TxSyncStream};
 use crate::{error::*, sys::*, types::*, BladeRF, BladeRfAny};
 use mem::ManuallyDrop;
+use ops::ControlFlow;
 use std::sync::Arc;
 use std::*;
-use sync::atomic::{AtomicBool, Ordering};
+use sync::atomic::AtomicBool;
 
 unsafe impl Send for BladeRf2 {}
 unsafe impl Sync for BladeRf2 {}
@@ -33,38 +35,75 @@ impl core::fmt::Debug for BladeRf2 {
 impl BladeRf2 {
     /// Get current bias tee state
     ///
-    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___b_i_a_s___t_e_e.html#ga308bc82fca6eaea01c714a772fd945db>
+    /// Same as [BladeRF::get_bias_tee()], usable without the trait in scope.
     pub fn get_bias_tee(&self, channel: Channel) -> Result<bool> {
-        let mut enable = false;
-        let res =
-            unsafe { bladerf_get_bias_tee(self.device, channel as bladerf_channel, &mut enable) };
-        check_res!(res);
-        Ok(enable)
+        BladeRF::get_bias_tee(self, channel)
     }
 
     /// Enable or disable the bias tee on the specified channel.
     ///
-    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___b_i_a_s___t_e_e.html#ga6289800def08a0e8f6ef77ae628e70a1>
+    /// Same as [BladeRF::set_bias_tee()], usable without the trait in scope.
     pub fn set_bias_tee(&self, channel: Channel, enable: bool) -> Result<()> {
-        let res = unsafe { bladerf_set_bias_tee(self.device, channel as bladerf_channel, enable) };
+        BladeRF::set_bias_tee(self, channel, enable)
+    }
+
+    /// Enable or disable the PLL which disciplines the VCTCXO to the reference on the REFIN connector.
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___p_l_l.html>
+    pub fn set_pll_enable(&self, enable: bool) -> Result<()> {
+        let res = unsafe { bladerf_set_pll_enable(self.device, enable) };
+        check_res!(res);
+        Ok(())
+    }
+
+    /// Get whether the reference clock PLL is enabled.
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___p_l_l.html>
+    pub fn get_pll_enable(&self) -> Result<bool> {
+        let mut enabled = false;
+        let res = unsafe { bladerf_get_pll_enable(self.device, &mut enabled) };
+        check_res!(res);
+        Ok(enabled)
+    }
+
+    /// Get whether the reference clock PLL is locked to the REFIN signal.
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___p_l_l.html>
+    pub fn get_pll_lock_state(&self) -> Result<bool> {
+        let mut locked = false;
+        let res = unsafe { bladerf_get_pll_lock_state(self.device, &mut locked) };
+        check_res!(res);
+        Ok(locked)
+    }
+
+    /// Set the frequency in Hz of the reference signal on the REFIN connector used by the PLL.
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___p_l_l.html>
+    pub fn set_pll_refclk(&self, frequency: u64) -> Result<()> {
+        let res = unsafe { bladerf_set_pll_refclk(self.device, frequency) };
         check_res!(res);
         Ok(())
     }
 
+    /// Get the frequency in Hz of the reference signal the PLL is configured for.
+    ///
+    /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___b_l_a_d_e_r_f2___p_l_l.html>
+    pub fn get_pll_refclk(&self) -> Result<u64> {
+        let mut frequency = 0;
+        let res = unsafe { bladerf_get_pll_refclk(self.device, &mut frequency) };
+        check_res!(res);
+        Ok(frequency)
+    }
+
     pub fn tx_streamer<T: SampleFormat>(
         &self,
         config: StreamConfig,
         layout: ChannelLayoutTx,
     ) -> Result<TxSyncStream<&Self, T, BladeRf2>> {
-        // TODO: Decide Ordering
-        self.tx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an TX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { TxSyncStream::new(self, config, layout) }
+        claim_stream(&self.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { TxSyncStream::new(self, config, layout) }
+        })
     }
 
     pub fn tx_streamer_arc<T: SampleFormat>(
@@ -72,16 +111,10 @@ impl BladeRf2 {
         config: StreamConfig,
         layout: ChannelLayoutTx,
     ) -> Result<TxSyncStream<Arc<Self>, T, Self>> {
-        // TODO: Decide Ordering
-        device
-            .tx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an TX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { TxSyncStream::new(device, config, layout) }
+        claim_stream(&device.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { TxSyncStream::new(device.clone(), config, layout) }
+        })
     }
 
     pub fn rx_streamer<T: SampleFormat>(
@@ -89,15 +122,10 @@ impl BladeRf2 {
         config: StreamConfig,
         layout: ChannelLayoutRx,
     ) -> Result<RxSyncStream<&Self, T, Self>> {
-        // TODO: Decide Ordering
-        self.rx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an RX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { RxSyncStream::new(self, config, layout) }
+        claim_stream(&self.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { RxSyncStream::new(self, config, layout) }
+        })
     }
 
     pub fn rx_streamer_arc<T: SampleFormat>(
@@ -105,16 +133,46 @@ impl BladeRf2 {
         config: StreamConfig,
         layout: ChannelLayoutRx,
     ) -> Result<RxSyncStream<Arc<Self>, T, Self>> {
-        // TODO: Decide Ordering
-        device
-            .rx_stream_configured
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .map_err(|_err| {
-                Error::Msg("Already have an RX stream open".to_owned().into_boxed_str())
-            })?;
-
-        // Safety: we check to make sure no other streamers are configured
-        unsafe { RxSyncStream::new(device, config, layout) }
+        claim_stream(&device.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { RxSyncStream::new(device.clone(), config, layout) }
+        })
+    }
+
+    /// Starts an [AsyncRxStream] which hands every received buffer to `callback` on a dedicated thread.
+    ///
+    /// See the [AsyncRxStream] docs for usage example.
+    pub fn rx_async_streamer<T: SampleFormat, C>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+        callback: C,
+    ) -> Result<AsyncRxStream<T, Self>>
+    where
+        C: FnMut(&[T]) -> ControlFlow<()> + Send + 'static,
+    {
+        claim_stream(&device.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { AsyncRxStream::new(device.clone(), config, layout, Box::new(callback)) }
+        })
+    }
+
+    /// Starts an [AsyncTxStream] which asks `callback` to fill every buffer to transmit on a dedicated thread.
+    ///
+    /// See the [AsyncTxStream] docs for usage example.
+    pub fn tx_async_streamer<T: SampleFormat, C>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutTx,
+        callback: C,
+    ) -> Result<AsyncTxStream<T, Self>>
+    where
+        C: FnMut(&mut [T]) -> ControlFlow<()> + Send + 'static,
+    {
+        claim_stream(&device.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { AsyncTxStream::new(device.clone(), config, layout, Box::new(callback)) }
+        })
     }
 }
 
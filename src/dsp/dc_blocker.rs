@@ -0,0 +1,78 @@
+use num_complex::Complex32;
+
+/// Removes the DC component of a signal, such as the LO leakage of a direct conversion receiver.
+///
+/// This is the single-pole high-pass filter `y[n] = x[n] - x[n-1] + alpha * y[n-1]`. The closer `alpha` is to 1, the
+/// narrower the notch at DC and the longer it takes to settle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DcBlocker {
+    alpha: f32,
+    previous_input: Complex32,
+    previous_output: Complex32,
+}
+
+impl DcBlocker {
+    /// DC blocker with the pole at `alpha`, which needs to be within `0.0..1.0` for the filter to be stable.
+    pub fn new(alpha: f32) -> Self {
+        Self {
+            alpha,
+            previous_input: Complex32::ZERO,
+            previous_output: Complex32::ZERO,
+        }
+    }
+
+    /// DC blocker whose -3 dB point is at about `cutoff` Hz, for samples at `sample_rate` Hz.
+    pub fn with_cutoff(cutoff: f64, sample_rate: f64) -> Self {
+        Self::new((-std::f64::consts::TAU * cutoff / sample_rate).exp() as f32)
+    }
+
+    /// Pole of the filter.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Filters `block` in place.
+    pub fn process(&mut self, block: &mut [Complex32]) {
+        for sample in block {
+            let output = *sample - self.previous_input + self.previous_output * self.alpha;
+            self.previous_input = *sample;
+            self.previous_output = output;
+            *sample = output;
+        }
+    }
+
+    /// Clears the samples remembered from previous blocks.
+    pub fn reset(&mut self) {
+        self.previous_input = Complex32::ZERO;
+        self.previous_output = Complex32::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::{assert_close, Nco};
+
+    #[test]
+    fn step_response() {
+        let mut blocker = DcBlocker::new(0.5);
+        let mut block = [Complex32::new(1.0, 2.0); 4];
+        blocker.process(&mut block[..1]);
+        blocker.process(&mut block[1..]);
+        let expected = [1.0, 0.5, 0.25, 0.125].map(|y| Complex32::new(y, 2.0 * y));
+        assert_close(&block, &expected);
+    }
+
+    #[test]
+    fn removes_offset() {
+        let mut blocker = DcBlocker::with_cutoff(100.0, 1e6);
+        let mut tone = vec![Complex32::ZERO; 100_000];
+        Nco::new(50e3, 1e6).generate(&mut tone);
+        let mut block: Vec<_> = tone.iter().map(|s| s + Complex32::new(0.3, -0.2)).collect();
+        blocker.process(&mut block);
+        // Settled after a few time constants of 1.6 ms
+        for (actual, expected) in block.iter().zip(&tone).skip(20_000) {
+            assert!((actual - expected).norm() < 0.01, "{actual} != {expected}");
+        }
+    }
+}
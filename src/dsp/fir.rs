@@ -0,0 +1,230 @@
+use num_complex::Complex32;
+
+use super::Window;
+use crate::Error;
+use crate::Result;
+
+/// Designs a windowed-sinc low-pass filter with `num_taps` taps and unity gain at DC.
+///
+/// `cutoff` is the -6 dB point as a fraction of the sample rate, within `0.0..0.5`. An odd number of taps gives a
+/// filter with a delay of a whole number of samples.
+pub fn low_pass(num_taps: usize, cutoff: f64, window: Window) -> Vec<f32> {
+    let center = num_taps.saturating_sub(1) as f64 / 2.0;
+    let mut taps: Vec<f64> = window
+        .symmetric(num_taps)
+        .into_iter()
+        .enumerate()
+        .map(|(n, w)| {
+            let x = 2.0 * cutoff * (n as f64 - center);
+            let sinc = if x == 0.0 {
+                1.0
+            } else {
+                (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+            };
+            2.0 * cutoff * sinc * w as f64
+        })
+        .collect();
+
+    let gain: f64 = taps.iter().sum();
+    if gain != 0.0 {
+        taps.iter_mut().for_each(|tap| *tap /= gain);
+    }
+    taps.into_iter().map(|tap| tap as f32).collect()
+}
+
+/// A FIR filter which changes the sample rate by `interpolation / decimation`, computing only the samples it outputs.
+///
+/// Conceptually, the input is upsampled by inserting `interpolation - 1` zeros after every sample, filtered with the
+/// taps and then only every `decimation`-th sample is kept. The taps are split into `interpolation` phases instead, so
+/// neither the zeros nor the dropped samples are ever computed. With both ratios at 1, this is a plain FIR filter.
+///
+/// The taps run at the upsampled rate, so they need a gain of `interpolation` to preserve the amplitude of the signal,
+/// which [RationalResampler](super::RationalResampler) takes care of.
+///
+/// ```
+/// use bladerf::dsp::{low_pass, PolyphaseFir, Window};
+/// use num_complex::Complex32;
+///
+/// // From 10 MHz down to 1 MHz, keeping 400 kHz either side of DC
+/// let taps = low_pass(101, 0.04, Window::Hamming);
+/// let mut decimator = PolyphaseFir::decimator(&taps, 10).unwrap();
+///
+/// let block = vec![Complex32::new(1.0, 0.0); 10_000];
+/// let mut output = Vec::new();
+/// decimator.process(&block, &mut output);
+/// assert_eq!(output.len(), 1000);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PolyphaseFir {
+    /// Taps of every phase, `phases[p][k]` being tap `p + k * interpolation`.
+    phases: Vec<Vec<f32>>,
+    interpolation: usize,
+    decimation: usize,
+    /// The most recent `taps_per_phase - 1` input samples, followed by the block being processed.
+    buffer: Vec<Complex32>,
+    /// Phase of the next output sample.
+    phase: usize,
+    /// Input sample the next output is computed at, relative to the start of the next block.
+    index: usize,
+}
+
+impl PolyphaseFir {
+    /// Filter with `taps` at the upsampled rate, resampling by `interpolation / decimation`.
+    ///
+    /// # Errors
+    /// `taps` is empty or a ratio is zero.
+    pub fn new(taps: &[f32], interpolation: usize, decimation: usize) -> Result<Self> {
+        if taps.is_empty() {
+            return Err(Error::msg("FIR filter needs at least one tap"));
+        }
+        if interpolation == 0 || decimation == 0 {
+            return Err(Error::msg("Resampling ratios need to be at least 1"));
+        }
+
+        let taps_per_phase = taps.len().div_ceil(interpolation);
+        let phases = (0..interpolation)
+            .map(|phase| {
+                (0..taps_per_phase)
+                    .map(|k| taps.get(phase + k * interpolation).copied().unwrap_or(0.0))
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            phases,
+            interpolation,
+            decimation,
+            buffer: vec![Complex32::ZERO; taps_per_phase - 1],
+            phase: 0,
+            index: 0,
+        })
+    }
+
+    /// Filter keeping every `factor`-th output sample.
+    pub fn decimator(taps: &[f32], factor: usize) -> Result<Self> {
+        Self::new(taps, 1, factor)
+    }
+
+    /// Filter outputting `factor` samples for every input sample.
+    pub fn interpolator(taps: &[f32], factor: usize) -> Result<Self> {
+        Self::new(taps, factor, 1)
+    }
+
+    /// Upsampling factor.
+    pub fn interpolation(&self) -> usize {
+        self.interpolation
+    }
+
+    /// Downsampling factor.
+    pub fn decimation(&self) -> usize {
+        self.decimation
+    }
+
+    /// Filters `input`, appending the output samples to `output` and returning how many there were.
+    ///
+    /// Over many blocks, this outputs `interpolation / decimation` samples for every input sample.
+    pub fn process(&mut self, input: &[Complex32], output: &mut Vec<Complex32>) -> usize {
+        let history = self.buffer.len();
+        self.buffer.extend_from_slice(input);
+
+        let before = output.len();
+        while self.index < input.len() {
+            let newest = self.index + history;
+            let sample = self.phases[self.phase]
+                .iter()
+                .enumerate()
+                .map(|(k, tap)| self.buffer[newest - k] * tap)
+                .sum();
+            output.push(sample);
+
+            self.phase += self.decimation;
+            self.index += self.phase / self.interpolation;
+            self.phase %= self.interpolation;
+        }
+
+        self.index -= input.len();
+        self.buffer.drain(..input.len());
+        output.len() - before
+    }
+
+    /// Clears the samples remembered from previous blocks.
+    pub fn reset(&mut self) {
+        self.buffer.fill(Complex32::ZERO);
+        self.phase = 0;
+        self.index = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::assert_close;
+
+    fn complex(values: &[f32]) -> Vec<Complex32> {
+        values.iter().map(|&re| Complex32::new(re, -re)).collect()
+    }
+
+    /// Feeds `input` in blocks of `sizes`, repeating the last size.
+    fn process_in_blocks(
+        fir: &mut PolyphaseFir,
+        input: &[Complex32],
+        sizes: &[usize],
+    ) -> Vec<Complex32> {
+        let mut output = Vec::new();
+        let mut rest = input;
+        for step in 0.. {
+            if rest.is_empty() {
+                break;
+            }
+            let size = sizes[step.min(sizes.len() - 1)].min(rest.len());
+            fir.process(&rest[..size], &mut output);
+            rest = &rest[size..];
+        }
+        output
+    }
+
+    #[test]
+    fn reference_low_pass() {
+        // scipy.signal.firwin(7, 0.3)
+        let expected: [f64; 7] = [
+            0.00329661, 0.05897323, 0.24920989, 0.37704053, 0.24920989, 0.05897323, 0.00329661,
+        ];
+        let taps = low_pass(7, 0.15, Window::Hamming);
+        for (tap, expected) in taps.iter().zip(expected) {
+            assert!((*tap as f64 - expected).abs() < 1e-6, "{taps:?}");
+        }
+    }
+
+    #[test]
+    fn decimation() {
+        let input = complex(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let expected = complex(&[1.0, 10.0, 22.0]);
+        for sizes in [&[6][..], &[3], &[1, 5], &[1]] {
+            let mut fir = PolyphaseFir::decimator(&[1.0, 2.0, 3.0], 2).unwrap();
+            assert_close(&process_in_blocks(&mut fir, &input, sizes), &expected);
+        }
+    }
+
+    #[test]
+    fn interpolation() {
+        let input = complex(&[2.0, 4.0, 6.0]);
+        let expected = complex(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        for sizes in [&[3][..], &[1], &[2, 1]] {
+            let mut fir = PolyphaseFir::interpolator(&[0.5, 1.0, 0.5], 2).unwrap();
+            assert_close(&process_in_blocks(&mut fir, &input, sizes), &expected);
+        }
+    }
+
+    #[test]
+    fn fractional_ratio() {
+        let input = complex(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let expected = complex(&[1.0, 0.0, 0.0, 3.0, 0.0, 0.0, 5.0, 0.0, 0.0]);
+        for sizes in [&[6][..], &[1], &[4, 2]] {
+            let mut fir = PolyphaseFir::new(&[1.0], 3, 2).unwrap();
+            assert_close(&process_in_blocks(&mut fir, &input, sizes), &expected);
+        }
+
+        assert!(PolyphaseFir::new(&[], 1, 1).is_err());
+        assert!(PolyphaseFir::new(&[1.0], 0, 1).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+//! Building blocks for processing baseband samples, operating on blocks of [Complex32](num_complex::Complex32).
+//!
+//! Everything keeps its state between calls, so a stream can be processed block by block as it is read with the
+//! same result as processing all of it at once.
+//!
+//! - [Nco] generates a complex tone and shifts signals in frequency.
+//! - [PolyphaseFir] filters and changes the sample rate by an integer ratio.
+//! - [RationalResampler] designs the filter for resampling by a fraction.
+//! - [DcBlocker] removes the DC offset, such as the LO leakage of the receiver.
+//! - [PowerSpectrum] computes averaged power spectra with a [Window].
+//!
+//! Requires the `dsp` feature.
+
+mod window;
+pub use window::*;
+
+mod nco;
+pub use nco::*;
+
+mod fir;
+pub use fir::*;
+
+mod resampler;
+pub use resampler::*;
+
+mod dc_blocker;
+pub use dc_blocker::*;
+
+mod spectrum;
+pub use spectrum::*;
+
+/// Tests if two blocks are equal within `tolerance`.
+#[cfg(test)]
+pub(crate) fn assert_close(actual: &[num_complex::Complex32], expected: &[num_complex::Complex32]) {
+    assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).norm() < 1e-5, "{actual:?} != {expected:?}");
+    }
+}
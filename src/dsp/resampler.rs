@@ -0,0 +1,149 @@
+use num_complex::Complex32;
+
+use super::{low_pass, PolyphaseFir, Window};
+use crate::Error;
+use crate::Result;
+
+/// Taps of the designed filter per output phase, either side of its center.
+const HALF_TAPS_PER_PHASE: usize = 12;
+
+/// Edge of the passband as a fraction of the lower of the two sample rates.
+const PASSBAND: f64 = 0.45;
+
+/// Resamples by a fraction `interpolation / decimation`, with an anti-aliasing filter designed for it.
+///
+/// The filter is a Hamming windowed-sinc [low_pass()] with its cutoff at 45% of the lower of the two rates, run as a
+/// [PolyphaseFir]. Use the latter directly for other filters.
+///
+/// ```
+/// use bladerf::dsp::RationalResampler;
+/// use num_complex::Complex32;
+///
+/// // From the 3.84 MHz the bladeRF streams at to 1.6 MHz, a ratio of 5 / 12
+/// let mut resampler = RationalResampler::from_rates(3_840_000, 1_600_000).unwrap();
+/// assert_eq!((resampler.interpolation(), resampler.decimation()), (5, 12));
+///
+/// let block = vec![Complex32::new(1.0, 0.0); 12_000];
+/// let mut output = Vec::new();
+/// assert_eq!(resampler.process(&block, &mut output), 5000);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RationalResampler {
+    fir: PolyphaseFir,
+    num_taps: usize,
+}
+
+impl RationalResampler {
+    /// Resampler outputting `interpolation` samples for every `decimation` input samples.
+    ///
+    /// The ratio is reduced to lowest terms first.
+    ///
+    /// # Errors
+    /// Either ratio is zero.
+    pub fn new(interpolation: usize, decimation: usize) -> Result<Self> {
+        if interpolation == 0 || decimation == 0 {
+            return Err(Error::msg("Resampling ratios need to be at least 1"));
+        }
+        let divisor = gcd(interpolation, decimation);
+        let (interpolation, decimation) = (interpolation / divisor, decimation / divisor);
+
+        let factor = interpolation.max(decimation);
+        let num_taps = 2 * HALF_TAPS_PER_PHASE * factor + 1;
+        let mut taps = low_pass(num_taps, PASSBAND / factor as f64, Window::Hamming);
+        // The taps run at the upsampled rate, where the signal lost power to the inserted zeros
+        taps.iter_mut().for_each(|tap| *tap *= interpolation as f32);
+
+        Ok(Self {
+            fir: PolyphaseFir::new(&taps, interpolation, decimation)?,
+            num_taps,
+        })
+    }
+
+    /// Resampler from `input_rate` to `output_rate`, both in Hz.
+    pub fn from_rates(input_rate: u32, output_rate: u32) -> Result<Self> {
+        Self::new(output_rate as usize, input_rate as usize)
+    }
+
+    /// Upsampling factor, in lowest terms.
+    pub fn interpolation(&self) -> usize {
+        self.fir.interpolation()
+    }
+
+    /// Downsampling factor, in lowest terms.
+    pub fn decimation(&self) -> usize {
+        self.fir.decimation()
+    }
+
+    /// Output samples per input sample.
+    pub fn ratio(&self) -> f64 {
+        self.interpolation() as f64 / self.decimation() as f64
+    }
+
+    /// Delay of the filter in output samples.
+    pub fn delay(&self) -> f64 {
+        (self.num_taps - 1) as f64 / 2.0 / self.decimation() as f64
+    }
+
+    /// Resamples `input`, appending the output samples to `output` and returning how many there were.
+    pub fn process(&mut self, input: &[Complex32], output: &mut Vec<Complex32>) -> usize {
+        self.fir.process(input, output)
+    }
+
+    /// Clears the samples remembered from previous blocks.
+    pub fn reset(&mut self) {
+        self.fir.reset();
+    }
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::Nco;
+
+    #[test]
+    fn tone_through_resampler() {
+        let mut resampler = RationalResampler::from_rates(1_000_000, 1_500_000).unwrap();
+        assert_eq!((resampler.interpolation(), resampler.decimation()), (3, 2));
+        assert_eq!(resampler.delay(), 18.0);
+
+        let mut input = vec![Complex32::ZERO; 2000];
+        Nco::new(100e3, 1e6).generate(&mut input);
+        let mut output = Vec::new();
+        for block in input.chunks(777) {
+            resampler.process(block, &mut output);
+        }
+        assert_eq!(output.len(), 3000);
+
+        // The same tone at the new rate, once the filter has filled up
+        let mut expected = vec![Complex32::ZERO; 3000];
+        let mut nco = Nco::new(100e3, 1.5e6);
+        nco.set_phase(-std::f64::consts::TAU * 100e3 * resampler.delay() / 1.5e6);
+        nco.generate(&mut expected);
+        for (actual, expected) in output.iter().zip(&expected).skip(100) {
+            assert!((actual - expected).norm() < 0.01, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn identity_ratio() {
+        let mut resampler = RationalResampler::new(4, 4).unwrap();
+        assert_eq!(resampler.ratio(), 1.0);
+
+        let input: Vec<_> = (0..100).map(|n| Complex32::new(n as f32, 0.0)).collect();
+        let mut output = Vec::new();
+        resampler.process(&input, &mut output);
+        // Symmetric taps with unity gain pass a ramp through unchanged apart from the delay, once filled up
+        let delay = resampler.delay() as usize;
+        for (actual, expected) in output[delay..].iter().zip(&input).skip(delay) {
+            assert!((actual - expected).norm() < 1e-3, "{actual} != {expected}");
+        }
+        assert!(RationalResampler::new(0, 1).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use num_complex::Complex32;
+use rustfft::{Fft, FftPlanner};
+
+use super::Window;
+use crate::Error;
+use crate::Result;
+
+/// Computes windowed FFT power spectra, averaged over consecutive blocks.
+///
+/// Spectra are ordered from the lowest to the highest frequency, with DC at index `size / 2`, and normalized to the
+/// window, so a complex tone of amplitude 1 centered on a bin has a power of 1, or 0 dBFS.
+///
+/// ```
+/// use bladerf::dsp::{Nco, PowerSpectrum, Window};
+/// use num_complex::Complex32;
+///
+/// let mut samples = vec![Complex32::ZERO; 4096];
+/// Nco::new(250e3, 2e6).generate(&mut samples);
+///
+/// let mut spectrum = PowerSpectrum::new(1024, Window::Hann).unwrap();
+/// let power = spectrum.compute_db(&samples).unwrap();
+/// let peak = (0..power.len()).max_by(|&a, &b| power[a].total_cmp(&power[b])).unwrap();
+/// assert_eq!(spectrum.bin_frequency(peak, 2e6), 250e3);
+/// ```
+pub struct PowerSpectrum {
+    fft: Arc<dyn Fft<f32>>,
+    window: Window,
+    coefficients: Vec<f32>,
+    /// Converts the squared magnitudes to power relative to a full scale tone.
+    scale: f32,
+    buffer: Vec<Complex32>,
+    scratch: Vec<Complex32>,
+}
+
+impl std::fmt::Debug for PowerSpectrum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PowerSpectrum")
+            .field("size", &self.size())
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PowerSpectrum {
+    /// Spectra of `size` bins, with every block weighted by a periodic `window`.
+    ///
+    /// # Errors
+    /// `size` is zero.
+    pub fn new(size: usize, window: Window) -> Result<Self> {
+        if size == 0 {
+            return Err(Error::msg("Spectrum needs at least one bin"));
+        }
+        let fft = FftPlanner::new().plan_fft_forward(size);
+        let coefficients = window.periodic(size);
+        let gain: f32 = coefficients.iter().sum();
+
+        Ok(Self {
+            scratch: vec![Complex32::ZERO; fft.get_inplace_scratch_len()],
+            fft,
+            window,
+            coefficients,
+            scale: 1.0 / (gain * gain),
+            buffer: vec![Complex32::ZERO; size],
+        })
+    }
+
+    /// Number of bins.
+    pub fn size(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Frequency of bin `index` relative to the center frequency, for samples at `sample_rate` Hz.
+    pub fn bin_frequency(&self, index: usize, sample_rate: f64) -> f64 {
+        (index as f64 - (self.size() / 2) as f64) * sample_rate / self.size() as f64
+    }
+
+    /// Power of every bin, averaged over all complete blocks of [PowerSpectrum::size()] samples in `samples`.
+    ///
+    /// # Errors
+    /// `samples` is shorter than one block.
+    pub fn compute(&mut self, samples: &[Complex32]) -> Result<Vec<f32>> {
+        let size = self.size();
+        let blocks = samples.len() / size;
+        if blocks == 0 {
+            return Err(Error::msg(format!(
+                "Spectrum of {size} bins needs at least {size} samples"
+            )));
+        }
+
+        let scale = self.scale / blocks as f32;
+        let mut power = vec![0.0; size];
+        for block in samples.chunks_exact(size) {
+            for ((bin, sample), weight) in self.buffer.iter_mut().zip(block).zip(&self.coefficients)
+            {
+                *bin = sample * weight;
+            }
+            self.fft
+                .process_with_scratch(&mut self.buffer, &mut self.scratch);
+            // Swap the halves, so the negative frequencies come first
+            for (index, bin) in self.buffer.iter().enumerate() {
+                power[(index + size / 2) % size] += bin.norm_sqr() * scale;
+            }
+        }
+        Ok(power)
+    }
+
+    /// Power of every bin in dB, see [PowerSpectrum::compute()].
+    pub fn compute_db(&mut self, samples: &[Complex32]) -> Result<Vec<f32>> {
+        let mut power = self.compute(samples)?;
+        power.iter_mut().for_each(|power| *power = to_db(*power));
+        Ok(power)
+    }
+}
+
+/// Converts a power ratio to dB, mapping zero to a very low level rather than negative infinity.
+pub fn to_db(power: f32) -> f32 {
+    10.0 * (power + f32::MIN_POSITIVE).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::Nco;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn tone_on_bin() {
+        let mut tone = [Complex32::ZERO; 16];
+        Nco::new(1.0, 8.0).generate(&mut tone);
+
+        let mut rectangular = PowerSpectrum::new(8, Window::Rectangular).unwrap();
+        let expected = [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        assert_close(&rectangular.compute(&tone).unwrap(), &expected);
+
+        // The main lobe of the Hann window spreads a quarter of the power into either neighbour
+        let mut hann = PowerSpectrum::new(8, Window::Hann).unwrap();
+        let expected = [0.0, 0.0, 0.0, 0.0, 0.25, 1.0, 0.25, 0.0];
+        assert_close(&hann.compute(&tone).unwrap(), &expected);
+        assert_eq!(hann.bin_frequency(5, 8.0), 1.0);
+        assert!(hann.compute(&tone[..7]).is_err());
+    }
+
+    #[test]
+    fn averaging() {
+        // DC in the first block and a tone at -fs / 4 in the second
+        let mut samples = [Complex32::new(0.5, 0.0); 8];
+        Nco::new(-1.0, 4.0).generate(&mut samples[4..]);
+
+        let mut spectrum = PowerSpectrum::new(4, Window::Rectangular).unwrap();
+        assert_close(
+            &spectrum.compute(&samples).unwrap(),
+            &[0.0, 0.5, 0.125, 0.0],
+        );
+        let db = spectrum.compute_db(&samples).unwrap();
+        assert!((db[1] + 3.0103).abs() < 1e-3);
+        assert!(db[0] < -300.0);
+    }
+}
@@ -0,0 +1,70 @@
+use std::f64::consts::TAU;
+
+/// Window functions for filter design and spectral analysis.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Window {
+    /// No tapering, with the narrowest main lobe and the highest sidelobes.
+    Rectangular,
+    /// Raised cosine, a good default for power spectra.
+    #[default]
+    Hann,
+    /// Raised cosine with lower first sidelobes than Hann, the usual choice for FIR design.
+    Hamming,
+    /// Three-term cosine with low sidelobes and a wide main lobe.
+    Blackman,
+}
+
+impl Window {
+    /// Symmetric window of `len` points, as used for FIR design.
+    pub fn symmetric(self, len: usize) -> Vec<f32> {
+        self.coefficients(len, len.saturating_sub(1))
+    }
+
+    /// Periodic window of `len` points, as used for spectral analysis.
+    pub fn periodic(self, len: usize) -> Vec<f32> {
+        self.coefficients(len, len)
+    }
+
+    fn coefficients(self, len: usize, period: usize) -> Vec<f32> {
+        if period == 0 {
+            return vec![1.0; len];
+        }
+        (0..len)
+            .map(|n| {
+                let x = TAU * n as f64 / period as f64;
+                let w = match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 - 0.5 * x.cos(),
+                    Window::Hamming => 0.54 - 0.46 * x.cos(),
+                    Window::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+                };
+                w as f32
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-6, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn reference_windows() {
+        assert_close(&Window::Hann.periodic(4), &[0.0, 0.5, 1.0, 0.5]);
+        assert_close(&Window::Hann.symmetric(5), &[0.0, 0.5, 1.0, 0.5, 0.0]);
+        assert_close(
+            &Window::Hamming.symmetric(5),
+            &[0.08, 0.54, 1.0, 0.54, 0.08],
+        );
+        assert_close(&Window::Blackman.symmetric(5), &[0.0, 0.34, 1.0, 0.34, 0.0]);
+        assert_close(&Window::Rectangular.periodic(3), &[1.0; 3]);
+        assert_close(&Window::Hann.symmetric(1), &[1.0]);
+    }
+}
@@ -30,7 +30,7 @@ impl Xb200<'_> {
     pub fn set_filterbank(&self, direction: Direction, filter: Xb200Filter) -> Result<()> {
         let res = unsafe {
             bladerf_xb200_set_filterbank(
-                self.device.get_device_ptr(),
+                self.device.device_ptr()?,
                 direction as bladerf_channel,
                 filter as bladerf_xb200_filter,
             )
@@ -47,7 +47,7 @@ impl Xb200<'_> {
         let mut filter = bladerf_xb200_filter_BLADERF_XB200_CUSTOM;
         let res = unsafe {
             bladerf_xb200_get_filterbank(
-                self.device.get_device_ptr(),
+                self.device.device_ptr()?,
                 path as bladerf_channel,
                 &mut filter,
             )
@@ -63,7 +63,7 @@ impl Xb200<'_> {
     pub fn set_path(&self, direction: Direction, path: Xb200Path) -> Result<()> {
         let res = unsafe {
             bladerf_xb200_set_path(
-                self.device.get_device_ptr(),
+                self.device.device_ptr()?,
                 direction as bladerf_channel,
                 path as bladerf_xb200_path,
             )
@@ -80,7 +80,7 @@ impl Xb200<'_> {
         let mut path = bladerf_xb200_path_BLADERF_XB200_BYPASS;
         let res = unsafe {
             bladerf_xb200_get_path(
-                self.device.get_device_ptr(),
+                self.device.device_ptr()?,
                 direction as bladerf_channel,
                 &mut path,
             )
@@ -135,14 +135,14 @@ impl<D: BladeRF> OutputPin for XbGpioPin<'_, Output, D> {
 #[inline]
 fn gpio_read<D: BladeRF>(dev: &D) -> Result<u32> {
     let mut val = 0;
-    let result = unsafe { sys::bladerf_expansion_gpio_read(dev.get_device_ptr(), &mut val) };
+    let result = unsafe { sys::bladerf_expansion_gpio_read(dev.device_ptr()?, &mut val) };
     check_res!(result);
     Ok(val)
 }
 
 #[inline]
 fn _gpio_write<D: BladeRF>(dev: &D, val: u32) -> Result<()> {
-    let result = unsafe { sys::bladerf_expansion_gpio_write(dev.get_device_ptr(), val) };
+    let result = unsafe { sys::bladerf_expansion_gpio_write(dev.device_ptr()?, val) };
     check_res!(result);
     Ok(())
 }
@@ -150,7 +150,7 @@ fn _gpio_write<D: BladeRF>(dev: &D, val: u32) -> Result<()> {
 #[inline]
 fn gpio_masked_write<D: BladeRF>(dev: &D, mask: u32, value: u32) -> Result<()> {
     let result =
-        unsafe { sys::bladerf_expansion_gpio_masked_write(dev.get_device_ptr(), mask, value) };
+        unsafe { sys::bladerf_expansion_gpio_masked_write(dev.device_ptr()?, mask, value) };
     check_res!(result);
     Ok(())
 }
@@ -158,23 +158,22 @@ fn gpio_masked_write<D: BladeRF>(dev: &D, mask: u32, value: u32) -> Result<()> {
 #[inline]
 fn _gpio_dir_read<D: BladeRF>(dev: &D) -> Result<u32> {
     let mut dir = 0;
-    let result = unsafe { sys::bladerf_expansion_gpio_dir_read(dev.get_device_ptr(), &mut dir) };
+    let result = unsafe { sys::bladerf_expansion_gpio_dir_read(dev.device_ptr()?, &mut dir) };
     check_res!(result);
     Ok(dir)
 }
 
 #[inline]
 fn _gpio_dir_write<D: BladeRF>(dev: &D, outputs: u32) -> Result<()> {
-    let result = unsafe { sys::bladerf_expansion_gpio_dir_write(dev.get_device_ptr(), outputs) };
+    let result = unsafe { sys::bladerf_expansion_gpio_dir_write(dev.device_ptr()?, outputs) };
     check_res!(result);
     Ok(())
 }
 
 #[inline]
 fn gpio_dir_masked_write<D: BladeRF>(dev: &D, mask: u32, outputs: u32) -> Result<()> {
-    let result = unsafe {
-        sys::bladerf_expansion_gpio_dir_masked_write(dev.get_device_ptr(), mask, outputs)
-    };
+    let result =
+        unsafe { sys::bladerf_expansion_gpio_dir_masked_write(dev.device_ptr()?, mask, outputs) };
     check_res!(result);
     Ok(())
 }
@@ -0,0 +1,263 @@
+use crate::BladeRF;
+use crate::Channel;
+use crate::Direction;
+use crate::Error;
+use crate::Hz;
+use crate::QuickTune;
+use crate::Result;
+
+/// Number of hops kept scheduled ahead of the device by default.
+const DEFAULT_LOOKAHEAD: usize = 4;
+
+/// A frequency of a [FrequencyHopper] along with the precomputed tuning parameters for it.
+#[derive(Clone, Debug)]
+pub struct Hop {
+    /// Frequency in Hz.
+    pub frequency: u64,
+    /// Tuning parameters for `frequency` as reported by [BladeRF::get_quick_tune()].
+    pub quick_tune: QuickTune,
+}
+
+/// A hop which was skipped, because its timestamp had already passed when it was due to be scheduled.
+///
+/// The samples of the hop were transferred at the frequency of an earlier hop.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LateRetune {
+    /// Index of the hop since [FrequencyHopper::start()].
+    pub hop: u64,
+    /// Frequency of the hop.
+    pub frequency: u64,
+    /// Timestamp the hop was due at.
+    pub timestamp: u64,
+    /// Timestamp of the device when the hop was skipped.
+    pub device_timestamp: u64,
+}
+
+/// Hops a channel through a list of frequencies in lockstep with the sample timestamps of its stream.
+///
+/// The [QuickTune] parameters of every frequency are computed once up front, by tuning to each of them. Once started,
+/// hop `n` begins at `start + n * dwell` and the sequence repeats indefinitely. [FrequencyHopper::schedule()] needs to
+/// be called regularly, such as after every read or write, to keep the next few hops queued with
+/// [BladeRF::schedule_retune()]. Hops which could not be scheduled in time are skipped and reported as [LateRetune]s.
+///
+/// Obtained from [RxSyncStream::frequency_hopper()](crate::RxSyncStream::frequency_hopper) or
+/// [TxSyncStream::frequency_hopper()](crate::TxSyncStream::frequency_hopper), which need to be configured with
+/// [StreamConfig::with_metadata()](crate::StreamConfig::with_metadata) on the hardware.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRF, BladeRfAny, ChannelLayoutRx, ComplexI16, Direction, RxChannel, StreamConfig};
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let conf = StreamConfig::default().with_metadata(true);
+/// let rx_stream = dev.rx_streamer::<ComplexI16>(conf, ChannelLayoutRx::SISO(RxChannel::Rx0)).unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// let channels = [902_500_000, 910_000_000, 917_500_000, 925_000_000];
+/// let mut hopper = rx_stream.frequency_hopper(&channels, 50_000).unwrap();
+/// hopper.start(dev.get_timestamp(Direction::RX).unwrap() + 100_000).unwrap();
+///
+/// let mut buffer = vec![ComplexI16::ZERO; 10_000];
+/// loop {
+///     hopper.schedule().unwrap();
+///     let meta = rx_stream.read_with_meta(&mut buffer, None, Duration::from_secs(1)).unwrap();
+///     let frequency = hopper.frequency_at(meta.timestamp);
+///     for late in hopper.take_late_retunes() {
+///         eprintln!("Hop {} to {} Hz was late", late.hop, late.frequency);
+///     }
+/// }
+/// ```
+///
+/// Dropping the hopper cancels the retunes it still has pending.
+#[derive(Debug)]
+pub struct FrequencyHopper<'d, D: BladeRF> {
+    dev: &'d D,
+    channel: Channel,
+    hops: Vec<Hop>,
+    dwell: u64,
+    lookahead: usize,
+    /// Timestamp of the first hop, [None] until started.
+    start: Option<u64>,
+    /// Index of the next hop to schedule.
+    next: u64,
+    late: Vec<LateRetune>,
+}
+
+impl<'d, D: BladeRF> FrequencyHopper<'d, D> {
+    /// Computes the [QuickTune] parameters of `frequencies` on `channel`, which is left tuned to its current frequency.
+    ///
+    /// Takes the frequencies either as `u64` in Hz or as [Hz].
+    ///
+    /// # Errors
+    /// - `frequencies` is empty or `dwell` is zero.
+    /// - A frequency is out of range for the channel.
+    pub fn new(
+        dev: &'d D,
+        channel: Channel,
+        frequencies: &[impl Into<Hz> + Copy],
+        dwell: u64,
+    ) -> Result<Self> {
+        if frequencies.is_empty() {
+            return Err(Error::msg("Hop sequence needs at least one frequency"));
+        }
+        if dwell == 0 {
+            return Err(Error::msg("Dwell time needs to be at least one sample"));
+        }
+
+        Ok(Self {
+            dev,
+            channel,
+            hops: compute_hops(dev, channel, frequencies.iter().map(|&f| f.into().0))?,
+            dwell,
+            lookahead: DEFAULT_LOOKAHEAD,
+            start: None,
+            next: 0,
+            late: Vec::new(),
+        })
+    }
+
+    /// Sets how many hops are kept scheduled ahead of the device, 4 by default.
+    ///
+    /// More hops tolerate longer gaps between calls to [FrequencyHopper::schedule()], but all of them need to fit into
+    /// the retune queue of the device, which holds 16 on the bladeRF 2.0.
+    pub fn with_lookahead(mut self, lookahead: usize) -> Self {
+        self.lookahead = lookahead.max(1);
+        self
+    }
+
+    /// The hops of the sequence, with their precomputed tuning parameters.
+    pub fn hops(&self) -> &[Hop] {
+        &self.hops
+    }
+
+    /// Number of samples spent on each hop.
+    pub fn dwell(&self) -> u64 {
+        self.dwell
+    }
+
+    /// Starts the sequence with the first hop at `timestamp`, cancelling any retunes pending on the channel.
+    pub fn start(&mut self, timestamp: u64) -> Result<()> {
+        self.dev.cancel_scheduled_retune(self.channel)?;
+        self.start = Some(timestamp);
+        self.next = 0;
+        self.late.clear();
+        self.schedule().map(|_| ())
+    }
+
+    /// Schedules the hops due within the lookahead of the current device timestamp, returning how many were scheduled.
+    ///
+    /// Hops whose timestamp already passed are skipped and reported as [LateRetune]s, so the sequence continues with the
+    /// first hop at or after the current timestamp. Stops early without an error if the retune queue of the device is
+    /// full.
+    ///
+    /// # Errors
+    /// The hopper was not started.
+    pub fn schedule(&mut self) -> Result<usize> {
+        let start = self
+            .start
+            .ok_or_else(|| Error::msg("Frequency hopper has not been started"))?;
+        let direction = if self.channel.is_rx() {
+            Direction::RX
+        } else {
+            Direction::TX
+        };
+        let now = self.dev.get_timestamp(direction)?;
+        let horizon = now.saturating_add(self.lookahead as u64 * self.dwell);
+
+        // Retuning to hops in the past would only delay the ones still ahead
+        let first_due = now.saturating_sub(start).div_ceil(self.dwell);
+        while self.next < first_due {
+            let index = (self.next % self.hops.len() as u64) as usize;
+            self.late.push(LateRetune {
+                hop: self.next,
+                frequency: self.hops[index].frequency,
+                timestamp: start + self.next * self.dwell,
+                device_timestamp: now,
+            });
+            self.next += 1;
+        }
+
+        let mut scheduled = 0;
+        loop {
+            let timestamp = start + self.next * self.dwell;
+            if timestamp > horizon {
+                return Ok(scheduled);
+            }
+
+            let index = (self.next % self.hops.len() as u64) as usize;
+            let hop = &mut self.hops[index];
+            let res = self.dev.schedule_retune(
+                self.channel,
+                timestamp,
+                hop.frequency,
+                Some(&mut hop.quick_tune),
+            );
+            match res {
+                Ok(()) => {}
+                Err(Error::QueueFull) => return Ok(scheduled),
+                Err(e) => return Err(e),
+            }
+            self.next += 1;
+            scheduled += 1;
+        }
+    }
+
+    /// Index of the hop the sample at `timestamp` belongs to, [None] before the first hop or if not started.
+    pub fn hop_at(&self, timestamp: u64) -> Option<u64> {
+        let start = self.start?;
+        timestamp
+            .checked_sub(start)
+            .map(|offset| offset / self.dwell)
+    }
+
+    /// Frequency the sample at `timestamp` was scheduled to be transferred at, [None] before the first hop.
+    pub fn frequency_at(&self, timestamp: u64) -> Option<u64> {
+        self.hop_at(timestamp)
+            .map(|hop| self.hops[(hop % self.hops.len() as u64) as usize].frequency)
+    }
+
+    /// The retunes scheduled too late since the hopper was started.
+    pub fn late_retunes(&self) -> &[LateRetune] {
+        &self.late
+    }
+
+    /// Returns and clears the retunes scheduled too late.
+    pub fn take_late_retunes(&mut self) -> Vec<LateRetune> {
+        std::mem::take(&mut self.late)
+    }
+
+    /// Cancels the pending retunes and stops the sequence until it is started again.
+    pub fn stop(&mut self) -> Result<()> {
+        self.start = None;
+        self.dev.cancel_scheduled_retune(self.channel)
+    }
+}
+
+/// Computes the [QuickTune] parameters of `frequencies` by tuning `channel` to each, then back to its current frequency.
+pub(crate) fn compute_hops<D: BladeRF>(
+    dev: &D,
+    channel: Channel,
+    frequencies: impl IntoIterator<Item = u64>,
+) -> Result<Vec<Hop>> {
+    let current = dev.get_frequency(channel)?;
+    let hops = frequencies
+        .into_iter()
+        .map(|frequency| {
+            dev.set_frequency(channel, frequency)?;
+            let quick_tune = dev.get_quick_tune(channel)?;
+            Ok(Hop {
+                frequency,
+                quick_tune,
+            })
+        })
+        .collect::<Result<Vec<_>>>();
+    dev.set_frequency(channel, current)?;
+    hops
+}
+
+impl<D: BladeRF> Drop for FrequencyHopper<'_, D> {
+    fn drop(&mut self) {
+        if self.start.is_some() {
+            let _ = self.dev.cancel_scheduled_retune(self.channel);
+        }
+    }
+}
@@ -10,6 +10,7 @@ compile_error!("This library only supports little endian architecture");
 mod error;
 
 pub use error::{Error, Result};
+mod rng;
 mod types;
 pub use types::*;
 #[macro_use]
@@ -21,6 +22,41 @@ mod bladerf2;
 pub use bladerf2::*;
 mod streamers;
 pub use streamers::*;
+mod trigger;
+pub use trigger::*;
+mod sync;
+pub use sync::*;
+mod simulated;
+pub use simulated::*;
+#[cfg(feature = "sigmf")]
+mod sigmf;
+#[cfg(any(feature = "sigmf", feature = "sweep"))]
+mod time;
+#[cfg(feature = "sigmf")]
+pub use sigmf::*;
+mod playback;
+pub use playback::*;
+mod hopping;
+pub use hopping::*;
+mod waveform;
+pub use waveform::*;
+mod ook;
+pub use ook::*;
+mod profile;
+pub use profile::*;
+mod snapshot;
+pub use snapshot::*;
+#[cfg(feature = "sweep")]
+mod sweep;
+#[cfg(feature = "sweep")]
+pub use sweep::*;
+#[cfg(feature = "seify")]
+mod seify_device;
+#[cfg(feature = "seify")]
+pub use seify_device::*;
+
+#[cfg(feature = "dsp")]
+pub mod dsp;
 
 pub mod expansion_boards;
 
@@ -0,0 +1,545 @@
+use std::collections::VecDeque;
+
+use num_complex::Complex32;
+
+use crate::ComplexI16;
+use crate::Error;
+use crate::Result;
+use crate::Waveform;
+use crate::BRF_CI16_SAMPLE_MAX;
+
+/// Parameters of the on-off keying shared by an [OokModulator] and an [OokDemodulator].
+///
+/// Every frame on air consists of
+/// - a preamble of alternating bits starting with a 1, which lets the demodulator settle its threshold and bit clock,
+/// - a 16 bit sync word marking the start of the frame,
+/// - the length of the payload as a single byte,
+/// - the payload,
+/// - a CRC-16/CCITT-FALSE over the length and the payload.
+///
+/// Everything is sent most significant bit first, with the carrier on for a 1 and off for a 0.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OokConfig {
+    pub(crate) samples_per_bit: usize,
+    pub(crate) preamble_bits: usize,
+    pub(crate) sync_word: u16,
+    pub(crate) amplitude: i16,
+}
+
+impl OokConfig {
+    /// Configuration with bits of `samples_per_bit` samples, so the bit rate is the sample rate divided by it.
+    ///
+    /// # Errors
+    /// `samples_per_bit` is below 4, which is too short to recover the bit clock.
+    pub fn new(samples_per_bit: usize) -> Result<Self> {
+        if samples_per_bit < 4 {
+            return Err(Error::msg("OOK bits need at least 4 samples"));
+        }
+        Ok(Self {
+            samples_per_bit,
+            preamble_bits: 32,
+            sync_word: 0x2dd4,
+            amplitude: BRF_CI16_SAMPLE_MAX,
+        })
+    }
+
+    /// Sets the length of the preamble, 32 bits by default.
+    pub fn with_preamble_bits(mut self, bits: usize) -> Self {
+        self.preamble_bits = bits;
+        self
+    }
+
+    /// Sets the sync word, `0x2DD4` by default.
+    ///
+    /// It should not look like the preamble, so the start of the frame is not found within it.
+    pub fn with_sync_word(mut self, sync_word: u16) -> Self {
+        self.sync_word = sync_word;
+        self
+    }
+
+    /// Sets the amplitude of the carrier, full scale by default.
+    pub fn with_amplitude(mut self, amplitude: i16) -> Self {
+        self.amplitude = amplitude.clamp(0, BRF_CI16_SAMPLE_MAX);
+        self
+    }
+
+    /// Number of samples per bit.
+    pub fn samples_per_bit(&self) -> usize {
+        self.samples_per_bit
+    }
+
+    /// The bits of the frame carrying `payload`.
+    fn frame_bits(&self, payload: &[u8]) -> Result<Vec<bool>> {
+        let len = u8::try_from(payload.len())
+            .map_err(|_| Error::msg("OOK payloads are limited to 255 bytes"))?;
+        let mut body = Vec::with_capacity(payload.len() + 3);
+        body.push(len);
+        body.extend_from_slice(payload);
+        let crc = crc16(&body);
+
+        let preamble = (0..self.preamble_bits).map(|bit| bit % 2 == 0);
+        let bytes = self
+            .sync_word
+            .to_be_bytes()
+            .into_iter()
+            .chain(body)
+            .chain(crc.to_be_bytes());
+        Ok(preamble
+            .chain(bytes.flat_map(|byte| (0..8).rev().map(move |bit| byte >> bit & 1 == 1)))
+            .collect())
+    }
+}
+
+/// CRC-16/CCITT-FALSE, with the polynomial 0x1021 and an initial value of 0xFFFF.
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xffff, |crc, &byte| {
+        (0..8).fold(crc ^ (byte as u16) << 8, |crc, _| {
+            if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Turns payloads into on-off keyed frames of [ComplexI16] samples, see [OokConfig] for the frame format.
+///
+/// Frames are queued with [OokModulator::send()] and come out of [OokModulator::modulate()] back to back, with the
+/// carrier off while there is nothing to send. It is also a [Waveform], for use with
+/// [TxSyncStream::transmit_from()](crate::TxSyncStream::transmit_from).
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutTx, ComplexI16, OokConfig, OokModulator, StreamConfig, TxChannel};
+///
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let tx_stream = dev
+///     .tx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutTx::SISO(TxChannel::Tx0))
+///     .unwrap();
+/// tx_stream.enable().unwrap();
+///
+/// // 100 bits per second at 2 MHz
+/// let mut modulator = OokModulator::new(OokConfig::new(20_000).unwrap());
+/// modulator.send(b"Hello").unwrap();
+/// let mut block = vec![ComplexI16::ZERO; 4096];
+/// while !modulator.is_idle() {
+///     modulator.modulate(&mut block);
+///     tx_stream.write(&block, Duration::from_secs(1)).unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OokModulator {
+    config: OokConfig,
+    bits: VecDeque<bool>,
+    /// Samples already sent of the first bit in `bits`.
+    position: usize,
+}
+
+impl OokModulator {
+    /// Idle modulator.
+    pub fn new(config: OokConfig) -> Self {
+        Self {
+            config,
+            bits: VecDeque::new(),
+            position: 0,
+        }
+    }
+
+    /// The configuration of the modulator.
+    pub fn config(&self) -> &OokConfig {
+        &self.config
+    }
+
+    /// Queues a frame carrying `payload`.
+    ///
+    /// # Errors
+    /// `payload` is longer than 255 bytes.
+    pub fn send(&mut self, payload: &[u8]) -> Result<()> {
+        let bits = self.config.frame_bits(payload)?;
+        self.bits.extend(bits);
+        Ok(())
+    }
+
+    /// Tests if all queued frames were modulated.
+    pub fn is_idle(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Number of samples left of the queued frames.
+    pub fn pending_samples(&self) -> usize {
+        (self.bits.len() * self.config.samples_per_bit).saturating_sub(self.position)
+    }
+
+    /// Fills `block` with the next samples of the queued frames, followed by zeros once they run out.
+    ///
+    /// Returns the number of samples which belong to frames.
+    pub fn modulate(&mut self, block: &mut [ComplexI16]) -> usize {
+        let on = ComplexI16::new(self.config.amplitude, 0);
+        let mut filled = 0;
+        while filled < block.len() {
+            let Some(&bit) = self.bits.front() else {
+                break;
+            };
+            let len = (self.config.samples_per_bit - self.position).min(block.len() - filled);
+            block[filled..filled + len].fill(if bit { on } else { ComplexI16::ZERO });
+            filled += len;
+            self.position += len;
+            if self.position == self.config.samples_per_bit {
+                self.bits.pop_front();
+                self.position = 0;
+            }
+        }
+        block[filled..].fill(ComplexI16::ZERO);
+        filled
+    }
+}
+
+impl Waveform for OokModulator {
+    fn generate(&mut self, block: &mut [Complex32]) {
+        let mut raw = vec![ComplexI16::ZERO; block.len()];
+        self.modulate(&mut raw);
+        crate::brf_ci16_to_cf32_slice(&raw, block);
+    }
+}
+
+/// A frame received by an [OokDemodulator].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OokFrame {
+    /// The payload of the frame.
+    pub payload: Vec<u8>,
+    /// Index of the sample after the end of the frame, counting every sample passed to the demodulator.
+    pub end: u64,
+}
+
+/// Counters of an [OokDemodulator].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OokStats {
+    /// Samples processed.
+    pub samples: u64,
+    /// Bits recovered, including noise while no frame was received.
+    pub bits: u64,
+    /// Frames received intact.
+    pub frames: u64,
+    /// Frames discarded because their CRC did not match.
+    pub crc_errors: u64,
+}
+
+/// Where the demodulator is within a frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FrameState {
+    /// Looking for the sync word.
+    Hunting,
+    /// Receiving the length byte, payload and CRC, expecting `bits` bits in total.
+    Receiving { bits: usize },
+}
+
+/// Recovers frames sent by an [OokModulator] from blocks of [ComplexI16] samples, see [OokConfig] for the frame format.
+///
+/// Processing happens in three stages:
+/// - The magnitude of every sample is compared to a threshold halfway between an envelope following the peaks and one
+///   following the troughs of the signal, so the threshold adapts to the received power. Below the squelch level, the
+///   carrier is considered off.
+/// - The bit clock is recovered by nudging the expected bit boundary towards every edge of the keyed signal, and every
+///   bit is decided by the majority of the samples in the middle half of it.
+/// - Frames are found by their sync word and only kept if their CRC matches.
+///
+/// Since all state is kept between calls, the samples can be passed in blocks of any size.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutRx, ComplexI16, OokConfig, OokDemodulator, RxChannel, StreamConfig};
+///
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let rx_stream = dev
+///     .rx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutRx::SISO(RxChannel::Rx0))
+///     .unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// let mut demodulator = OokDemodulator::new(OokConfig::new(20_000).unwrap());
+/// let mut block = vec![ComplexI16::ZERO; 4096];
+/// loop {
+///     rx_stream.read(&mut block, Duration::from_secs(1)).unwrap();
+///     for frame in demodulator.process(&block) {
+///         println!("{}", String::from_utf8_lossy(&frame.payload));
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OokDemodulator {
+    config: OokConfig,
+    squelch: f32,
+    /// Smoothing of the envelopes per sample.
+    release: f32,
+    high: f32,
+    low: f32,
+    /// Whether the carrier was on in the previous sample.
+    level: bool,
+    /// Index of the current sample within the bit.
+    position: usize,
+    /// Samples in the middle half of the current bit and how many of them were on.
+    window: usize,
+    ones: usize,
+    state: FrameState,
+    /// The most recently received bits, newest in the lowest bit.
+    shift: u16,
+    bytes: Vec<u8>,
+    bits_in_byte: usize,
+    stats: OokStats,
+}
+
+impl OokDemodulator {
+    /// Demodulator with a squelch level of 64, which is about 30 dB below full scale.
+    pub fn new(config: OokConfig) -> Self {
+        Self {
+            config,
+            squelch: 64.0,
+            // Peaks are followed at once, but the envelopes hold over runs of a few bytes without edges
+            release: 1.0 / (32 * config.samples_per_bit) as f32,
+            high: 0.0,
+            low: 0.0,
+            level: false,
+            position: 0,
+            window: 0,
+            ones: 0,
+            state: FrameState::Hunting,
+            shift: 0,
+            bytes: Vec::new(),
+            bits_in_byte: 0,
+            stats: OokStats::default(),
+        }
+    }
+
+    /// Sets the difference between the on and off magnitudes below which the carrier is considered off.
+    pub fn with_squelch(mut self, squelch: f32) -> Self {
+        self.squelch = squelch;
+        self
+    }
+
+    /// The configuration of the demodulator.
+    pub fn config(&self) -> &OokConfig {
+        &self.config
+    }
+
+    /// The current decision threshold on the sample magnitude.
+    pub fn threshold(&self) -> f32 {
+        (self.high + self.low) / 2.0
+    }
+
+    /// The counters since the demodulator was created.
+    pub fn stats(&self) -> OokStats {
+        self.stats
+    }
+
+    /// Processes the next block of samples and returns the frames which ended within it.
+    pub fn process(&mut self, block: &[ComplexI16]) -> Vec<OokFrame> {
+        let mut frames = Vec::new();
+        for sample in block {
+            self.stats.samples += 1;
+            let magnitude = (sample.re as f32).hypot(sample.im as f32);
+            let level = self.slice(magnitude);
+            if let Some(bit) = self.clock(level) {
+                if let Some(payload) = self.deframe(bit) {
+                    frames.push(OokFrame {
+                        payload,
+                        end: self.stats.samples,
+                    });
+                }
+            }
+        }
+        frames
+    }
+
+    /// Updates the envelopes with `magnitude` and decides if the carrier is on.
+    fn slice(&mut self, magnitude: f32) -> bool {
+        if magnitude > self.high {
+            self.high = magnitude;
+        } else {
+            self.high += (magnitude - self.high) * self.release;
+        }
+        if magnitude < self.low {
+            self.low = magnitude;
+        } else {
+            self.low += (magnitude - self.low) * self.release;
+        }
+        self.high - self.low >= self.squelch && magnitude > self.threshold()
+    }
+
+    /// Tracks the bit clock, returning the bit which ended with this sample, if any.
+    fn clock(&mut self, level: bool) -> Option<bool> {
+        let samples_per_bit = self.config.samples_per_bit;
+        if level != self.level {
+            self.level = level;
+            // The edge belongs to the start of a bit, move halfway towards it
+            let position = self.position;
+            self.position = if position < samples_per_bit / 2 {
+                position - position / 2
+            } else {
+                position + (samples_per_bit - position) / 2
+            };
+        }
+
+        if (samples_per_bit / 4..samples_per_bit * 3 / 4).contains(&self.position) {
+            self.window += 1;
+            self.ones += level as usize;
+        }
+        self.position += 1;
+        if self.position < samples_per_bit {
+            return None;
+        }
+
+        let bit = if self.window == 0 {
+            level
+        } else {
+            self.ones * 2 > self.window
+        };
+        self.position = 0;
+        self.window = 0;
+        self.ones = 0;
+        self.stats.bits += 1;
+        Some(bit)
+    }
+
+    /// Feeds `bit` to the frame state machine, returning the payload of a frame which ended with it.
+    fn deframe(&mut self, bit: bool) -> Option<Vec<u8>> {
+        self.shift = self.shift << 1 | bit as u16;
+        match self.state {
+            FrameState::Hunting => {
+                if self.shift == self.config.sync_word {
+                    self.state = FrameState::Receiving { bits: 8 };
+                    self.bytes.clear();
+                    self.bits_in_byte = 0;
+                }
+                None
+            }
+            FrameState::Receiving { bits } => {
+                self.bits_in_byte += 1;
+                if self.bits_in_byte < 8 {
+                    return None;
+                }
+                self.bits_in_byte = 0;
+                self.bytes.push(self.shift as u8);
+                if self.bytes.len() == 1 {
+                    // Length byte, followed by the payload and the CRC
+                    self.state = FrameState::Receiving {
+                        bits: (self.bytes[0] as usize + 3) * 8,
+                    };
+                    return None;
+                }
+                if self.bytes.len() * 8 < bits {
+                    return None;
+                }
+
+                self.state = FrameState::Hunting;
+                let (body, crc) = self.bytes.split_at(self.bytes.len() - 2);
+                if crc16(body) != u16::from_be_bytes([crc[0], crc[1]]) {
+                    self.stats.crc_errors += 1;
+                    return None;
+                }
+                self.stats.frames += 1;
+                Some(body[1..].to_vec())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Noise;
+
+    fn modulate(modulator: &mut OokModulator) -> Vec<ComplexI16> {
+        let mut samples = vec![ComplexI16::ZERO; modulator.pending_samples()];
+        modulator.modulate(&mut samples);
+        samples
+    }
+
+    #[test]
+    fn reference_crc() {
+        assert_eq!(crc16(b"123456789"), 0x29b1);
+    }
+
+    #[test]
+    fn frame_layout() {
+        let config = OokConfig::new(4).unwrap().with_preamble_bits(4);
+        let mut modulator = OokModulator::new(config.with_amplitude(1000));
+        modulator.send(&[0x81]).unwrap();
+        // 4 preamble bits, 2 sync bytes, length, payload and 2 CRC bytes
+        assert_eq!(modulator.pending_samples(), (4 + 6 * 8) * 4);
+
+        let bits: Vec<_> = modulate(&mut modulator)
+            .chunks(4)
+            .map(|bit| bit[0].re == 1000)
+            .collect();
+        let byte = |bits: &[bool]| bits.iter().fold(0u8, |byte, &bit| byte << 1 | bit as u8);
+        assert_eq!(bits[..4], [true, false, true, false]);
+        assert_eq!(byte(&bits[4..12]), 0x2d);
+        assert_eq!(byte(&bits[12..20]), 0xd4);
+        assert_eq!(byte(&bits[20..28]), 1);
+        assert_eq!(byte(&bits[28..36]), 0x81);
+        assert_eq!(
+            u16::from_be_bytes([byte(&bits[36..44]), byte(&bits[44..52])]),
+            crc16(&[1, 0x81])
+        );
+        assert!(modulator.is_idle());
+        assert!(modulator.send(&[0; 256]).is_err());
+    }
+
+    #[test]
+    fn loopback() {
+        let config = OokConfig::new(50).unwrap();
+        let mut modulator = OokModulator::new(config.with_amplitude(800));
+        modulator.send(b"Hello").unwrap();
+        modulator.send(b"").unwrap();
+        modulator
+            .send(&(0..=255).collect::<Vec<u8>>()[..255])
+            .unwrap();
+        let mut samples = vec![ComplexI16::ZERO; 1234];
+        samples.extend(modulate(&mut modulator));
+        samples.extend([ComplexI16::ZERO; 500]);
+
+        // Add noise and a DC offset, then feed the samples in odd sized blocks
+        let mut noise = vec![Complex32::ZERO; samples.len()];
+        Noise::new(0.02, 3).generate(&mut noise);
+        for (sample, noise) in samples.iter_mut().zip(noise) {
+            sample.re += (noise.re * 2048.0) as i16 + 40;
+            sample.im += (noise.im * 2048.0) as i16;
+        }
+        let mut demodulator = OokDemodulator::new(config);
+        let frames: Vec<_> = samples
+            .chunks(777)
+            .flat_map(|block| demodulator.process(block))
+            .collect();
+
+        let payloads: Vec<_> = frames
+            .iter()
+            .map(|frame| frame.payload.as_slice())
+            .collect();
+        assert_eq!(
+            payloads,
+            [&b"Hello"[..], &[], &(0..255).collect::<Vec<u8>>()]
+        );
+        let first_end = 1234 + (32 + 16 + 8 + 5 * 8 + 16) * 50;
+        assert!(
+            frames[0].end.abs_diff(first_end as u64) < 50,
+            "{}",
+            frames[0].end
+        );
+        assert_eq!(demodulator.stats().crc_errors, 0);
+        assert!(demodulator.threshold() < 400.0);
+    }
+
+    #[test]
+    fn clock_drift() {
+        // The transmitter runs 2% slow, which would slip a bit every 50 bits without clock recovery
+        let mut modulator = OokModulator::new(OokConfig::new(51).unwrap());
+        let payload = b"The quick brown fox jumps over the lazy dog";
+        modulator.send(payload).unwrap();
+        let samples = modulate(&mut modulator);
+
+        let mut demodulator = OokDemodulator::new(OokConfig::new(50).unwrap());
+        let frames = demodulator.process(&samples);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].payload, payload);
+    }
+}
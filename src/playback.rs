@@ -0,0 +1,334 @@
+//! Transmission of recorded IQ files through a [TxSyncStream].
+
+use std::borrow::Borrow;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use num_complex::Complex32;
+
+#[cfg(feature = "sigmf")]
+use crate::sigmf::with_suffix;
+use crate::streamers::TxSyncStream;
+#[cfg(feature = "sigmf")]
+use crate::SigMfMetadata;
+use crate::{
+    brf_cf32_to_ci16_slice, ci16_to_format, zeroed_samples, BladeRF, ChannelLayoutTx, ComplexI16,
+    Error, Result, SampleFormat, TxChannel,
+};
+
+/// Sample formats of IQ files which can be played back.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IqFileFormat {
+    /// Interleaved signed 8 bit I and Q, scaled like [ComplexI8](crate::ComplexI8) (SigMF `ci8`).
+    Cs8,
+    /// Interleaved signed 16 bit little endian I and Q, scaled like [ComplexI16] (SigMF `ci16_le`).
+    ///
+    /// This is what [SigMfRecorder](crate::SigMfRecorder) and the `rx_file` example write, so samples are used as is
+    /// and only the lower 12 bits are significant.
+    Cs16,
+    /// Interleaved 32 bit little endian float I and Q in [-1.0, 1.0) (SigMF `cf32_le`).
+    Cf32,
+}
+
+impl IqFileFormat {
+    /// Looks up the format for a SigMF `core:datatype`.
+    ///
+    /// # Errors
+    /// [Error::Unsupported] for data types other than `ci8`, `ci16_le` and `cf32_le`.
+    #[cfg(feature = "sigmf")]
+    pub fn from_sigmf_datatype(datatype: &str) -> Result<Self> {
+        match datatype {
+            "ci8" => Ok(Self::Cs8),
+            "ci16_le" => Ok(Self::Cs16),
+            "cf32_le" => Ok(Self::Cf32),
+            _ => Err(Error::Unsupported),
+        }
+    }
+
+    /// Size of one sample in the file in bytes.
+    pub fn sample_size(&self) -> usize {
+        match self {
+            Self::Cs8 => 2,
+            Self::Cs16 => 4,
+            Self::Cf32 => 8,
+        }
+    }
+
+    /// Decodes `bytes` into samples in the device format.
+    fn decode(&self, bytes: &[u8], dst: &mut Vec<ComplexI16>) {
+        dst.clear();
+        match self {
+            // Q7 to Q11
+            Self::Cs8 => {
+                dst.extend(bytes.chunks_exact(2).map(|iq| {
+                    ComplexI16::new((iq[0] as i8 as i16) << 4, (iq[1] as i8 as i16) << 4)
+                }))
+            }
+            Self::Cs16 => dst.extend(bytes.chunks_exact(4).map(|iq| {
+                ComplexI16::new(
+                    i16::from_le_bytes([iq[0], iq[1]]),
+                    i16::from_le_bytes([iq[2], iq[3]]),
+                )
+            })),
+            Self::Cf32 => {
+                let samples: Vec<Complex32> = bytes
+                    .chunks_exact(8)
+                    .map(|iq| {
+                        Complex32::new(
+                            f32::from_le_bytes([iq[0], iq[1], iq[2], iq[3]]),
+                            f32::from_le_bytes([iq[4], iq[5], iq[6], iq[7]]),
+                        )
+                    })
+                    .collect();
+                dst.resize(samples.len(), ComplexI16::ZERO);
+                brf_cf32_to_ci16_slice(&samples, dst);
+            }
+        }
+    }
+}
+
+/// How often a [Playback] transmits the file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Repeat {
+    /// Transmit the file the given number of times.
+    Count(u32),
+    /// Transmit the file until stopped through [Playback::play_until()].
+    Forever,
+}
+
+/// Transmits a SigMF recording or a raw IQ file through a [TxSyncStream].
+///
+/// The samples are converted from the [IqFileFormat] of the file to the [SampleFormat] of the stream and written in
+/// chunks of the stream's buffer size. When repeating, the end of the file is followed directly by its start within the
+/// same chunk, so there are no gaps between repetitions.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutTx, ComplexI16, IqFileFormat, Playback, StreamConfig, TxChannel};
+/// let dev = BladeRfAny::open_first().unwrap();
+///
+/// let mut playback = Playback::open_raw("capture.cf32", IqFileFormat::Cf32)
+///     .unwrap()
+///     .with_repeat(3);
+///
+/// let tx_stream = dev
+///     .tx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutTx::SISO(TxChannel::Tx0))
+///     .unwrap();
+/// tx_stream.enable().unwrap();
+/// playback.play(&tx_stream, Duration::from_secs(1)).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Playback {
+    path: PathBuf,
+    file: BufReader<File>,
+    format: IqFileFormat,
+    num_samples: u64,
+    num_channels: usize,
+    sample_rate: Option<u32>,
+    frequency: Option<u64>,
+    repeat: Repeat,
+}
+
+impl Playback {
+    /// Opens a raw IQ file without any metadata.
+    ///
+    /// Samples of MIMO streams are expected to be interleaved per channel.
+    pub fn open_raw(path: impl AsRef<Path>, format: IqFileFormat) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let file = File::open(&path)
+            .map_err(|e| Error::msg(format!("Failed to open {}: {e}", path.display())))?;
+        let len = file
+            .metadata()
+            .map_err(|e| Error::msg(format!("Failed to read {}: {e}", path.display())))?
+            .len();
+
+        Ok(Self {
+            path,
+            file: BufReader::new(file),
+            format,
+            num_samples: len / format.sample_size() as u64,
+            num_channels: 1,
+            sample_rate: None,
+            frequency: None,
+            repeat: Repeat::Count(1),
+        })
+    }
+
+    /// Opens a SigMF recording.
+    ///
+    /// `path` may be the `.sigmf-meta` file, the `.sigmf-data` file or the name of the recording without extension.
+    ///
+    /// ```no_run
+    /// use bladerf::{BladeRfAny, Playback, TxChannel};
+    /// let dev = BladeRfAny::open_first().unwrap();
+    ///
+    /// let playback = Playback::open_sigmf("capture").unwrap();
+    /// // Tune to the frequency and sample rate of the recording
+    /// playback.configure(&dev, TxChannel::Tx0).unwrap();
+    /// ```
+    #[cfg(feature = "sigmf")]
+    pub fn open_sigmf(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let base = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("sigmf-meta" | "sigmf-data") => path.with_extension(""),
+            _ => path.to_owned(),
+        };
+
+        let meta = SigMfMetadata::read(with_suffix(&base, ".sigmf-meta"))?;
+        let format = IqFileFormat::from_sigmf_datatype(&meta.datatype)?;
+        let mut playback = Self::open_raw(with_suffix(&base, ".sigmf-data"), format)?;
+        playback.num_channels = meta.num_channels;
+        playback.sample_rate = meta.sample_rate.map(|rate| rate.round() as u32);
+        playback.frequency = meta.frequency.map(|frequency| frequency.round() as u64);
+        Ok(playback)
+    }
+
+    /// Transmits the file `count` times. Defaults to once.
+    pub fn with_repeat(mut self, count: u32) -> Self {
+        self.repeat = Repeat::Count(count);
+        self
+    }
+
+    /// Transmits the file over and over, until stopped through [Playback::play_until()].
+    pub fn looping(mut self) -> Self {
+        self.repeat = Repeat::Forever;
+        self
+    }
+
+    /// The format of the samples in the file.
+    pub fn format(&self) -> IqFileFormat {
+        self.format
+    }
+
+    /// Number of samples in the file, counting all channels.
+    pub fn num_samples(&self) -> u64 {
+        self.num_samples
+    }
+
+    /// Sample rate from the SigMF metadata.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+    }
+
+    /// Center frequency of the first capture segment from the SigMF metadata.
+    pub fn frequency(&self) -> Option<u64> {
+        self.frequency
+    }
+
+    /// Tunes `channel` to the frequency and sample rate of the recording, where the metadata provides them.
+    pub fn configure<D: BladeRF>(&self, dev: &D, channel: TxChannel) -> Result<()> {
+        if let Some(frequency) = self.frequency {
+            dev.set_frequency(channel.into(), frequency)?;
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            dev.set_sample_rate(channel.into(), sample_rate)?;
+        }
+        Ok(())
+    }
+
+    /// Transmits the file through `stream` as often as configured and returns the number of samples written.
+    ///
+    /// With [Repeat::Forever] this only returns on errors, use [Playback::play_until()] instead.
+    ///
+    /// # Errors
+    /// - The stream was configured with [StreamConfig::with_metadata()](crate::StreamConfig::with_metadata).
+    /// - The number of channels of the recording does not match the layout of the stream.
+    pub fn play<T: Borrow<D>, F: SampleFormat, D: BladeRF>(
+        &mut self,
+        stream: &TxSyncStream<T, F, D>,
+        timeout: Duration,
+    ) -> Result<u64> {
+        self.play_until(stream, timeout, |_| false)
+    }
+
+    /// Same as [Playback::play()], but calls `stop` with the number of samples written so far after every chunk and
+    /// returns early once it returns `true`.
+    pub fn play_until<T: Borrow<D>, F: SampleFormat, D: BladeRF>(
+        &mut self,
+        stream: &TxSyncStream<T, F, D>,
+        timeout: Duration,
+        mut stop: impl FnMut(u64) -> bool,
+    ) -> Result<u64> {
+        if stream.config.metadata {
+            return Err(Error::msg(
+                "Playback requires a stream configured without metadata",
+            ));
+        }
+        let stream_channels = match stream.layout {
+            ChannelLayoutTx::SISO(_) => 1,
+            ChannelLayoutTx::MIMO => 2,
+        };
+        if self.num_channels != stream_channels {
+            return Err(Error::msg(format!(
+                "Recording has {} channels, but the stream has {stream_channels}",
+                self.num_channels
+            )));
+        }
+        if self.num_samples == 0 {
+            return Ok(0);
+        }
+
+        let chunk_len = stream.config.buffer_size as usize;
+        let sample_size = self.format.sample_size();
+        let mut bytes = vec![0; chunk_len * sample_size];
+        let mut decoded = Vec::with_capacity(chunk_len);
+        let mut chunk: Vec<F> = Vec::with_capacity(chunk_len);
+
+        let mut passes_left = match self.repeat {
+            Repeat::Count(0) => return Ok(0),
+            Repeat::Count(count) => Some(count - 1),
+            Repeat::Forever => None,
+        };
+        self.rewind()?;
+        let mut left_in_pass = self.num_samples;
+        let mut written = 0;
+
+        loop {
+            // Fill the chunk, wrapping around to the start of the file while there are passes left
+            let mut filled = 0;
+            while filled < chunk_len {
+                if left_in_pass == 0 {
+                    match &mut passes_left {
+                        Some(0) => break,
+                        Some(passes) => *passes -= 1,
+                        None => {}
+                    }
+                    self.rewind()?;
+                    left_in_pass = self.num_samples;
+                }
+                let len = (chunk_len - filled).min(left_in_pass as usize);
+                let dst = &mut bytes[filled * sample_size..(filled + len) * sample_size];
+                self.file.read_exact(dst).map_err(|e| {
+                    Error::msg(format!("Failed to read {}: {e}", self.path.display()))
+                })?;
+                filled += len;
+                left_in_pass -= len as u64;
+            }
+            if filled == 0 {
+                return Ok(written);
+            }
+
+            self.format
+                .decode(&bytes[..filled * sample_size], &mut decoded);
+            if chunk.len() != decoded.len() / F::CHANNELS {
+                chunk = zeroed_samples(decoded.len() / F::CHANNELS);
+            }
+            ci16_to_format(&decoded, &mut chunk);
+            stream.write(&chunk, timeout)?;
+            written += filled as u64;
+
+            if stop(written) {
+                return Ok(written);
+            }
+        }
+    }
+
+    fn rewind(&mut self) -> Result<()> {
+        self.file
+            .seek(SeekFrom::Start(0))
+            .map(|_| ())
+            .map_err(|e| Error::msg(format!("Failed to seek {}: {e}", self.path.display())))
+    }
+}
@@ -0,0 +1,677 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display};
+#[cfg(any(feature = "toml", feature = "json"))]
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BladeRF, Channel, Config, CorrectionDcOffsetI, CorrectionDcOffsetQ, CorrectionGain,
+    CorrectionPhase, CorrectionValue, Error, Gain, GainMode, Loopback, ModuleConfig, Range,
+    RationalRate, Result, RxMux, SmbMode, TuningMode,
+};
+
+/// Sample rate of a [ChannelProfile], either in whole Hz or as a [RationalRate].
+///
+/// In configuration files, an integer selects [ProfileSampleRate::Integer] and a table with `integer`, `num` and `den`
+/// selects [ProfileSampleRate::Rational].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
+pub enum ProfileSampleRate {
+    /// Set with [BladeRF::set_sample_rate()]
+    Integer(u32),
+    /// Set with [BladeRF::set_rational_sample_rate()]
+    Rational(RationalRate),
+}
+
+impl ProfileSampleRate {
+    /// The rate in Hz.
+    pub fn hz(&self) -> f64 {
+        match *self {
+            ProfileSampleRate::Integer(rate) => rate as f64,
+            ProfileSampleRate::Rational(rate) => rate.as_f64(),
+        }
+    }
+}
+
+impl Display for ProfileSampleRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileSampleRate::Integer(rate) => write!(f, "{rate}"),
+            ProfileSampleRate::Rational(rate) => {
+                write!(f, "{} {}/{}", rate.integer, rate.num, rate.den)
+            }
+        }
+    }
+}
+
+impl From<u32> for ProfileSampleRate {
+    fn from(rate: u32) -> Self {
+        ProfileSampleRate::Integer(rate)
+    }
+}
+
+impl From<RationalRate> for ProfileSampleRate {
+    fn from(rate: RationalRate) -> Self {
+        ProfileSampleRate::Rational(rate)
+    }
+}
+
+/// IQ corrections of a [ChannelProfile], in the units of the matching [CorrectionValue].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct ProfileCorrections {
+    /// See [CorrectionDcOffsetI]
+    pub dc_offset_i: Option<i16>,
+    /// See [CorrectionDcOffsetQ]
+    pub dc_offset_q: Option<i16>,
+    /// See [CorrectionPhase]
+    pub phase: Option<i16>,
+    /// See [CorrectionGain]
+    pub gain: Option<i16>,
+}
+
+/// Settings of one channel in a [DeviceProfile].
+///
+/// Settings left at [None] are not touched when the profile is applied.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct ChannelProfile {
+    /// Center frequency in Hz
+    pub frequency: Option<u64>,
+    /// Sample rate in Hz, either whole or rational
+    pub sample_rate: Option<ProfileSampleRate>,
+    /// Bandwidth in Hz
+    pub bandwidth: Option<u32>,
+    /// Gain control mode, see [BladeRF::get_gain_modes()]
+    pub gain_mode: Option<GainMode>,
+    /// Overall system gain, applied before the individual stages
+    pub gain: Option<Gain>,
+    /// Gains of individual stages by name, see [BladeRF::get_gain_stages()]
+    pub gain_stages: BTreeMap<String, Gain>,
+    /// Only available on the bladeRF 2.0
+    pub bias_tee: Option<bool>,
+    /// IQ corrections
+    pub corrections: ProfileCorrections,
+}
+
+impl From<ModuleConfig> for ChannelProfile {
+    fn from(config: ModuleConfig) -> Self {
+        Self {
+            frequency: Some(config.frequency),
+            sample_rate: Some(config.sample_rate.into()),
+            bandwidth: Some(config.bandwidth),
+            gain: Some(config.gain),
+            ..Default::default()
+        }
+    }
+}
+
+/// Complete setup of a device, covering every channel as well as the device wide settings.
+///
+/// Every setting is optional and left untouched if not given. A profile is checked against the ranges and modes the
+/// device reports with [DeviceProfile::validate()], which [DeviceProfile::apply()] does before changing anything.
+///
+/// With the `toml` or `json` features, profiles can be kept in configuration files:
+///
+/// ```toml
+/// tuning_mode = "fpga"
+///
+/// [rx0]
+/// frequency = 915000000
+/// sample_rate = 2000000
+/// bandwidth = 1500000
+/// gain_mode = "manual"
+/// gain = 30
+///
+/// [tx0]
+/// frequency = 915000000
+/// sample_rate = { integer = 2000000, num = 1, den = 3 }
+/// bias_tee = true
+/// corrections = { dc_offset_i = 12, dc_offset_q = -40 }
+/// ```
+///
+/// ```no_run
+/// use bladerf::{BladeRfAny, ChannelProfile, DeviceProfile};
+///
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let profile = DeviceProfile {
+///     rx0: Some(ChannelProfile {
+///         frequency: Some(915_000_000),
+///         sample_rate: Some(2_000_000.into()),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// // Safety: No streams are running yet
+/// unsafe { profile.apply(&dev) }.unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct DeviceProfile {
+    /// See [BladeRF::set_tuning_mode()]
+    pub tuning_mode: Option<TuningMode>,
+    /// See [BladeRF::set_loopback()]
+    pub loopback: Option<Loopback>,
+    /// See [BladeRF::set_rx_mux()]
+    pub rx_mux: Option<RxMux>,
+    /// See [BladeRF::set_smb_mode()], only available on the bladeRF 1
+    pub smb_mode: Option<SmbMode>,
+    /// Settings of [Channel::Rx0]
+    pub rx0: Option<ChannelProfile>,
+    /// Settings of [Channel::Rx1]
+    pub rx1: Option<ChannelProfile>,
+    /// Settings of [Channel::Tx0]
+    pub tx0: Option<ChannelProfile>,
+    /// Settings of [Channel::Tx1]
+    pub tx1: Option<ChannelProfile>,
+}
+
+impl From<Config> for DeviceProfile {
+    fn from(config: Config) -> Self {
+        Self {
+            rx0: Some(config.rx.into()),
+            tx0: Some(config.tx.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// A setting which differs between two profiles, see [DeviceProfile::diff()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileChange {
+    /// Name of the setting as in configuration files, such as `rx0.frequency`
+    pub setting: String,
+    /// The value in the first profile, if present
+    pub from: Option<String>,
+    /// The value in the second profile, if present
+    pub to: Option<String>,
+}
+
+impl Display for ProfileChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "unset".to_owned());
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.setting,
+            value(&self.from),
+            value(&self.to)
+        )
+    }
+}
+
+impl DeviceProfile {
+    /// The settings of `channel`, if any.
+    pub fn channel(&self, channel: Channel) -> Option<&ChannelProfile> {
+        match channel {
+            Channel::Rx0 => self.rx0.as_ref(),
+            Channel::Rx1 => self.rx1.as_ref(),
+            Channel::Tx0 => self.tx0.as_ref(),
+            Channel::Tx1 => self.tx1.as_ref(),
+        }
+    }
+
+    /// The settings of `channel`, inserting empty ones if there are none yet.
+    pub fn channel_mut(&mut self, channel: Channel) -> &mut ChannelProfile {
+        match channel {
+            Channel::Rx0 => self.rx0.get_or_insert_with(Default::default),
+            Channel::Rx1 => self.rx1.get_or_insert_with(Default::default),
+            Channel::Tx0 => self.tx0.get_or_insert_with(Default::default),
+            Channel::Tx1 => self.tx1.get_or_insert_with(Default::default),
+        }
+    }
+
+    /// All channels with settings.
+    pub fn channels(&self) -> impl Iterator<Item = (Channel, &ChannelProfile)> {
+        [Channel::Rx0, Channel::Rx1, Channel::Tx0, Channel::Tx1]
+            .into_iter()
+            .filter_map(|channel| Some((channel, self.channel(channel)?)))
+    }
+
+    /// Checks every setting against the ranges and modes reported by `dev`, without changing anything.
+    ///
+    /// # Errors
+    /// Lists every setting which `dev` does not support, or the error of querying `dev`.
+    pub fn validate<D: BladeRF>(&self, dev: &D) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if self.tuning_mode == Some(TuningMode::Invalid) {
+            problems.push("tuning_mode: invalid".to_owned());
+        }
+        if self.rx_mux == Some(RxMux::Invalid) {
+            problems.push("rx_mux: invalid".to_owned());
+        }
+        match self.smb_mode {
+            Some(mode @ (SmbMode::Invalid | SmbMode::Unavailable)) => {
+                problems.push(format!("smb_mode: {mode:?} cannot be selected"));
+            }
+            Some(_) => match dev.get_smb_mode() {
+                Ok(_) => {}
+                Err(Error::Unsupported) => {
+                    problems.push("smb_mode: not available on this board".to_owned());
+                }
+                Err(e) => return Err(e),
+            },
+            None => {}
+        }
+        if let Some(loopback) = self.loopback {
+            if !dev.is_loopback_mode_supported(loopback)? {
+                problems.push(format!("loopback: {loopback:?} is not supported"));
+            }
+        }
+
+        for (channel, profile) in self.channels() {
+            let name = format!("{channel:?}").to_lowercase();
+            if let Some(frequency) = profile.frequency {
+                check_range(
+                    &mut problems,
+                    format!("{name}.frequency"),
+                    frequency as f64,
+                    dev.get_frequency_range(channel)?,
+                );
+            }
+            match profile.sample_rate {
+                Some(ProfileSampleRate::Rational(RationalRate { den: 0, .. })) => {
+                    problems.push(format!("{name}.sample_rate: denominator is zero"));
+                }
+                Some(rate) => check_range(
+                    &mut problems,
+                    format!("{name}.sample_rate"),
+                    rate.hz(),
+                    dev.get_sample_rate_range(channel)?,
+                ),
+                None => {}
+            }
+            if let Some(bandwidth) = profile.bandwidth {
+                check_range(
+                    &mut problems,
+                    format!("{name}.bandwidth"),
+                    bandwidth as f64,
+                    dev.get_bandwidth_range(channel)?,
+                );
+            }
+            if let Some(gain) = profile.gain {
+                check_range(
+                    &mut problems,
+                    format!("{name}.gain"),
+                    gain as f64,
+                    dev.get_gain_range(channel)?,
+                );
+            }
+            if !profile.gain_stages.is_empty() {
+                let stages = dev.get_gain_stages(channel)?;
+                for (stage, &gain) in &profile.gain_stages {
+                    if stages.contains(stage) {
+                        check_range(
+                            &mut problems,
+                            format!("{name}.gain_stages.{stage}"),
+                            gain as f64,
+                            dev.get_gain_stage_range(channel, stage)?,
+                        );
+                    } else {
+                        problems.push(format!(
+                            "{name}.gain_stages: unknown stage {stage:?}, available are {stages:?}"
+                        ));
+                    }
+                }
+            }
+
+            if let Some(mode) = profile.gain_mode {
+                let modes = dev.get_gain_modes(channel)?;
+                let supported = if modes.is_empty() {
+                    mode == GainMode::Default
+                } else {
+                    modes.iter().any(|info| info.mode == mode)
+                };
+                if !supported {
+                    problems.push(format!("{name}.gain_mode: {mode:?} is not supported"));
+                }
+            }
+            if profile.bias_tee.is_some() {
+                match dev.get_bias_tee(channel) {
+                    Ok(_) => {}
+                    Err(Error::Unsupported) => {
+                        problems.push(format!("{name}.bias_tee: not available on this board"));
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            let corrections = &profile.corrections;
+            let setting = |correction: &str| format!("{name}.corrections.{correction}");
+            check_correction::<CorrectionDcOffsetI>(
+                &mut problems,
+                setting("dc_offset_i"),
+                corrections.dc_offset_i,
+            );
+            check_correction::<CorrectionDcOffsetQ>(
+                &mut problems,
+                setting("dc_offset_q"),
+                corrections.dc_offset_q,
+            );
+            check_correction::<CorrectionPhase>(&mut problems, setting("phase"), corrections.phase);
+            check_correction::<CorrectionGain>(&mut problems, setting("gain"), corrections.gain);
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::msg(format!(
+                "Invalid device profile: {}",
+                problems.join("; ")
+            )))
+        }
+    }
+
+    /// Validates the profile with [DeviceProfile::validate()], then applies every setting it contains to `dev`.
+    ///
+    /// Device wide settings are applied first, followed by the channels in the order RX0, RX1, TX0, TX1. Within a
+    /// channel, the sample rate and bandwidth are set before the frequency, and the gain mode before any gains.
+    ///
+    /// # Safety
+    /// If the profile sets the loopback mode, no samples may be streamed while it is applied, see
+    /// [BladeRF::set_loopback()].
+    pub unsafe fn apply<D: BladeRF>(&self, dev: &D) -> Result<()> {
+        self.validate(dev)?;
+
+        if let Some(mode) = self.tuning_mode {
+            dev.set_tuning_mode(mode)?;
+        }
+        if let Some(mux) = self.rx_mux {
+            dev.set_rx_mux(mux)?;
+        }
+        if let Some(mode) = self.smb_mode {
+            dev.set_smb_mode(mode)?;
+        }
+        if let Some(loopback) = self.loopback {
+            // Safety: Guaranteed by the caller
+            unsafe { dev.set_loopback(loopback) }?;
+        }
+
+        for (channel, profile) in self.channels() {
+            match profile.sample_rate {
+                Some(ProfileSampleRate::Integer(rate)) => {
+                    dev.set_sample_rate(channel, rate)?;
+                }
+                Some(ProfileSampleRate::Rational(rate)) => {
+                    dev.set_rational_sample_rate(channel, rate.into())?;
+                }
+                None => {}
+            }
+            if let Some(bandwidth) = profile.bandwidth {
+                dev.set_bandwidth(channel, bandwidth)?;
+            }
+            if let Some(frequency) = profile.frequency {
+                dev.set_frequency(channel, frequency)?;
+            }
+            if let Some(mode) = profile.gain_mode {
+                dev.set_gain_mode(channel, mode)?;
+            }
+            if let Some(gain) = profile.gain {
+                dev.set_gain(channel, gain)?;
+            }
+            for (stage, &gain) in &profile.gain_stages {
+                dev.set_gain_stage(channel, stage, gain)?;
+            }
+            if let Some(enable) = profile.bias_tee {
+                dev.set_bias_tee(channel, enable)?;
+            }
+
+            let corrections = &profile.corrections;
+            apply_correction::<_, CorrectionDcOffsetI>(dev, channel, corrections.dc_offset_i)?;
+            apply_correction::<_, CorrectionDcOffsetQ>(dev, channel, corrections.dc_offset_q)?;
+            apply_correction::<_, CorrectionPhase>(dev, channel, corrections.phase)?;
+            apply_correction::<_, CorrectionGain>(dev, channel, corrections.gain)?;
+        }
+        Ok(())
+    }
+
+    /// Lists every setting which differs from `other`, including settings only present in one of the profiles.
+    pub fn diff(&self, other: &DeviceProfile) -> Vec<ProfileChange> {
+        let before = self.settings();
+        let after = other.settings();
+        let mut changes: Vec<_> = before
+            .iter()
+            .filter_map(|(setting, from)| {
+                let to = after.iter().find(|(other, _)| other == setting);
+                match to {
+                    Some((_, to)) if to == from => None,
+                    to => Some(ProfileChange {
+                        setting: setting.clone(),
+                        from: Some(from.clone()),
+                        to: to.map(|(_, to)| to.clone()),
+                    }),
+                }
+            })
+            .collect();
+        changes.extend(
+            after
+                .into_iter()
+                .filter(|(setting, _)| !before.iter().any(|(other, _)| other == setting))
+                .map(|(setting, to)| ProfileChange {
+                    setting,
+                    from: None,
+                    to: Some(to),
+                }),
+        );
+        changes
+    }
+
+    /// Every setting which is present, by the name it has in configuration files.
+    fn settings(&self) -> Vec<(String, String)> {
+        fn push(settings: &mut Vec<(String, String)>, name: &str, value: Option<impl Display>) {
+            if let Some(value) = value {
+                settings.push((name.to_owned(), value.to_string()));
+            }
+        }
+
+        fn debug(value: &impl Debug) -> String {
+            format!("{value:?}")
+        }
+
+        let mut settings = Vec::new();
+        push(
+            &mut settings,
+            "tuning_mode",
+            self.tuning_mode.as_ref().map(debug),
+        );
+        push(&mut settings, "loopback", self.loopback.as_ref().map(debug));
+        push(&mut settings, "rx_mux", self.rx_mux.as_ref().map(debug));
+        push(&mut settings, "smb_mode", self.smb_mode.as_ref().map(debug));
+        for (channel, profile) in self.channels() {
+            let name = format!("{channel:?}").to_lowercase();
+            let setting = |setting: &str| format!("{name}.{setting}");
+            push(&mut settings, &setting("frequency"), profile.frequency);
+            push(&mut settings, &setting("sample_rate"), profile.sample_rate);
+            push(&mut settings, &setting("bandwidth"), profile.bandwidth);
+            push(
+                &mut settings,
+                &setting("gain_mode"),
+                profile.gain_mode.as_ref().map(debug),
+            );
+            push(&mut settings, &setting("gain"), profile.gain);
+            for (stage, gain) in &profile.gain_stages {
+                push(
+                    &mut settings,
+                    &setting(&format!("gain_stages.{stage}")),
+                    Some(gain),
+                );
+            }
+            push(&mut settings, &setting("bias_tee"), profile.bias_tee);
+            let corrections = &profile.corrections;
+            push(
+                &mut settings,
+                &setting("corrections.dc_offset_i"),
+                corrections.dc_offset_i,
+            );
+            push(
+                &mut settings,
+                &setting("corrections.dc_offset_q"),
+                corrections.dc_offset_q,
+            );
+            push(
+                &mut settings,
+                &setting("corrections.phase"),
+                corrections.phase,
+            );
+            push(
+                &mut settings,
+                &setting("corrections.gain"),
+                corrections.gain,
+            );
+        }
+        settings
+    }
+
+    /// Parses a profile in TOML.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(invalid)
+    }
+
+    /// Formats the profile as TOML.
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(invalid)
+    }
+
+    /// Parses a profile in JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(invalid)
+    }
+
+    /// Formats the profile as pretty printed JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(invalid)
+    }
+
+    /// Reads a profile from a `.toml` or `.json` file, depending on which of the `toml` and `json` features are enabled.
+    #[cfg(any(feature = "toml", feature = "json"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Error::msg(format!("Failed to read {}: {e}", path.display())))?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&contents),
+            #[cfg(feature = "json")]
+            Some("json") => Self::from_json(&contents),
+            _ => Err(Error::msg(format!(
+                "Unsupported device profile format: {}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+fn check_range(problems: &mut Vec<String>, setting: String, value: f64, range: Range) {
+    if value < range.min || value > range.max {
+        problems.push(format!("{setting}: {value} is outside of {range}"));
+    }
+}
+
+fn check_correction<T: CorrectionValue>(
+    problems: &mut Vec<String>,
+    setting: String,
+    value: Option<i16>,
+) {
+    if let Some(value) = value.filter(|value| T::new(*value).is_none()) {
+        problems.push(format!(
+            "{setting}: {value} is outside of {}..{}",
+            T::MIN,
+            T::MAX
+        ));
+    }
+}
+
+fn apply_correction<D: BladeRF, T: CorrectionValue>(
+    dev: &D,
+    channel: Channel,
+    value: Option<i16>,
+) -> Result<()> {
+    match value {
+        Some(value) => dev.set_correction(channel, T::new(value).ok_or(Error::Range)?),
+        None => Ok(()),
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+fn invalid(e: impl Display) -> Error {
+    Error::msg(format!("Invalid device profile: {e}"))
+}
+
+#[cfg(all(test, feature = "toml", feature = "json"))]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+tuning_mode = "fpga"
+loopback = "firmware"
+
+[rx0]
+frequency = 915000000
+sample_rate = 2000000
+gain_mode = "manual"
+gain_stages = { full = 30 }
+
+[tx1]
+sample_rate = { integer = 2000000, num = 1, den = 3 }
+bias_tee = true
+corrections = { dc_offset_i = 12, phase = -40 }
+"#;
+
+    #[test]
+    fn parse_toml() {
+        let profile = DeviceProfile::from_toml(TOML).unwrap();
+        assert_eq!(profile.tuning_mode, Some(TuningMode::FPGA));
+        assert_eq!(profile.loopback, Some(Loopback::Firmware));
+        assert_eq!(profile.rx1, None);
+
+        let rx0 = profile.channel(Channel::Rx0).unwrap();
+        assert_eq!(rx0.frequency, Some(915_000_000));
+        assert_eq!(rx0.sample_rate, Some(ProfileSampleRate::Integer(2_000_000)));
+        assert_eq!(rx0.gain_mode, Some(GainMode::Manual));
+        assert_eq!(rx0.gain_stages["full"], 30);
+
+        let tx1 = profile.channel(Channel::Tx1).unwrap();
+        let rate = RationalRate {
+            integer: 2_000_000,
+            num: 1,
+            den: 3,
+        };
+        assert_eq!(tx1.sample_rate, Some(rate.into()));
+        assert_eq!(tx1.bias_tee, Some(true));
+        assert_eq!(tx1.corrections.dc_offset_i, Some(12));
+        assert_eq!(tx1.corrections.phase, Some(-40));
+        assert_eq!(tx1.corrections.gain, None);
+
+        // Typos are rejected rather than silently ignored
+        assert!(DeviceProfile::from_toml("[rx0]\nfrequncy = 1").is_err());
+        assert!(DeviceProfile::from_toml("loopback = \"sideways\"").is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let profile = DeviceProfile::from_toml(TOML).unwrap();
+        let toml = profile.to_toml().unwrap();
+        assert_eq!(DeviceProfile::from_toml(&toml).unwrap(), profile);
+        let json = profile.to_json().unwrap();
+        assert_eq!(DeviceProfile::from_json(&json).unwrap(), profile);
+    }
+}
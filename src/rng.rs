@@ -0,0 +1,51 @@
+//! A reproducible random number generator, shared by the simulator and the waveform generators.
+
+use num_complex::Complex32;
+
+/// Small xorshift generator, so the noise is reproducible without pulling in a dependency.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // Xorshift gets stuck at zero
+        Self(seed.max(1))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in (0, 1]
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        ((self.next_u64() >> 40) + 1) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Complex gaussian sample with the given standard deviation per component (Box-Muller).
+    pub(crate) fn gaussian(&mut self, std_dev: f32) -> Complex32 {
+        let radius = (-2.0 * self.next_f32().ln()).sqrt() * std_dev;
+        let angle = std::f32::consts::TAU * self.next_f32();
+        Complex32::from_polar(radius, angle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.gaussian(1.0), b.gaussian(1.0));
+        }
+        assert!((0..1000).all(|_| {
+            let x = a.next_f32();
+            x > 0.0 && x <= 1.0
+        }));
+    }
+}
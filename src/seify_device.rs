@@ -0,0 +1,646 @@
+//! Implementation of the [seify](https://github.com/FutureSDR/seify) device and streamer traits.
+
+use std::any::Any;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use num_complex::Complex32;
+use seify::{Args, DeviceTrait, Direction, Driver, RangeItem};
+
+use crate::{
+    BladeRF, BladeRfAny, Channel, ChannelLayoutRx, ChannelLayoutTx, Error, GainMode, RxChannel,
+    RxSyncStream, StreamConfig, TxChannel, TxSyncStream,
+};
+
+/// Name of the only frequency component of a channel, see [DeviceTrait::frequency_components()].
+const FREQUENCY_COMPONENT: &str = "RF";
+
+impl From<Error> for seify::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Nodev | Error::NoFile => seify::Error::NotFound,
+            Error::Inval | Error::Range => seify::Error::ValueError,
+            Error::Unsupported => seify::Error::NotSupported,
+            Error::Unexpected | Error::IO | Error::Permission => seify::Error::DeviceError,
+            err => seify::Error::Misc(err.to_string()),
+        }
+    }
+}
+
+/// A [BladeRfAny] driven through seify's [DeviceTrait], so applications written against seify can use bladeRF 1 and 2 devices.
+///
+/// Wrap it with [seify::Device::from_impl()] to use it like any other seify device:
+///
+/// ```no_run
+/// use bladerf::SeifyBladeRf;
+/// use seify::{Device, Direction};
+/// let dev = Device::from_impl(SeifyBladeRf::open("serial=deadbeef").unwrap());
+/// dev.set_frequency(Direction::Rx, 0, 915e6).unwrap();
+/// ```
+///
+/// Channels are numbered the same as in `libbladerf`, so `0` is [Channel::Rx0]/[Channel::Tx0] and `1` is
+/// [Channel::Rx1]/[Channel::Tx1] on the bladeRF 2.0. Samples are streamed as [Complex32], see [RxSyncStream] for how
+/// they are converted.
+///
+/// Only one RX and one TX streamer can exist at a time per device, as with [BladeRfAny::rx_streamer()] and
+/// [BladeRfAny::tx_streamer()]. Dropping a streamer allows creating a new one.
+///
+/// seify has no bladeRF [Driver], so [DeviceTrait::driver()] reports [Driver::Soapy], which reaches bladeRF devices
+/// through the same `libbladerf`.
+#[derive(Clone, Debug)]
+pub struct SeifyBladeRf {
+    dev: Arc<BladeRfAny>,
+}
+
+impl SeifyBladeRf {
+    /// Lists the attached devices, as seify [Args] which can be passed to [SeifyBladeRf::open()].
+    ///
+    /// Every entry contains the `driver` (always `bladerf`) and the `serial` of the device.
+    pub fn probe(_args: &Args) -> Result<Vec<Args>, seify::Error> {
+        crate::get_device_list()?
+            .into_iter()
+            .map(|info| {
+                let mut args = Args::new();
+                args.set("driver", "bladerf");
+                args.set("serial", info.serial());
+                args.set("instance", info.instance().to_string());
+                Ok(args)
+            })
+            .collect()
+    }
+
+    /// Opens the device with the `serial` given in `args`, or the first device if no serial is given.
+    pub fn open<A: TryInto<Args>>(args: A) -> Result<Self, seify::Error> {
+        let args: Args = args.try_into().or(Err(seify::Error::ValueError))?;
+        let dev = match args.get::<String>("serial") {
+            Ok(serial) => BladeRfAny::open_identifier(&format!("*:serial={serial}"))?,
+            Err(_) => BladeRfAny::open_first()?,
+        };
+        Ok(Self::from_device(Arc::new(dev)))
+    }
+
+    /// Wraps an already opened device.
+    pub fn from_device(dev: Arc<BladeRfAny>) -> Self {
+        Self { dev }
+    }
+
+    /// The wrapped device, to access functionality which is not exposed through seify.
+    pub fn device(&self) -> &Arc<BladeRfAny> {
+        &self.dev
+    }
+
+    fn channel(&self, direction: Direction, channel: usize) -> Result<Channel, seify::Error> {
+        if channel >= self.num_channels(direction)? {
+            return Err(seify::Error::ValueError);
+        }
+        Ok(match (direction, channel) {
+            (Direction::Rx, 0) => Channel::Rx0,
+            (Direction::Rx, _) => Channel::Rx1,
+            (Direction::Tx, 0) => Channel::Tx0,
+            (Direction::Tx, _) => Channel::Tx1,
+        })
+    }
+}
+
+fn range(range: crate::Range) -> seify::Range {
+    if range.step > 0.0 {
+        seify::Range::new(vec![RangeItem::Step(range.min, range.max, range.step)])
+    } else {
+        seify::Range::new(vec![RangeItem::Interval(range.min, range.max)])
+    }
+}
+
+impl DeviceTrait for SeifyBladeRf {
+    type RxStreamer = SeifyRxStreamer;
+    type TxStreamer = SeifyTxStreamer;
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn driver(&self) -> Driver {
+        // No bladeRF variant exists, see the struct docs
+        Driver::Soapy
+    }
+
+    fn id(&self) -> Result<String, seify::Error> {
+        Ok(self.dev.get_serial()?)
+    }
+
+    fn info(&self) -> Result<Args, seify::Error> {
+        let mut args = Args::new();
+        args.set("driver", "bladerf");
+        args.set("board", self.dev.get_board_name());
+        args.set("serial", self.dev.get_serial()?);
+        args.set(
+            "firmware_version",
+            self.dev.get_firmware_version()?.to_string(),
+        );
+        args.set("fpga_version", self.dev.get_fpga_version()?.to_string());
+        Ok(args)
+    }
+
+    fn num_channels(&self, _direction: Direction) -> Result<usize, seify::Error> {
+        match self.dev.get_board_name() {
+            "bladerf1" => Ok(1),
+            "bladerf2" => Ok(2),
+            _ => Err(seify::Error::NotSupported),
+        }
+    }
+
+    fn full_duplex(&self, direction: Direction, channel: usize) -> Result<bool, seify::Error> {
+        self.channel(direction, channel)?;
+        Ok(true)
+    }
+
+    fn rx_streamer(
+        &self,
+        channels: &[usize],
+        _args: Args,
+    ) -> Result<SeifyRxStreamer, seify::Error> {
+        let layout = match channels {
+            [0] => ChannelLayoutRx::SISO(RxChannel::Rx0),
+            [1] => ChannelLayoutRx::SISO(RxChannel::Rx1),
+            [0, 1] => ChannelLayoutRx::MIMO,
+            _ => return Err(seify::Error::ValueError),
+        };
+        for channel in channels {
+            self.channel(Direction::Rx, *channel)?;
+        }
+        let config = StreamConfig::default();
+        let stream = BladeRfAny::rx_streamer_arc(self.dev.clone(), config, layout)?;
+        Ok(SeifyRxStreamer {
+            stream,
+            _release: StreamRelease {
+                dev: self.dev.clone(),
+                direction: Direction::Rx,
+            },
+            channels: channels.len(),
+            mtu: config.buffer_size as usize,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn tx_streamer(
+        &self,
+        channels: &[usize],
+        _args: Args,
+    ) -> Result<SeifyTxStreamer, seify::Error> {
+        let layout = match channels {
+            [0] => ChannelLayoutTx::SISO(TxChannel::Tx0),
+            [1] => ChannelLayoutTx::SISO(TxChannel::Tx1),
+            [0, 1] => ChannelLayoutTx::MIMO,
+            _ => return Err(seify::Error::ValueError),
+        };
+        for channel in channels {
+            self.channel(Direction::Tx, *channel)?;
+        }
+        let config = StreamConfig::default();
+        let stream = BladeRfAny::tx_streamer_arc(self.dev.clone(), config, layout)?;
+        Ok(SeifyTxStreamer {
+            stream,
+            _release: StreamRelease {
+                dev: self.dev.clone(),
+                direction: Direction::Tx,
+            },
+            channels: channels.len(),
+            mtu: config.buffer_size as usize,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn antennas(&self, direction: Direction, channel: usize) -> Result<Vec<String>, seify::Error> {
+        self.antenna(direction, channel).map(|name| vec![name])
+    }
+
+    fn antenna(&self, direction: Direction, channel: usize) -> Result<String, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(format!("{channel:?}").to_uppercase())
+    }
+
+    fn set_antenna(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<(), seify::Error> {
+        // Every channel has a single, fixed antenna port
+        if self.antenna(direction, channel)? == name {
+            Ok(())
+        } else {
+            Err(seify::Error::NotSupported)
+        }
+    }
+
+    fn gain_elements(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<String>, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(self.dev.get_gain_stages(channel)?)
+    }
+
+    fn supports_agc(&self, direction: Direction, channel: usize) -> Result<bool, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(channel.is_rx() && !self.dev.get_gain_modes(channel)?.is_empty())
+    }
+
+    fn enable_agc(
+        &self,
+        direction: Direction,
+        channel: usize,
+        agc: bool,
+    ) -> Result<(), seify::Error> {
+        if !self.supports_agc(direction, channel)? {
+            return Err(seify::Error::NotSupported);
+        }
+        let channel = self.channel(direction, channel)?;
+        let mode = if agc {
+            GainMode::Default
+        } else {
+            GainMode::Manual
+        };
+        Ok(self.dev.set_gain_mode(channel, mode)?)
+    }
+
+    fn agc(&self, direction: Direction, channel: usize) -> Result<bool, seify::Error> {
+        if !self.supports_agc(direction, channel)? {
+            return Ok(false);
+        }
+        let channel = self.channel(direction, channel)?;
+        Ok(self.dev.get_gain_mode(channel)? != GainMode::Manual)
+    }
+
+    fn set_gain(
+        &self,
+        direction: Direction,
+        channel: usize,
+        gain: f64,
+    ) -> Result<(), seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(self.dev.set_gain(channel, gain.round() as i32)?)
+    }
+
+    fn gain(&self, direction: Direction, channel: usize) -> Result<Option<f64>, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(Some(self.dev.get_gain(channel)?.into()))
+    }
+
+    fn gain_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<seify::Range, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(range(self.dev.get_gain_range(channel)?))
+    }
+
+    fn set_gain_element(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+        gain: f64,
+    ) -> Result<(), seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(self
+            .dev
+            .set_gain_stage(channel, name, gain.round() as i32)?)
+    }
+
+    fn gain_element(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<Option<f64>, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(Some(self.dev.get_gain_stage(channel, name)?.into()))
+    }
+
+    fn gain_element_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<seify::Range, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(range(self.dev.get_gain_stage_range(channel, name)?))
+    }
+
+    fn frequency_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<seify::Range, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(range(self.dev.get_frequency_range(channel)?))
+    }
+
+    fn frequency(&self, direction: Direction, channel: usize) -> Result<f64, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(self.dev.get_frequency(channel)? as f64)
+    }
+
+    fn set_frequency(
+        &self,
+        direction: Direction,
+        channel: usize,
+        frequency: f64,
+        _args: Args,
+    ) -> Result<(), seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(self.dev.set_frequency(channel, frequency.round() as u64)?)
+    }
+
+    fn frequency_components(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<String>, seify::Error> {
+        self.channel(direction, channel)?;
+        Ok(vec![FREQUENCY_COMPONENT.to_owned()])
+    }
+
+    fn component_frequency_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<seify::Range, seify::Error> {
+        if name != FREQUENCY_COMPONENT {
+            return Err(seify::Error::NotFound);
+        }
+        self.frequency_range(direction, channel)
+    }
+
+    fn component_frequency(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<f64, seify::Error> {
+        if name != FREQUENCY_COMPONENT {
+            return Err(seify::Error::NotFound);
+        }
+        self.frequency(direction, channel)
+    }
+
+    fn set_component_frequency(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+        frequency: f64,
+    ) -> Result<(), seify::Error> {
+        if name != FREQUENCY_COMPONENT {
+            return Err(seify::Error::NotFound);
+        }
+        self.set_frequency(direction, channel, frequency, Args::new())
+    }
+
+    fn sample_rate(&self, direction: Direction, channel: usize) -> Result<f64, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        let rate = self.dev.get_rational_sample_rate(channel)?;
+        Ok(rate.integer as f64 + rate.num as f64 / rate.den as f64)
+    }
+
+    fn set_sample_rate(
+        &self,
+        direction: Direction,
+        channel: usize,
+        rate: f64,
+    ) -> Result<(), seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        self.dev.set_sample_rate(channel, rate.round() as u32)?;
+        Ok(())
+    }
+
+    fn get_sample_rate_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<seify::Range, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(range(self.dev.get_sample_rate_range(channel)?))
+    }
+
+    fn bandwidth(&self, direction: Direction, channel: usize) -> Result<f64, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(self.dev.get_bandwidth(channel)?.into())
+    }
+
+    fn set_bandwidth(
+        &self,
+        direction: Direction,
+        channel: usize,
+        bw: f64,
+    ) -> Result<(), seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        self.dev.set_bandwidth(channel, bw.round() as u32)?;
+        Ok(())
+    }
+
+    fn get_bandwidth_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<seify::Range, seify::Error> {
+        let channel = self.channel(direction, channel)?;
+        Ok(range(self.dev.get_bandwidth_range(channel)?))
+    }
+
+    fn has_dc_offset_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<bool, seify::Error> {
+        self.channel(direction, channel)?;
+        Ok(false)
+    }
+
+    fn set_dc_offset_mode(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _automatic: bool,
+    ) -> Result<(), seify::Error> {
+        Err(seify::Error::NotSupported)
+    }
+
+    fn dc_offset_mode(&self, _direction: Direction, _channel: usize) -> Result<bool, seify::Error> {
+        Err(seify::Error::NotSupported)
+    }
+}
+
+/// Converts a seify timeout in microseconds, where a negative timeout means to block indefinitely.
+fn timeout(timeout_us: i64) -> Duration {
+    u64::try_from(timeout_us)
+        .map(Duration::from_micros)
+        .unwrap_or(Duration::MAX)
+}
+
+/// Releases the RX or TX stream of the device when dropped, so a new streamer can be created.
+#[derive(Debug)]
+struct StreamRelease {
+    dev: Arc<BladeRfAny>,
+    direction: Direction,
+}
+
+impl Drop for StreamRelease {
+    fn drop(&mut self) {
+        let configured = match self.direction {
+            Direction::Rx => &self.dev.rx_stream_configured,
+            Direction::Tx => &self.dev.tx_stream_configured,
+        };
+        configured.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Receive streamer of a [SeifyBladeRf], see [seify::RxStreamer].
+///
+/// Timed activation is not supported, [seify::RxStreamer::activate_at()] fails unless the time is [None].
+#[derive(Debug)]
+pub struct SeifyRxStreamer {
+    stream: RxSyncStream<Arc<BladeRfAny>, Complex32, BladeRfAny>,
+    /// Declared after `stream`, so the device is released only once the stream has been disabled.
+    _release: StreamRelease,
+    channels: usize,
+    mtu: usize,
+    /// Interleaved samples as read from the device when streaming multiple channels.
+    buffer: Vec<Complex32>,
+}
+
+impl seify::RxStreamer for SeifyRxStreamer {
+    fn mtu(&self) -> Result<usize, seify::Error> {
+        Ok(self.mtu)
+    }
+
+    fn activate_at(&mut self, time_ns: Option<i64>) -> Result<(), seify::Error> {
+        if time_ns.is_some() {
+            return Err(seify::Error::NotSupported);
+        }
+        Ok(self.stream.enable()?)
+    }
+
+    fn deactivate_at(&mut self, time_ns: Option<i64>) -> Result<(), seify::Error> {
+        if time_ns.is_some() {
+            return Err(seify::Error::NotSupported);
+        }
+        Ok(self.stream.disable()?)
+    }
+
+    fn read(
+        &mut self,
+        buffers: &mut [&mut [Complex32]],
+        timeout_us: i64,
+    ) -> Result<usize, seify::Error> {
+        if buffers.len() != self.channels {
+            return Err(seify::Error::ValueError);
+        }
+        let len = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+        if len == 0 {
+            return Ok(0);
+        }
+
+        if let [buffer] = buffers {
+            self.stream.read(&mut buffer[..len], timeout(timeout_us))?;
+            return Ok(len);
+        }
+
+        self.buffer
+            .resize(len * self.channels, Complex32::default());
+        self.stream.read(&mut self.buffer, timeout(timeout_us))?;
+        // MIMO samples are interleaved, one sample per channel
+        for (i, frame) in self.buffer.chunks_exact(self.channels).enumerate() {
+            for (buffer, sample) in buffers.iter_mut().zip(frame) {
+                buffer[i] = *sample;
+            }
+        }
+        Ok(len)
+    }
+}
+
+/// Transmit streamer of a [SeifyBladeRf], see [seify::TxStreamer].
+///
+/// Timed activation and timed writes are not supported and fail unless the time is [None]. `end_burst` is ignored,
+/// since the stream is not configured with metadata.
+#[derive(Debug)]
+pub struct SeifyTxStreamer {
+    stream: TxSyncStream<Arc<BladeRfAny>, Complex32, BladeRfAny>,
+    /// Declared after `stream`, so the device is released only once the stream has been disabled.
+    _release: StreamRelease,
+    channels: usize,
+    mtu: usize,
+    /// Interleaved samples to be written to the device when streaming multiple channels.
+    buffer: Vec<Complex32>,
+}
+
+impl seify::TxStreamer for SeifyTxStreamer {
+    fn mtu(&self) -> Result<usize, seify::Error> {
+        Ok(self.mtu)
+    }
+
+    fn activate_at(&mut self, time_ns: Option<i64>) -> Result<(), seify::Error> {
+        if time_ns.is_some() {
+            return Err(seify::Error::NotSupported);
+        }
+        Ok(self.stream.enable()?)
+    }
+
+    fn deactivate_at(&mut self, time_ns: Option<i64>) -> Result<(), seify::Error> {
+        if time_ns.is_some() {
+            return Err(seify::Error::NotSupported);
+        }
+        Ok(self.stream.disable()?)
+    }
+
+    fn write(
+        &mut self,
+        buffers: &[&[Complex32]],
+        at_ns: Option<i64>,
+        _end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, seify::Error> {
+        if at_ns.is_some() {
+            return Err(seify::Error::NotSupported);
+        }
+        if buffers.len() != self.channels {
+            return Err(seify::Error::ValueError);
+        }
+        let len = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+        if len == 0 {
+            return Ok(0);
+        }
+
+        if let [buffer] = buffers {
+            self.stream.write(&buffer[..len], timeout(timeout_us))?;
+            return Ok(len);
+        }
+
+        self.buffer.clear();
+        for i in 0..len {
+            self.buffer.extend(buffers.iter().map(|buffer| buffer[i]));
+        }
+        self.stream.write(&self.buffer, timeout(timeout_us))?;
+        Ok(len)
+    }
+
+    fn write_all(
+        &mut self,
+        buffers: &[&[Complex32]],
+        at_ns: Option<i64>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<(), seify::Error> {
+        // Writes to the sync stream are never partial
+        self.write(buffers, at_ns, end_burst, timeout_us)?;
+        Ok(())
+    }
+}
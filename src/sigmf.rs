@@ -0,0 +1,500 @@
+//! Recording of received samples in the [SigMF](https://sigmf.org) format.
+
+use std::borrow::Borrow;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::streamers::RxSyncStream;
+use crate::time::iso8601;
+use crate::{
+    BladeRF, Channel, ChannelLayoutRx, Direction, Error, Format, Gain, Result, RxChannel,
+    SampleFormat,
+};
+
+/// Version of the SigMF specification the metadata is written for.
+const SIGMF_VERSION: &str = "1.0.0";
+
+/// Version of the `bladerf` extension namespace, see [SigMfRecorder].
+const EXTENSION_VERSION: &str = "1.0.0";
+
+/// A section of the recording during which the device was tuned to one frequency and gain.
+///
+/// Written to the `captures` array of the `.sigmf-meta` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigMfCapture {
+    /// Index of the first sample of the segment in the data file, counted per channel.
+    pub sample_start: u64,
+    /// Center frequency in Hz.
+    pub frequency: u64,
+    /// Overall gain in dB.
+    pub gain: Gain,
+    /// Wall clock time at which the segment started.
+    pub datetime: SystemTime,
+}
+
+/// Records an [RxSyncStream] to a SigMF recording, a `.sigmf-data` file with the raw samples and a `.sigmf-meta` file
+/// describing them.
+///
+/// The global metadata (sample format, sample rate and hardware description) is read from the device when the recorder
+/// is created. A new capture segment is started whenever the device is retuned through [SigMfRecorder::retune()] or
+/// [SigMfRecorder::set_gain()], or when [SigMfRecorder::update_capture()] notices that the frequency or gain changed.
+///
+/// Samples already buffered by the stream were received with the previous settings, so a new segment does not start at
+/// the end of the recording. If the stream is configured with [metadata](crate::StreamConfig::with_metadata), the
+/// start of the segment is derived from the device timestamp at the time of the change. Otherwise it is estimated by
+/// assuming that all buffers of the stream are full, which can place the boundary up to
+/// `num_buffers × buffer_size` samples too late, see [StreamConfig::new()](crate::StreamConfig::new).
+/// Segments which start after the last recorded sample are left out of the metadata.
+///
+/// [ComplexI16] and [ComplexI12](crate::ComplexI12) are recorded as `ci16_le`, [ComplexI8](crate::ComplexI8) as `ci8`
+/// and [Complex32](num_complex::Complex32) as `cf32_le`. MIMO streams are recorded with two interleaved channels.
+///
+/// The gain is not part of the SigMF core namespace, so it is recorded in the `bladerf` extension namespace, which is
+/// declared as optional in `core:extensions`. Readers which do not know it can ignore it. Version 1.0.0 of the namespace
+/// defines a single field:
+///
+/// | Field          | Object  | Type | Description                                                                      |
+/// |----------------|---------|------|----------------------------------------------------------------------------------|
+/// | `bladerf:gain` | capture | int  | Overall gain of the channel in dB during the segment, see [BladeRF::get_gain()]. |
+///
+/// The metadata is written by [SigMfRecorder::finish()]. If the recorder is dropped instead, for example after a read
+/// error, the metadata is still written but errors are ignored.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRF, BladeRfAny, Channel, ChannelLayoutRx, ComplexI16, RxChannel, SigMfRecorder, StreamConfig};
+/// let dev = BladeRfAny::open_first().unwrap();
+/// dev.set_frequency(Channel::Rx0, 915_000_000).unwrap();
+/// dev.set_sample_rate(Channel::Rx0, 2_000_000).unwrap();
+///
+/// let rx_stream = dev
+///     .rx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutRx::SISO(RxChannel::Rx0))
+///     .unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// // Writes capture.sigmf-data and capture.sigmf-meta
+/// let mut recorder = SigMfRecorder::create(rx_stream, "capture")
+///     .unwrap()
+///     .with_description("ISM band survey");
+/// let mut buffer = vec![ComplexI16::ZERO; 8192];
+/// for _ in 0..100 {
+///     recorder.record(&mut buffer, Duration::from_secs(1)).unwrap();
+/// }
+/// recorder.retune(868_000_000).unwrap();
+/// for _ in 0..100 {
+///     recorder.record(&mut buffer, Duration::from_secs(1)).unwrap();
+/// }
+/// recorder.finish().unwrap();
+/// ```
+///
+/// [ComplexI16]: crate::ComplexI16
+#[derive(Debug)]
+pub struct SigMfRecorder<T: Borrow<D>, F: SampleFormat, D: BladeRF> {
+    stream: RxSyncStream<T, F, D>,
+    data: BufWriter<File>,
+    meta_path: PathBuf,
+    datatype: &'static str,
+    sample_rate: u32,
+    hw: String,
+    description: Option<String>,
+    author: Option<String>,
+    captures: Vec<SigMfCapture>,
+    sample_count: u64,
+    /// Device timestamp of the first recorded sample, if the stream has metadata.
+    first_timestamp: Option<u64>,
+    finished: bool,
+}
+
+impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> SigMfRecorder<T, F, D> {
+    /// Creates `<path>.sigmf-data` and starts recording samples read from `stream` to it.
+    ///
+    /// The `.sigmf-meta` file is created next to it once the recording is finished.
+    /// Existing files are overwritten.
+    pub fn create(stream: RxSyncStream<T, F, D>, path: impl AsRef<Path>) -> Result<Self> {
+        let datatype = datatype::<F>();
+        let dev = stream.dev.borrow();
+        let channel = first_channel(stream.layout);
+        let sample_rate = dev.get_sample_rate(channel)?;
+        let hw = describe_hardware(dev)?;
+        let capture = SigMfCapture {
+            sample_start: 0,
+            frequency: dev.get_frequency(channel)?,
+            gain: dev.get_gain(channel)?,
+            datetime: SystemTime::now(),
+        };
+
+        let data_path = with_suffix(path.as_ref(), ".sigmf-data");
+        let data = File::create(&data_path)
+            .map_err(|e| Error::msg(format!("Failed to create {}: {e}", data_path.display())))?;
+
+        Ok(Self {
+            stream,
+            data: BufWriter::new(data),
+            meta_path: with_suffix(path.as_ref(), ".sigmf-meta"),
+            datatype,
+            sample_rate,
+            hw,
+            description: None,
+            author: None,
+            captures: vec![capture],
+            sample_count: 0,
+            first_timestamp: None,
+            finished: false,
+        })
+    }
+
+    /// Sets `core:description` of the recording.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets `core:author` of the recording.
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Reads samples from the stream into `buffer` and appends them to the recording.
+    ///
+    /// The samples are left in `buffer` for further processing.
+    pub fn record(&mut self, buffer: &mut [F], timeout: Duration) -> Result<()> {
+        if self.stream.config.metadata {
+            let meta = self.stream.read_with_meta(buffer, None, timeout)?;
+            self.first_timestamp.get_or_insert(meta.timestamp);
+        } else {
+            self.stream.read(buffer, timeout)?;
+        }
+        // Safety: SampleFormat types are plain old data without padding
+        let bytes = unsafe {
+            std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), std::mem::size_of_val(buffer))
+        };
+        self.data
+            .write_all(bytes)
+            .map_err(|e| Error::msg(format!("Failed to write SigMF data: {e}")))?;
+        self.sample_count += (buffer.len() * F::CHANNELS / self.num_channels()) as u64;
+        Ok(())
+    }
+
+    /// Tunes all channels of the stream to `frequency` and starts a new capture segment.
+    pub fn retune(&mut self, frequency: u64) -> Result<()> {
+        for channel in channels(self.stream.layout) {
+            self.stream.dev.borrow().set_frequency(channel, frequency)?;
+        }
+        self.update_capture().map(|_| ())
+    }
+
+    /// Sets the overall gain of all channels of the stream and starts a new capture segment.
+    pub fn set_gain(&mut self, gain: Gain) -> Result<()> {
+        for channel in channels(self.stream.layout) {
+            self.stream.dev.borrow().set_gain(channel, gain)?;
+        }
+        self.update_capture().map(|_| ())
+    }
+
+    /// Queries the frequency and gain from the device and starts a new capture segment if either changed.
+    ///
+    /// Use this after changing the device configuration without going through the recorder, for example after a
+    /// [scheduled retune](BladeRF::schedule_retune). Returns whether a segment was started.
+    pub fn update_capture(&mut self) -> Result<bool> {
+        let dev = self.stream.dev.borrow();
+        let channel = first_channel(self.stream.layout);
+        let frequency = dev.get_frequency(channel)?;
+        let gain = dev.get_gain(channel)?;
+
+        let last = self.captures.last_mut().expect("there is always a capture");
+        if last.frequency == frequency && last.gain == gain {
+            return Ok(false);
+        }
+
+        let sample_start = match self.first_timestamp {
+            // The timestamp counts samples, so it maps directly to the position in the recording
+            Some(first) => dev
+                .get_timestamp(Direction::RX)?
+                .saturating_sub(first)
+                .max(self.sample_count),
+            // Samples buffered by the stream were received before the change
+            None => {
+                let buffered =
+                    self.stream.config.num_buffers as u64 * self.stream.config.buffer_size as u64;
+                self.sample_count + buffered / channels(self.stream.layout).len() as u64
+            }
+        };
+        let capture = SigMfCapture {
+            sample_start,
+            frequency,
+            gain,
+            datetime: SystemTime::now(),
+        };
+        // Nothing was received with the previous settings, so it does not need a segment of its own.
+        if last.sample_start >= sample_start {
+            *last = capture;
+        } else {
+            self.captures.push(capture);
+        }
+        Ok(true)
+    }
+
+    /// The capture segments recorded so far.
+    pub fn captures(&self) -> &[SigMfCapture] {
+        &self.captures
+    }
+
+    /// Number of samples recorded per channel.
+    pub fn sample_count(&self) -> u64 {
+        self.sample_count
+    }
+
+    /// The stream being recorded.
+    pub fn stream(&self) -> &RxSyncStream<T, F, D> {
+        &self.stream
+    }
+
+    /// Generates the contents of the `.sigmf-meta` file for the samples recorded so far.
+    pub fn metadata(&self) -> String {
+        let file = MetaFile {
+            global: Global {
+                datatype: self.datatype.to_owned(),
+                sample_rate: Some(self.sample_rate.into()),
+                version: Some(SIGMF_VERSION.to_owned()),
+                num_channels: Some(self.num_channels()),
+                hw: Some(self.hw.clone()),
+                recorder: Some(concat!("bladerf-rs ", env!("CARGO_PKG_VERSION")).to_owned()),
+                description: self.description.clone(),
+                author: self.author.clone(),
+                // Declares the namespace of `bladerf:gain`, see the SigMfRecorder docs
+                extensions: vec![Extension {
+                    name: "bladerf".to_owned(),
+                    version: EXTENSION_VERSION.to_owned(),
+                    optional: true,
+                }],
+            },
+            captures: self
+                .captures
+                .iter()
+                .enumerate()
+                .filter(|(i, capture)| *i == 0 || capture.sample_start < self.sample_count)
+                .map(|(_, capture)| capture)
+                .map(|capture| Capture {
+                    sample_start: capture.sample_start,
+                    frequency: Some(capture.frequency as f64),
+                    datetime: Some(iso8601(capture.datetime)),
+                    gain: Some(capture.gain),
+                })
+                .collect(),
+            annotations: Vec::new(),
+        };
+        let mut json =
+            serde_json::to_string_pretty(&file).expect("SigMF metadata is always serializable");
+        json.push('\n');
+        json
+    }
+
+    /// Flushes the data file and writes the `.sigmf-meta` file.
+    ///
+    /// Dropping the recorder without calling this also writes the metadata, but errors are ignored.
+    pub fn finish(mut self) -> Result<()> {
+        self.finished = true;
+        self.write_metadata()
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        self.data
+            .flush()
+            .map_err(|e| Error::msg(format!("Failed to write SigMF data: {e}")))?;
+        std::fs::write(&self.meta_path, self.metadata())
+            .map_err(|e| Error::msg(format!("Failed to write {}: {e}", self.meta_path.display())))
+    }
+
+    fn num_channels(&self) -> usize {
+        channels(self.stream.layout).len()
+    }
+}
+
+impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> Drop for SigMfRecorder<T, F, D> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.write_metadata();
+        }
+    }
+}
+
+/// The parts of a `.sigmf-meta` file needed to play a recording back.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SigMfMetadata {
+    /// `core:datatype`, the format of the samples in the `.sigmf-data` file.
+    pub datatype: String,
+    /// `core:sample_rate` in samples per second.
+    pub sample_rate: Option<f64>,
+    /// `core:num_channels`, defaults to 1.
+    pub num_channels: usize,
+    /// `core:frequency` of the first capture segment in Hz.
+    pub frequency: Option<f64>,
+    /// `core:description` of the recording.
+    pub description: Option<String>,
+}
+
+impl SigMfMetadata {
+    /// Parses the contents of a `.sigmf-meta` file.
+    pub fn parse(json: &str) -> Result<Self> {
+        let file: MetaFile = serde_json::from_str(json)
+            .map_err(|e| Error::msg(format!("Invalid SigMF metadata: {e}")))?;
+        let num_channels = match file.global.num_channels {
+            Some(0) => return Err(Error::msg("Invalid core:num_channels in SigMF metadata")),
+            Some(num_channels) => num_channels,
+            None => 1,
+        };
+
+        Ok(Self {
+            datatype: file.global.datatype,
+            sample_rate: file.global.sample_rate,
+            num_channels,
+            frequency: file.captures.first().and_then(|capture| capture.frequency),
+            description: file.global.description,
+        })
+    }
+
+    /// Reads and parses a `.sigmf-meta` file.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| Error::msg(format!("Failed to read {}: {e}", path.display())))?;
+        Self::parse(&json)
+    }
+}
+
+/// Appends `suffix` to the file name of `path`, keeping any existing extension.
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    path.into()
+}
+
+/// The SigMF `core:datatype` of samples stored as `F`.
+fn datatype<F: SampleFormat>() -> &'static str {
+    match (F::CONVERTED, F::FORMAT) {
+        (true, _) => "cf32_le",
+        (false, Format::Sc8Q7) => "ci8",
+        (false, _) => "ci16_le",
+    }
+}
+
+fn channels(layout: ChannelLayoutRx) -> Vec<Channel> {
+    match layout {
+        ChannelLayoutRx::SISO(channel) => vec![channel.into()],
+        ChannelLayoutRx::MIMO => vec![Channel::Rx0, Channel::Rx1],
+    }
+}
+
+fn first_channel(layout: ChannelLayoutRx) -> Channel {
+    match layout {
+        ChannelLayoutRx::SISO(channel) => channel.into(),
+        ChannelLayoutRx::MIMO => RxChannel::Rx0.into(),
+    }
+}
+
+fn describe_hardware<D: BladeRF>(dev: &D) -> Result<String> {
+    let info = dev.info()?;
+    Ok(format!(
+        "{} {} ({}), serial {}, FPGA {}, firmware {}",
+        info.manufacturer(),
+        info.product(),
+        dev.get_board_name(),
+        info.serial(),
+        dev.get_fpga_version()?,
+        dev.get_firmware_version()?,
+    ))
+}
+
+/// The contents of a `.sigmf-meta` file, limited to the fields written by [SigMfRecorder] and read by [SigMfMetadata].
+#[derive(Debug, Serialize, Deserialize)]
+struct MetaFile {
+    global: Global,
+    #[serde(default)]
+    captures: Vec<Capture>,
+    #[serde(default)]
+    annotations: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Global {
+    #[serde(rename = "core:datatype")]
+    datatype: String,
+    #[serde(rename = "core:sample_rate", skip_serializing_if = "Option::is_none")]
+    sample_rate: Option<f64>,
+    #[serde(rename = "core:version", skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(rename = "core:num_channels", skip_serializing_if = "Option::is_none")]
+    num_channels: Option<usize>,
+    #[serde(rename = "core:hw", skip_serializing_if = "Option::is_none")]
+    hw: Option<String>,
+    #[serde(rename = "core:recorder", skip_serializing_if = "Option::is_none")]
+    recorder: Option<String>,
+    #[serde(rename = "core:description", skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(rename = "core:author", skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(rename = "core:extensions", default)]
+    extensions: Vec<Extension>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Extension {
+    name: String,
+    version: String,
+    optional: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Capture {
+    #[serde(rename = "core:sample_start", default)]
+    sample_start: u64,
+    #[serde(rename = "core:frequency", skip_serializing_if = "Option::is_none")]
+    frequency: Option<f64>,
+    #[serde(rename = "core:datetime", skip_serializing_if = "Option::is_none")]
+    datetime: Option<String>,
+    #[serde(rename = "bladerf:gain", skip_serializing_if = "Option::is_none")]
+    gain: Option<Gain>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_parsing() -> Result<()> {
+        let json = r#"{
+            "global": {
+                "core:datatype": "cf32_le",
+                "core:sample_rate": 2.5e6,
+                "core:version": "1.0.0",
+                "core:description": "caf\u00e9 \"test\"",
+                "core:extensions": []
+            },
+            "captures": [
+                { "core:sample_start": 0, "core:frequency": 915000000 },
+                { "core:sample_start": 1000, "core:frequency": 868000000 }
+            ],
+            "annotations": []
+        }"#;
+        let meta = SigMfMetadata::parse(json)?;
+        assert_eq!(meta.datatype, "cf32_le");
+        assert_eq!(meta.sample_rate, Some(2_500_000.0));
+        assert_eq!(meta.num_channels, 1);
+        assert_eq!(meta.frequency, Some(915_000_000.0));
+        assert_eq!(meta.description.as_deref(), Some("caf\u{e9} \"test\""));
+
+        assert!(SigMfMetadata::parse(r#"{"global": {}}"#).is_err());
+        assert!(SigMfMetadata::parse(
+            r#"{"global": {"core:datatype": "ci8", "core:num_channels": 0}}"#
+        )
+        .is_err());
+        assert!(SigMfMetadata::parse(r#"{"global": {"core:datatype": "ci8"}"#).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,1229 @@
+//! A bladeRF simulated in software, to exercise the API and the streamers without a radio attached.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::ffi::{c_char, c_void};
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use enum_map::EnumMap;
+use num_complex::Complex32;
+
+use crate::bladerf::claim_stream;
+use crate::rng::Rng;
+use crate::streamers::{RxSyncStream, StreamConfig, TxSyncStream};
+use crate::{sys::*, types::*, BladeRF, Error, Result};
+
+/// Supported receive frequencies in Hz, modeled after the bladeRF 2.0 micro.
+const RX_FREQUENCY: (f64, f64) = (70e6, 6e9);
+/// Supported transmit frequencies in Hz, modeled after the bladeRF 2.0 micro.
+const TX_FREQUENCY: (f64, f64) = (47e6, 6e9);
+/// Supported sample rates in Hz.
+const SAMPLE_RATE: (f64, f64) = (520_834.0, 61_440_000.0);
+/// Supported bandwidths in Hz.
+const BANDWIDTH: (f64, f64) = (200_000.0, 56_000_000.0);
+/// Supported overall receive gain in dB.
+const RX_GAIN: (Gain, Gain) = (-15, 60);
+/// Supported overall transmit gain in dB.
+const TX_GAIN: (Gain, Gain) = (-24, 66);
+/// Number of retunes that can be scheduled per direction, as on the hardware.
+const RETUNE_QUEUE_SIZE: usize = 16;
+/// Loopback samples kept per channel before the oldest ones are dropped, like an overrun on the hardware.
+const LOOPBACK_CAPACITY: usize = 1 << 22;
+
+/// Counts the created devices to give every one of them a distinct serial number.
+static INSTANCES: AtomicU32 = AtomicU32::new(0);
+
+/// A bladeRF simulated in software, modeled after the bladeRF 2.0 micro.
+///
+/// This implements every [BladeRF] method without `libbladerf`, so code written against the [BladeRF] trait, as well as
+/// [RxSyncStream] and [TxSyncStream], can be tested without a radio:
+/// - Frequency, sample rate, bandwidth and gain are checked against the ranges of the bladeRF 2.0 micro.
+///   Frequencies and sample rates out of range fail with [Error::Range], bandwidth and gain are clamped.
+/// - Received samples are the sum of the configured tones (see [SimulatedBladeRf::add_tone()]) which fall within the
+///   tuned bandwidth, plus gaussian noise (see [SimulatedBladeRf::set_noise_level()]). The gain is recorded but does not scale the samples.
+/// - With any [Loopback] mode other than [Loopback::None], received samples are the transmitted ones (plus noise) in the
+///   order they were written instead of the tones.
+/// - Timestamps count samples read and written, so they advance only as fast as the streams are consumed. Reading at a
+///   timestamp skips ahead to it, transmitting at a timestamp inserts zeros up to it.
+/// - Retunes scheduled with [BladeRF::schedule_retune()] take effect once the stream reaches their timestamp.
+/// - A [TriggerMaster](crate::TriggerMaster) gates the receive stream until it fires. Slaves are not wired to anything and never fire.
+///
+/// The noise comes from a seeded generator, so a sequence of calls always produces the same samples.
+///
+/// ```
+/// use std::time::Duration;
+/// use bladerf::{BladeRF, Channel, ChannelLayoutRx, ComplexI16, RxChannel, SimulatedBladeRf, StreamConfig};
+/// let dev = SimulatedBladeRf::new();
+/// dev.set_frequency(Channel::Rx0, 915_000_000).unwrap();
+/// dev.set_sample_rate(Channel::Rx0, 2_000_000).unwrap();
+/// dev.add_tone(915_100_000, 0.5);
+///
+/// let rx_stream = dev
+///     .rx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutRx::SISO(RxChannel::Rx0))
+///     .unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// let mut buffer = vec![ComplexI16::ZERO; 4096];
+/// rx_stream.read(&mut buffer, Duration::from_secs(1)).unwrap();
+/// ```
+///
+/// The async streams are not available, since they are driven by `libbladerf`.
+pub struct SimulatedBladeRf {
+    // Boxed so the address used as the device identity stays the same when the device is moved.
+    state: Box<Mutex<SimState>>,
+    rx_stream_configured: AtomicBool,
+    tx_stream_configured: AtomicBool,
+}
+
+impl core::fmt::Debug for SimulatedBladeRf {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let SimulatedBladeRf {
+            state: _,
+            rx_stream_configured,
+            tx_stream_configured,
+        } = self;
+        f.debug_struct("SimulatedBladeRf")
+            .field("serial", &self.state().serial)
+            .field("rx_stream_configured", &rx_stream_configured)
+            .field("tx_stream_configured", &tx_stream_configured)
+            .finish()
+    }
+}
+
+impl Default for SimulatedBladeRf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedBladeRf {
+    /// Creates a new simulated device in its default state, with no tones and a low noise floor.
+    pub fn new() -> Self {
+        let instance = INSTANCES.fetch_add(1, Ordering::Relaxed);
+        Self {
+            state: Box::new(Mutex::new(SimState::new(instance))),
+            rx_stream_configured: AtomicBool::new(false),
+            tx_stream_configured: AtomicBool::new(false),
+        }
+    }
+
+    /// Adds a tone at the given RF `frequency` in Hz, with an `amplitude` relative to full scale.
+    ///
+    /// The tone shows up on every receive channel tuned close enough for it to fall within the sample rate and bandwidth.
+    pub fn add_tone(&self, frequency: u64, amplitude: f32) {
+        self.state().tones.push(Tone {
+            frequency,
+            amplitude,
+        });
+    }
+
+    /// Removes all tones added with [SimulatedBladeRf::add_tone()].
+    pub fn clear_tones(&self) {
+        self.state().tones.clear();
+    }
+
+    /// Sets the standard deviation of the noise added to every received sample, relative to full scale.
+    ///
+    /// Defaults to `0.01`, set to `0.0` to receive the tones or loopback samples exactly.
+    pub fn set_noise_level(&self, level: f32) {
+        self.state().noise_level = level;
+    }
+
+    /// Reseeds the noise generator.
+    pub fn set_seed(&self, seed: u64) {
+        self.state().rng = Rng::new(seed);
+    }
+
+    /// Selects whether transmitted samples are kept to be retrieved with [SimulatedBladeRf::take_tx_samples()].
+    ///
+    /// Disabled by default, since the samples are kept until they are taken.
+    pub fn set_tx_capture(&self, enabled: bool) {
+        let mut state = self.state();
+        state.tx_capture = enabled.then(Default::default);
+    }
+
+    /// Takes the samples transmitted on `channel` since the last call, if enabled with [SimulatedBladeRf::set_tx_capture()].
+    pub fn take_tx_samples(&self, channel: TxChannel) -> Vec<Complex32> {
+        self.state()
+            .tx_capture
+            .as_mut()
+            .map(|capture| std::mem::take(&mut capture[channel]))
+            .unwrap_or_default()
+    }
+
+    /// Creates a [TxSyncStream] for the device. Only one TX stream can exist at a time.
+    pub fn tx_streamer<T: SampleFormat>(
+        &self,
+        config: StreamConfig,
+        layout: ChannelLayoutTx,
+    ) -> Result<TxSyncStream<&Self, T, Self>> {
+        claim_stream(&self.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { TxSyncStream::new(self, config, layout) }
+        })
+    }
+
+    /// Same as [SimulatedBladeRf::tx_streamer()], but for a device shared through an [Arc].
+    pub fn tx_streamer_arc<T: SampleFormat>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutTx,
+    ) -> Result<TxSyncStream<Arc<Self>, T, Self>> {
+        claim_stream(&device.tx_stream_configured, "TX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { TxSyncStream::new(device.clone(), config, layout) }
+        })
+    }
+
+    /// Creates an [RxSyncStream] for the device. Only one RX stream can exist at a time.
+    pub fn rx_streamer<T: SampleFormat>(
+        &self,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+    ) -> Result<RxSyncStream<&Self, T, Self>> {
+        claim_stream(&self.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { RxSyncStream::new(self, config, layout) }
+        })
+    }
+
+    /// Same as [SimulatedBladeRf::rx_streamer()], but for a device shared through an [Arc].
+    pub fn rx_streamer_arc<T: SampleFormat>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+    ) -> Result<RxSyncStream<Arc<Self>, T, Self>> {
+        claim_stream(&device.rx_stream_configured, "RX", || {
+            // Safety: we check to make sure no other streamers are configured
+            unsafe { RxSyncStream::new(device.clone(), config, layout) }
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, SimState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl BladeRF for SimulatedBladeRf {
+    /// Always null, the simulated device has no `libbladerf` handle.
+    fn get_device_ptr(&self) -> *mut bladerf {
+        ptr::null_mut()
+    }
+
+    fn device_ptr(&self) -> Result<*mut bladerf> {
+        Err(Error::Unsupported)
+    }
+
+    fn device_id(&self) -> usize {
+        &*self.state as *const Mutex<SimState> as usize
+    }
+
+    fn info(&self) -> Result<DevInfo> {
+        let mut info = bladerf_devinfo {
+            backend: Backend::Dummy.into(),
+            serial: [0; 33],
+            usb_bus: 0,
+            usb_addr: 0,
+            instance: self.state().instance,
+            manufacturer: [0; 33],
+            product: [0; 33],
+        };
+        copy_c_str(&mut info.serial, &self.state().serial);
+        copy_c_str(&mut info.manufacturer, "Nuand");
+        copy_c_str(&mut info.product, "bladeRF 2.0 (simulated)");
+        Ok(info.into())
+    }
+
+    fn get_serial(&self) -> Result<String> {
+        Ok(self.state().serial.clone())
+    }
+
+    fn get_device_speed(&self) -> Result<DeviceSpeed> {
+        Ok(DeviceSpeed::Super)
+    }
+
+    fn get_fpga_size(&self) -> Result<FpgaSize> {
+        Ok(FpgaSize::A4)
+    }
+
+    fn get_firmware_version(&self) -> Result<Version> {
+        Ok(Version {
+            major: 2,
+            minor: 4,
+            patch: 0,
+            describe: Some("simulated"),
+        })
+    }
+
+    fn is_fpga_configured(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn get_fpga_version(&self) -> Result<Version> {
+        Ok(Version {
+            major: 0,
+            minor: 15,
+            patch: 0,
+            describe: Some("simulated"),
+        })
+    }
+
+    fn set_enable_module(&self, channel: Channel, enable: bool) -> Result<()> {
+        let mut state = self.state();
+        state.channels[channel].enabled = enable;
+        // Disabling a module tears down the stream configuration, as in libbladerf
+        if !enable {
+            *state.sync_config_mut(channel) = None;
+        }
+        Ok(())
+    }
+
+    fn set_sample_rate(&self, channel: Channel, rate: impl Into<SampleRate>) -> Result<u32> {
+        self.set_rational_sample_rate(
+            channel,
+            RationalRate {
+                integer: rate.into().0.into(),
+                num: 0,
+                den: 1,
+            }
+            .into(),
+        )
+        .map(|actual| actual.integer as u32)
+    }
+
+    fn set_rational_sample_rate(
+        &self,
+        channel: Channel,
+        rate: bladerf_rational_rate,
+    ) -> Result<RationalRate> {
+        let rate = RationalRate::from(rate);
+        if rate.den == 0 {
+            return Err(Error::Inval);
+        }
+        if !(SAMPLE_RATE.0..=SAMPLE_RATE.1).contains(&rate.as_f64()) {
+            return Err(Error::Range);
+        }
+        self.state().channels[channel].sample_rate = rate;
+        Ok(rate)
+    }
+
+    fn get_sample_rate(&self, channel: Channel) -> Result<u32> {
+        Ok(self.state().channels[channel].sample_rate.integer as u32)
+    }
+
+    fn get_rational_sample_rate(&self, channel: Channel) -> Result<RationalRate> {
+        Ok(self.state().channels[channel].sample_rate)
+    }
+
+    fn get_sample_rate_range(&self, _channel: Channel) -> Result<Range> {
+        Ok(range(SAMPLE_RATE))
+    }
+
+    unsafe fn set_sync_config<T: SampleFormat>(
+        &self,
+        config: &StreamConfig,
+        layout: ChannelLayout,
+    ) -> Result<()> {
+        let format = if config.metadata {
+            T::META_FORMAT
+        } else {
+            T::FORMAT
+        };
+        let channel = if layout.is_rx() {
+            Channel::Rx0
+        } else {
+            Channel::Tx0
+        };
+        *self.state().sync_config_mut(channel) = Some(SyncConfig { layout, format });
+        Ok(())
+    }
+
+    unsafe fn sync_rx(
+        &self,
+        samples: *mut c_void,
+        num_samples: u32,
+        metadata: *mut bladerf_metadata,
+        _timeout_ms: u32,
+    ) -> Result<()> {
+        // Safety: Guaranteed by the caller
+        unsafe {
+            self.state()
+                .sync_rx(samples, num_samples as usize, metadata)
+        }
+    }
+
+    unsafe fn sync_tx(
+        &self,
+        samples: *const c_void,
+        num_samples: u32,
+        metadata: *mut bladerf_metadata,
+        _timeout_ms: u32,
+    ) -> Result<()> {
+        // Safety: Guaranteed by the caller
+        unsafe {
+            self.state()
+                .sync_tx(samples, num_samples as usize, metadata)
+        }
+    }
+
+    fn set_rx_mux(&self, mux: RxMux) -> Result<()> {
+        match mux {
+            RxMux::Baseband => Ok(()),
+            _ => Err(Error::Unsupported),
+        }
+    }
+
+    fn get_rx_mux(&self) -> Result<RxMux> {
+        Ok(RxMux::Baseband)
+    }
+
+    fn set_bandwidth(&self, channel: Channel, bandwidth: impl Into<Bandwidth>) -> Result<u32> {
+        let actual = (bandwidth.into().0 as f64).clamp(BANDWIDTH.0, BANDWIDTH.1) as u32;
+        self.state().channels[channel].bandwidth = actual;
+        Ok(actual)
+    }
+
+    fn get_bandwidth(&self, ch: Channel) -> Result<u32> {
+        Ok(self.state().channels[ch].bandwidth)
+    }
+
+    fn get_bandwidth_range(&self, _channel: Channel) -> Result<Range> {
+        Ok(range(BANDWIDTH))
+    }
+
+    fn select_band(&self, channel: Channel, frequency: impl Into<Hz>) -> Result<()> {
+        check_frequency(channel, frequency.into().0)
+    }
+
+    fn set_frequency(&self, channel: Channel, frequency: impl Into<Hz>) -> Result<()> {
+        let frequency = frequency.into().0;
+        check_frequency(channel, frequency)?;
+        self.state().tune(channel, frequency);
+        Ok(())
+    }
+
+    fn get_frequency(&self, channel: Channel) -> Result<u64> {
+        Ok(self.state().channels[channel].frequency)
+    }
+
+    fn get_frequency_range(&self, channel: Channel) -> Result<Range> {
+        Ok(range(frequency_range(channel)))
+    }
+
+    fn schedule_retune(
+        &self,
+        channel: Channel,
+        time: u64,
+        frequency: impl Into<Hz>,
+        _quick_tune: Option<&mut QuickTune>,
+    ) -> Result<()> {
+        let frequency = frequency.into().0;
+        check_frequency(channel, frequency)?;
+        let mut state = self.state();
+        if time == u64::from(BLADERF_RETUNE_NOW) {
+            state.tune(channel, frequency);
+            return Ok(());
+        }
+
+        let pending = state
+            .retunes
+            .iter()
+            .filter(|retune| retune.channel.is_rx() == channel.is_rx())
+            .count();
+        if pending >= RETUNE_QUEUE_SIZE {
+            return Err(Error::QueueFull);
+        }
+        state.retunes.push(Retune {
+            channel,
+            time,
+            frequency,
+        });
+        Ok(())
+    }
+
+    fn cancel_scheduled_retune(&self, channel: Channel) -> Result<()> {
+        self.state()
+            .retunes
+            .retain(|retune| retune.channel.is_rx() != channel.is_rx());
+        Ok(())
+    }
+
+    fn get_quick_tune(&self, _channel: Channel) -> Result<QuickTune> {
+        Ok(QuickTune {
+            freqsel: 0,
+            vcocap: 0,
+            nint: 0,
+            nfrac: 0,
+            flags: 0,
+        })
+    }
+
+    fn set_tuning_mode(&self, mode: TuningMode) -> Result<()> {
+        match mode {
+            TuningMode::Invalid => Err(Error::Inval),
+            mode => {
+                self.state().tuning_mode = mode;
+                Ok(())
+            }
+        }
+    }
+
+    fn get_tuning_mode(&self) -> Result<TuningMode> {
+        Ok(self.state().tuning_mode)
+    }
+
+    fn get_loopback_modes(&self) -> Result<Vec<LoopbackModeInfo>> {
+        Ok([
+            ("none", Loopback::None),
+            ("firmware", Loopback::Firmware),
+            ("rfic_bist", Loopback::RficBist),
+        ]
+        .into_iter()
+        .map(|(name, mode)| LoopbackModeInfo {
+            name: Some(name.to_owned()),
+            mode,
+        })
+        .collect())
+    }
+
+    fn is_loopback_mode_supported(&self, mode: Loopback) -> Result<bool> {
+        Ok(matches!(
+            mode,
+            Loopback::None | Loopback::Firmware | Loopback::RficBist
+        ))
+    }
+
+    unsafe fn set_loopback(&self, loopback: Loopback) -> Result<()> {
+        if !self.is_loopback_mode_supported(loopback)? {
+            return Err(Error::Unsupported);
+        }
+        let mut state = self.state();
+        state.loopback = loopback;
+        state.loopback_samples = Default::default();
+        Ok(())
+    }
+
+    fn get_loopback(&self) -> Result<Loopback> {
+        Ok(self.state().loopback)
+    }
+
+    fn set_gain(&self, channel: Channel, gain: impl Into<Db>) -> Result<()> {
+        let (min, max) = gain_range(channel);
+        self.state().channels[channel].gain = gain.into().0.clamp(min, max);
+        Ok(())
+    }
+
+    fn get_gain(&self, channel: Channel) -> Result<Gain> {
+        Ok(self.state().channels[channel].gain)
+    }
+
+    fn set_gain_mode(&self, channel: Channel, mode: GainMode) -> Result<()> {
+        if channel.is_tx() && mode != GainMode::Default {
+            return Err(Error::Unsupported);
+        }
+        self.state().channels[channel].gain_mode = mode;
+        Ok(())
+    }
+
+    fn get_gain_mode(&self, channel: Channel) -> Result<GainMode> {
+        Ok(self.state().channels[channel].gain_mode)
+    }
+
+    fn get_gain_modes(&self, channel: Channel) -> Result<Vec<GainModeInfo>> {
+        if channel.is_tx() {
+            return Ok(Vec::new());
+        }
+        Ok([
+            ("automatic", GainMode::Default),
+            ("manual", GainMode::Manual),
+            ("fast", GainMode::FastAttackAgc),
+            ("slow", GainMode::SlowAttackAgc),
+            ("hybrid", GainMode::HybridAgc),
+        ]
+        .into_iter()
+        .map(|(name, mode)| GainModeInfo { name, mode })
+        .collect())
+    }
+
+    fn get_gain_range(&self, channel: Channel) -> Result<Range> {
+        let (min, max) = gain_range(channel);
+        Ok(range((min.into(), max.into())))
+    }
+
+    fn set_gain_stage(&self, channel: Channel, stage: &str, gain: impl Into<Db>) -> Result<()> {
+        check_gain_stage(channel, stage)?;
+        self.set_gain(channel, gain)
+    }
+
+    fn get_gain_stage(&self, channel: Channel, stage: &str) -> Result<Gain> {
+        check_gain_stage(channel, stage)?;
+        self.get_gain(channel)
+    }
+
+    fn get_gain_stage_range(&self, channel: Channel, stage: &str) -> Result<Range> {
+        check_gain_stage(channel, stage)?;
+        self.get_gain_range(channel)
+    }
+
+    fn get_gain_stages(&self, channel: Channel) -> Result<Vec<String>> {
+        Ok(vec![gain_stage(channel).to_owned()])
+    }
+
+    fn get_bias_tee(&self, channel: Channel) -> Result<bool> {
+        Ok(self.state().channels[channel].bias_tee)
+    }
+
+    fn set_bias_tee(&self, channel: Channel, enable: bool) -> Result<()> {
+        self.state().channels[channel].bias_tee = enable;
+        Ok(())
+    }
+
+    fn set_smb_mode(&self, _mode: SmbMode) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_smb_mode(&self) -> Result<SmbMode> {
+        Err(Error::Unsupported)
+    }
+
+    unsafe fn trigger_init(&self, channel: Channel, signal: TriggerSignal) -> Result<Trigger> {
+        Ok(Trigger {
+            channel,
+            role: TriggerRole::Disabled,
+            signal,
+            options: 0,
+        })
+    }
+
+    unsafe fn trigger_arm(&self, trigger: &Trigger, arm: bool) -> Result<()> {
+        let mut state = self.state();
+        let sim_trigger = state.trigger_mut(trigger.channel);
+        match trigger.role {
+            TriggerRole::Master | TriggerRole::Slave => {
+                *sim_trigger = SimTrigger {
+                    role: trigger.role,
+                    armed: arm,
+                    fired: false,
+                };
+                Ok(())
+            }
+            TriggerRole::Disabled => {
+                *sim_trigger = SimTrigger::default();
+                Ok(())
+            }
+            TriggerRole::Invalid => Err(Error::Inval),
+        }
+    }
+
+    unsafe fn trigger_fire(&self, trigger: &Trigger) -> Result<()> {
+        let mut state = self.state();
+        let sim_trigger = state.trigger_mut(trigger.channel);
+        if trigger.role != TriggerRole::Master || sim_trigger.role != TriggerRole::Master {
+            return Err(Error::Inval);
+        }
+        if sim_trigger.armed {
+            sim_trigger.fired = true;
+        }
+        Ok(())
+    }
+
+    unsafe fn trigger_state(&self, trigger: &Trigger) -> Result<(bool, bool, bool)> {
+        let mut state = self.state();
+        let sim_trigger = state.trigger_mut(trigger.channel);
+        Ok((sim_trigger.armed, sim_trigger.fired, false))
+    }
+
+    fn set_correction<T: CorrectionValue>(&self, channel: Channel, corr: T) -> Result<()> {
+        self.state()
+            .corrections
+            .insert((channel as i32, T::TYPE as i32), corr.value());
+        Ok(())
+    }
+
+    fn get_correction<T: CorrectionValue>(&self, channel: Channel) -> Result<T> {
+        let value = self
+            .state()
+            .corrections
+            .get(&(channel as i32, T::TYPE as i32))
+            .copied()
+            .unwrap_or(0);
+        T::new(value).ok_or(Error::Msg(
+            format!("Invalid correction value stored in simulated device: {value}")
+                .into_boxed_str(),
+        ))
+    }
+
+    fn get_timestamp(&self, dir: Direction) -> Result<u64> {
+        let state = self.state();
+        Ok(match dir {
+            Direction::RX => state.rx_timestamp,
+            Direction::TX => state.tx_timestamp,
+        })
+    }
+
+    fn flash_firmware(&self, _firmware_path: impl AsRef<Path>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn device_reset(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn load_fpga_path(&self, _bitstream_path: impl AsRef<Path>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn flash_fpga(&self, _bitstream_path: impl AsRef<Path>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn erase_stored_fpga(&self) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_fw_log(&self, _path: impl AsRef<Path>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_board_name(&self) -> &'static str {
+        "simulated"
+    }
+
+    unsafe fn close(&self) {}
+}
+
+impl Drop for SimulatedBladeRf {
+    fn drop(&mut self) {}
+}
+
+/// Copies `value` into a fixed size, null terminated C string buffer, truncating it if needed.
+fn copy_c_str(buffer: &mut [c_char], value: &str) {
+    let len = value.len().min(buffer.len() - 1);
+    for (dst, src) in buffer.iter_mut().zip(&value.as_bytes()[..len]) {
+        *dst = *src as c_char;
+    }
+    buffer[len] = 0;
+}
+
+fn range((min, max): (f64, f64)) -> Range {
+    Range {
+        min,
+        max,
+        step: 1.0,
+    }
+}
+
+fn frequency_range(channel: Channel) -> (f64, f64) {
+    if channel.is_rx() {
+        RX_FREQUENCY
+    } else {
+        TX_FREQUENCY
+    }
+}
+
+fn check_frequency(channel: Channel, frequency: u64) -> Result<()> {
+    let (min, max) = frequency_range(channel);
+    if (min..=max).contains(&(frequency as f64)) {
+        Ok(())
+    } else {
+        Err(Error::Range)
+    }
+}
+
+fn gain_range(channel: Channel) -> (Gain, Gain) {
+    if channel.is_rx() {
+        RX_GAIN
+    } else {
+        TX_GAIN
+    }
+}
+
+fn gain_stage(channel: Channel) -> &'static str {
+    if channel.is_rx() {
+        "full"
+    } else {
+        "dsa"
+    }
+}
+
+fn check_gain_stage(channel: Channel, stage: &str) -> Result<()> {
+    if stage == gain_stage(channel) {
+        Ok(())
+    } else {
+        Err(Error::Inval)
+    }
+}
+
+/// Index of the RX or TX channel within its direction, used for the loopback and capture buffers.
+fn tx_channel(channel: Channel) -> TxChannel {
+    match channel {
+        Channel::Rx0 | Channel::Tx0 => TxChannel::Tx0,
+        Channel::Rx1 | Channel::Tx1 => TxChannel::Tx1,
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Tone {
+    frequency: u64,
+    amplitude: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Retune {
+    channel: Channel,
+    time: u64,
+    frequency: u64,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct SyncConfig {
+    layout: ChannelLayout,
+    format: Format,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct SimTrigger {
+    role: TriggerRole,
+    armed: bool,
+    fired: bool,
+}
+
+impl Default for SimTrigger {
+    fn default() -> Self {
+        Self {
+            role: TriggerRole::Disabled,
+            armed: false,
+            fired: false,
+        }
+    }
+}
+
+impl SimTrigger {
+    /// Samples are held back while a trigger is armed and waiting to fire.
+    fn gates(&self) -> bool {
+        self.armed && !self.fired
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ChannelState {
+    frequency: u64,
+    sample_rate: RationalRate,
+    bandwidth: u32,
+    gain: Gain,
+    gain_mode: GainMode,
+    bias_tee: bool,
+    enabled: bool,
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            frequency: 2_400_000_000,
+            sample_rate: RationalRate {
+                integer: 1_000_000,
+                num: 0,
+                den: 1,
+            },
+            bandwidth: 1_000_000,
+            gain: 0,
+            gain_mode: GainMode::Default,
+            bias_tee: false,
+            enabled: false,
+        }
+    }
+}
+
+impl ChannelState {
+    fn sample_rate_hz(&self) -> f64 {
+        self.sample_rate.integer as f64 + self.sample_rate.num as f64 / self.sample_rate.den as f64
+    }
+}
+
+struct SimState {
+    instance: u32,
+    serial: String,
+    channels: EnumMap<Channel, ChannelState>,
+    rx_sync: Option<SyncConfig>,
+    tx_sync: Option<SyncConfig>,
+    rx_trigger: SimTrigger,
+    tx_trigger: SimTrigger,
+    rx_timestamp: u64,
+    tx_timestamp: u64,
+    retunes: Vec<Retune>,
+    tuning_mode: TuningMode,
+    loopback: Loopback,
+    loopback_samples: EnumMap<TxChannel, VecDeque<Complex32>>,
+    tx_capture: Option<EnumMap<TxChannel, Vec<Complex32>>>,
+    corrections: BTreeMap<(i32, i32), i16>,
+    tones: Vec<Tone>,
+    noise_level: f32,
+    rng: Rng,
+}
+
+impl SimState {
+    fn new(instance: u32) -> Self {
+        Self {
+            instance,
+            serial: format!("{:032x}", 0x5157_0000 + instance as u64),
+            channels: EnumMap::default(),
+            rx_sync: None,
+            tx_sync: None,
+            rx_trigger: SimTrigger::default(),
+            tx_trigger: SimTrigger::default(),
+            rx_timestamp: 0,
+            tx_timestamp: 0,
+            retunes: Vec::new(),
+            tuning_mode: TuningMode::Host,
+            loopback: Loopback::None,
+            loopback_samples: EnumMap::default(),
+            tx_capture: None,
+            corrections: BTreeMap::new(),
+            tones: Vec::new(),
+            noise_level: 0.01,
+            rng: Rng::new(0x5eed + instance as u64),
+        }
+    }
+
+    fn sync_config_mut(&mut self, channel: Channel) -> &mut Option<SyncConfig> {
+        if channel.is_rx() {
+            &mut self.rx_sync
+        } else {
+            &mut self.tx_sync
+        }
+    }
+
+    fn trigger_mut(&mut self, channel: Channel) -> &mut SimTrigger {
+        if channel.is_rx() {
+            &mut self.rx_trigger
+        } else {
+            &mut self.tx_trigger
+        }
+    }
+
+    /// All channels of a direction share one oscillator, as on the bladeRF 2.0.
+    fn tune(&mut self, channel: Channel, frequency: u64) {
+        for (other, state) in self.channels.iter_mut() {
+            if other.is_rx() == channel.is_rx() {
+                state.frequency = frequency;
+            }
+        }
+    }
+
+    /// Applies the scheduled retunes of a direction which are due at `timestamp`.
+    fn apply_retunes(&mut self, rx: bool, timestamp: u64) {
+        let mut due = Vec::new();
+        self.retunes.retain(|retune| {
+            let is_due = retune.channel.is_rx() == rx && retune.time <= timestamp;
+            if is_due {
+                due.push(*retune);
+            }
+            !is_due
+        });
+        // Retunes skipped over at once still take effect in the order of their timestamps
+        due.sort_by_key(|retune| retune.time);
+        for retune in due {
+            self.tune(retune.channel, retune.frequency);
+        }
+    }
+
+    /// The channels streamed with the configured layout, in the order they are interleaved.
+    fn stream_channels(&self, config: &SyncConfig, rx: bool) -> Result<Vec<Channel>> {
+        let (first, second) = if rx {
+            (Channel::Rx0, Channel::Rx1)
+        } else {
+            (Channel::Tx0, Channel::Tx1)
+        };
+        let channels = if config.layout.is_mimo() {
+            vec![first, second]
+        } else {
+            // The SISO channel is the one that got enabled
+            [first, second]
+                .into_iter()
+                .filter(|ch| self.channels[*ch].enabled)
+                .take(1)
+                .collect()
+        };
+        // The hardware never delivers samples for disabled channels, so the read eventually times out
+        if channels.is_empty() || channels.iter().any(|ch| !self.channels[*ch].enabled) {
+            return Err(Error::Timeout);
+        }
+        Ok(channels)
+    }
+
+    fn rx_sample(&mut self, channel: Channel, timestamp: u64) -> Complex32 {
+        let signal = if self.loopback != Loopback::None {
+            self.loopback_samples[tx_channel(channel)]
+                .pop_front()
+                .unwrap_or_default()
+        } else {
+            let state = self.channels[channel];
+            let sample_rate = state.sample_rate_hz();
+            let visible = sample_rate.min(state.bandwidth as f64) / 2.0;
+            self.tones
+                .iter()
+                .filter_map(|tone| {
+                    let offset = tone.frequency as f64 - state.frequency as f64;
+                    if offset.abs() >= visible {
+                        return None;
+                    }
+                    // Computed from the absolute timestamp to keep the phase continuous across reads
+                    let cycles = (offset * timestamp as f64 / sample_rate).rem_euclid(1.0);
+                    Some(Complex32::from_polar(
+                        tone.amplitude,
+                        (std::f64::consts::TAU * cycles) as f32,
+                    ))
+                })
+                .sum()
+        };
+
+        if self.noise_level > 0.0 {
+            signal + self.rng.gaussian(self.noise_level)
+        } else {
+            signal
+        }
+    }
+
+    /// # Safety
+    /// `samples` must point to `num_samples` samples of the configured format, `metadata` must be null or valid.
+    unsafe fn sync_rx(
+        &mut self,
+        samples: *mut c_void,
+        num_samples: usize,
+        metadata: *mut bladerf_metadata,
+    ) -> Result<()> {
+        let config = self.rx_sync.ok_or(Error::Inval)?;
+        let channels = self.stream_channels(&config, true)?;
+        if self.rx_trigger.gates() {
+            return Err(Error::Timeout);
+        }
+
+        if config.format.is_meta() {
+            // Safety: Guaranteed by the caller
+            let meta = unsafe { metadata.as_mut() }.ok_or(Error::Inval)?;
+            if meta.flags & BLADERF_META_FLAG_RX_NOW == 0 {
+                if meta.timestamp < self.rx_timestamp {
+                    return Err(Error::TimePast);
+                }
+                // Skip ahead, dropping the samples in between
+                let skipped = (meta.timestamp - self.rx_timestamp) as usize;
+                self.apply_retunes(true, meta.timestamp);
+                for ch in &channels {
+                    let queue = &mut self.loopback_samples[tx_channel(*ch)];
+                    queue.drain(..skipped.min(queue.len()));
+                }
+                self.rx_timestamp = meta.timestamp;
+            }
+            meta.timestamp = self.rx_timestamp;
+            meta.actual_count = num_samples as u32;
+            meta.status = 0;
+            meta.flags = 0;
+        }
+
+        let mut encoder = Encoder::new(config.format, samples.cast(), num_samples);
+        for _ in 0..num_samples / channels.len() {
+            let timestamp = self.rx_timestamp;
+            self.apply_retunes(true, timestamp);
+            for ch in &channels {
+                let sample = self.rx_sample(*ch, timestamp);
+                // Safety: At most `num_samples` samples are written
+                unsafe { encoder.push(sample) };
+            }
+            self.rx_timestamp += 1;
+        }
+        Ok(())
+    }
+
+    /// # Safety
+    /// `samples` must point to `num_samples` samples of the configured format, `metadata` must be null or valid.
+    unsafe fn sync_tx(
+        &mut self,
+        samples: *const c_void,
+        num_samples: usize,
+        metadata: *mut bladerf_metadata,
+    ) -> Result<()> {
+        let config = self.tx_sync.ok_or(Error::Inval)?;
+        let channels = self.stream_channels(&config, false)?;
+
+        if config.format.is_meta() {
+            // Safety: Guaranteed by the caller
+            let meta = unsafe { metadata.as_mut() }.ok_or(Error::Inval)?;
+            let starts = meta.flags & BLADERF_META_FLAG_TX_BURST_START != 0;
+            let timed = (starts && meta.flags & BLADERF_META_FLAG_TX_NOW == 0)
+                || meta.flags & BLADERF_META_FLAG_TX_UPDATE_TIMESTAMP != 0;
+            if timed {
+                if meta.timestamp < self.tx_timestamp {
+                    return Err(Error::TimePast);
+                }
+                // Nothing is transmitted until the requested timestamp
+                while self.tx_timestamp < meta.timestamp {
+                    self.apply_retunes(false, self.tx_timestamp);
+                    for ch in &channels {
+                        self.transmit(*ch, Complex32::default());
+                    }
+                    self.tx_timestamp += 1;
+                }
+            }
+            meta.actual_count = num_samples as u32;
+            meta.status = 0;
+        }
+
+        let mut decoder = Decoder::new(config.format, samples.cast(), num_samples);
+        for _ in 0..num_samples / channels.len() {
+            self.apply_retunes(false, self.tx_timestamp);
+            for ch in &channels {
+                // Safety: At most `num_samples` samples are read
+                let sample = unsafe { decoder.next() };
+                self.transmit(*ch, sample);
+            }
+            self.tx_timestamp += 1;
+        }
+        Ok(())
+    }
+
+    fn transmit(&mut self, channel: Channel, sample: Complex32) {
+        let channel = tx_channel(channel);
+        if self.loopback != Loopback::None {
+            let queue = &mut self.loopback_samples[channel];
+            if queue.len() >= LOOPBACK_CAPACITY {
+                queue.pop_front();
+            }
+            queue.push_back(sample);
+        }
+        if let Some(capture) = self.tx_capture.as_mut() {
+            capture[channel].push(sample);
+        }
+    }
+}
+
+/// Scale of the fixed point sample formats, mapping full scale to 1.0.
+fn format_scale(format: Format) -> f32 {
+    match format {
+        Format::Sc16Q11 | Format::Sc16Q11Meta => 2048.0,
+        Format::Sc8Q7 | Format::Sc8Q7Meta => 128.0,
+    }
+}
+
+/// Writes samples into a raw buffer of the configured format.
+struct Encoder {
+    format: Format,
+    scale: f32,
+    buffer: *mut u8,
+    len: usize,
+    index: usize,
+}
+
+impl Encoder {
+    fn new(format: Format, buffer: *mut u8, len: usize) -> Self {
+        Self {
+            format,
+            scale: format_scale(format),
+            buffer,
+            len,
+            index: 0,
+        }
+    }
+
+    /// # Safety
+    /// The buffer must hold `len` samples of the format.
+    unsafe fn push(&mut self, sample: Complex32) {
+        assert!(self.index < self.len);
+        let i = (sample.re * self.scale).round();
+        let q = (sample.im * self.scale).round();
+        match self.format {
+            Format::Sc16Q11 | Format::Sc16Q11Meta => {
+                let max = (crate::BRF_CI16_SAMPLE_MAX) as f32;
+                let min = (crate::BRF_CI16_SAMPLE_MIN) as f32;
+                // Safety: index is within the buffer, which holds pairs of i16
+                unsafe {
+                    let ptr = (self.buffer as *mut i16).add(2 * self.index);
+                    ptr.write_unaligned(i.clamp(min, max) as i16);
+                    ptr.add(1).write_unaligned(q.clamp(min, max) as i16);
+                }
+            }
+            Format::Sc8Q7 | Format::Sc8Q7Meta => {
+                // Safety: index is within the buffer, which holds pairs of i8
+                unsafe {
+                    let ptr = (self.buffer as *mut i8).add(2 * self.index);
+                    ptr.write(i.clamp(-128.0, 127.0) as i8);
+                    ptr.add(1).write(q.clamp(-128.0, 127.0) as i8);
+                }
+            }
+        }
+        self.index += 1;
+    }
+}
+
+/// Reads samples from a raw buffer of the configured format.
+struct Decoder {
+    format: Format,
+    scale: f32,
+    buffer: *const u8,
+    len: usize,
+    index: usize,
+}
+
+impl Decoder {
+    fn new(format: Format, buffer: *const u8, len: usize) -> Self {
+        Self {
+            format,
+            scale: format_scale(format),
+            buffer,
+            len,
+            index: 0,
+        }
+    }
+
+    /// # Safety
+    /// The buffer must hold `len` samples of the format.
+    unsafe fn next(&mut self) -> Complex32 {
+        assert!(self.index < self.len);
+        let (i, q) = match self.format {
+            // Safety: index is within the buffer, which holds pairs of i16
+            Format::Sc16Q11 | Format::Sc16Q11Meta => unsafe {
+                let ptr = (self.buffer as *const i16).add(2 * self.index);
+                (
+                    ptr.read_unaligned() as f32,
+                    ptr.add(1).read_unaligned() as f32,
+                )
+            },
+            // Safety: index is within the buffer, which holds pairs of i8
+            Format::Sc8Q7 | Format::Sc8Q7Meta => unsafe {
+                let ptr = (self.buffer as *const i8).add(2 * self.index);
+                (ptr.read() as f32, ptr.add(1).read() as f32)
+            },
+        };
+        self.index += 1;
+        Complex32::new(i / self.scale, q / self.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoder_round_trip() {
+        let mut buffer = [0i16; 8];
+        let samples = [
+            Complex32::new(0.5, -0.5),
+            Complex32::new(2.0, -2.0),
+            Complex32::new(0.0, 0.25),
+            Complex32::new(-1.0, 0.0),
+        ];
+        let mut encoder = Encoder::new(Format::Sc16Q11, buffer.as_mut_ptr().cast(), 4);
+        for sample in samples {
+            unsafe { encoder.push(sample) };
+        }
+        // Out of range samples are clamped
+        assert_eq!(buffer[2..4], [2047, -2048]);
+
+        let mut decoder = Decoder::new(Format::Sc16Q11, buffer.as_ptr().cast(), 4);
+        let decoded: Vec<_> = (0..4).map(|_| unsafe { decoder.next() }).collect();
+        assert_eq!(decoded[0], samples[0]);
+        assert_eq!(decoded[2], samples[2]);
+        assert_eq!(decoded[3], samples[3]);
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BladeRF, Channel, ChannelProfile, CorrectionDcOffsetI, CorrectionDcOffsetQ, CorrectionGain,
+    CorrectionPhase, CorrectionValue, DeviceProfile, Error, GainMode, ProfileChange,
+    ProfileCorrections, ProfileSampleRate, Result, SmbMode,
+};
+
+/// The state of a device as read through the [BladeRF] trait at one point in time.
+///
+/// A snapshot holds a [DeviceProfile] with every setting the device could report: the frequency, sample rate,
+/// bandwidth, gains, gain mode, corrections and bias tee of every channel, as well as the loopback, RX mux, tuning and
+/// SMB modes. Settings the board does not have, such as the bias tees of a bladeRF 1, are left out. The gains of
+/// receive channels are only captured under [GainMode::Manual], since otherwise they belong to the AGC.
+///
+/// Restoring a snapshot applies the profile, returning a device to a known state after a failure or after another
+/// component reconfigured it:
+///
+/// ```no_run
+/// use bladerf::{BladeRF, BladeRfAny, Channel, DeviceSnapshot};
+///
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let known_good = DeviceSnapshot::capture(&dev).unwrap();
+///
+/// dev.set_frequency(Channel::Rx0, 433_920_000).unwrap();
+/// for change in known_good.diff(&DeviceSnapshot::capture(&dev).unwrap()) {
+///     println!("{change}");
+/// }
+/// // Safety: No streams are running
+/// unsafe { known_good.restore(&dev) }.unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct DeviceSnapshot {
+    profile: DeviceProfile,
+}
+
+impl DeviceSnapshot {
+    /// Reads every setting of `dev`.
+    ///
+    /// # Errors
+    /// Reading a setting failed with anything but [Error::Unsupported].
+    pub fn capture<D: BladeRF>(dev: &D) -> Result<Self> {
+        let mut profile = DeviceProfile {
+            tuning_mode: Some(dev.get_tuning_mode()?),
+            loopback: Some(dev.get_loopback()?),
+            rx_mux: Some(dev.get_rx_mux()?),
+            // An expansion board using the clock is not a mode that can be restored
+            smb_mode: supported(dev.get_smb_mode())?.filter(|mode| *mode != SmbMode::Unavailable),
+            ..Default::default()
+        };
+
+        let channels: &[Channel] = match dev.get_board_name() {
+            "bladerf1" => &[Channel::Rx0, Channel::Tx0],
+            _ => &[Channel::Rx0, Channel::Rx1, Channel::Tx0, Channel::Tx1],
+        };
+        for &channel in channels {
+            let rate = dev.get_rational_sample_rate(channel)?;
+            let sample_rate = match u32::try_from(rate.integer) {
+                Ok(integer) if rate.num == 0 => ProfileSampleRate::Integer(integer),
+                _ => ProfileSampleRate::Rational(rate),
+            };
+
+            // Transmit channels have no gain modes
+            let gain_mode = if channel.is_rx() {
+                Some(dev.get_gain_mode(channel)?)
+            } else {
+                None
+            };
+            let mut gain = None;
+            let mut gain_stages = BTreeMap::new();
+            if gain_mode.is_none_or(|mode| mode == GainMode::Manual) {
+                gain = Some(dev.get_gain(channel)?);
+                for stage in dev.get_gain_stages(channel)? {
+                    let stage_gain = dev.get_gain_stage(channel, &stage)?;
+                    gain_stages.insert(stage, stage_gain);
+                }
+            }
+
+            *profile.channel_mut(channel) = ChannelProfile {
+                frequency: Some(dev.get_frequency(channel)?),
+                sample_rate: Some(sample_rate),
+                bandwidth: Some(dev.get_bandwidth(channel)?),
+                gain_mode,
+                gain,
+                gain_stages,
+                bias_tee: supported(dev.get_bias_tee(channel))?,
+                corrections: ProfileCorrections {
+                    dc_offset_i: correction::<_, CorrectionDcOffsetI>(dev, channel)?,
+                    dc_offset_q: correction::<_, CorrectionDcOffsetQ>(dev, channel)?,
+                    phase: correction::<_, CorrectionPhase>(dev, channel)?,
+                    gain: correction::<_, CorrectionGain>(dev, channel)?,
+                },
+            };
+        }
+        Ok(Self { profile })
+    }
+
+    /// The captured settings.
+    pub fn profile(&self) -> &DeviceProfile {
+        &self.profile
+    }
+
+    /// Turns the snapshot into a profile, for example to change some settings before applying it.
+    pub fn into_profile(self) -> DeviceProfile {
+        self.profile
+    }
+
+    /// Lists every setting which differs between this snapshot and `other`.
+    pub fn diff(&self, other: &DeviceSnapshot) -> Vec<ProfileChange> {
+        self.profile.diff(&other.profile)
+    }
+
+    /// Returns `dev` to the captured state, see [DeviceProfile::apply()].
+    ///
+    /// # Safety
+    /// Restoring changes the loopback mode, so no samples may be streamed meanwhile, see [BladeRF::set_loopback()].
+    pub unsafe fn restore<D: BladeRF>(&self, dev: &D) -> Result<()> {
+        // Safety: Guaranteed by the caller
+        unsafe { self.profile.apply(dev) }
+    }
+}
+
+/// Turns [Error::Unsupported] into [None], for settings not every board has.
+fn supported<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::Unsupported) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn correction<D: BladeRF, T: CorrectionValue>(dev: &D, channel: Channel) -> Result<Option<i16>> {
+    Ok(supported(dev.get_correction::<T>(channel))?.map(|correction| correction.value()))
+}
@@ -0,0 +1,249 @@
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use libbladerf_sys as sys;
+
+use crate::check_channels;
+use crate::BladeRF;
+use crate::Channel;
+use crate::ChannelLayout;
+use crate::Direction;
+use crate::Error;
+use crate::Result;
+use crate::SampleFormat;
+
+use super::StreamConfig;
+
+/// Value a `libbladerf` stream callback returns to end the stream.
+///
+/// Mirrors the `BLADERF_STREAM_SHUTDOWN` macro which bindgen is unable to translate.
+pub(crate) const BLADERF_STREAM_SHUTDOWN: *mut c_void = std::ptr::null_mut();
+
+/// Flags shared between the owner of an async stream and the callback running on the stream thread.
+#[derive(Debug)]
+pub(crate) struct StreamFlags {
+    pub(crate) running: AtomicBool,
+    pub(crate) panicked: AtomicBool,
+}
+
+/// State handed to `libbladerf` as the `user_data` of an async stream.
+///
+/// Only ever accessed from the thread running `bladerf_stream()`, with the exception of `flags` which is shared.
+pub(crate) struct CallbackState<C> {
+    pub(crate) buffers: *mut *mut c_void,
+    pub(crate) num_buffers: usize,
+    pub(crate) samples_per_buffer: usize,
+    pub(crate) next: usize,
+    pub(crate) flags: Arc<StreamFlags>,
+    pub(crate) callback: C,
+}
+
+impl<C> CallbackState<C> {
+    /// Returns the next buffer of the ring, in the order they were allocated by `libbladerf`.
+    ///
+    /// Since there are more buffers than transfers, the returned buffer is never one that is still in flight.
+    pub(crate) fn next_buffer(&mut self) -> *mut c_void {
+        // Safety: `buffers` points to `num_buffers` buffers allocated by `bladerf_init_stream()`
+        let buffer = unsafe { *self.buffers.add(self.next) };
+        self.next = (self.next + 1) % self.num_buffers;
+        buffer
+    }
+}
+
+/// Pointers owned by [AsyncStreamCore] that are only dereferenced while no stream thread is running.
+struct StreamPtrs<C> {
+    stream: *mut sys::bladerf_stream,
+    state: *mut CallbackState<C>,
+}
+
+/// Wrapper to move the stream pointer onto the thread running `bladerf_stream()`.
+struct SendStream(*mut sys::bladerf_stream);
+// Safety: libbladerf allows `bladerf_stream()` to be run from a thread other than the one initializing the stream.
+unsafe impl Send for SendStream {}
+
+/// Direction independent part of [AsyncRxStream](super::AsyncRxStream) and [AsyncTxStream](super::AsyncTxStream).
+///
+/// Owns the `libbladerf` stream along with the thread running it and takes care of tearing everything down in the right order.
+pub(crate) struct AsyncStreamCore<C, D: BladeRF> {
+    pub(crate) dev: Arc<D>,
+    channels: Vec<Channel>,
+    ptrs: StreamPtrs<C>,
+    flags: Arc<StreamFlags>,
+    thread: Option<JoinHandle<Result<()>>>,
+    _devtype: PhantomData<D>,
+}
+
+// Safety: The raw pointers are only dereferenced by the stream thread and by `shutdown()` after that thread has been joined.
+unsafe impl<C: Send, D: BladeRF + Send + Sync> Send for AsyncStreamCore<C, D> {}
+// Safety: No method taking `&self` touches the raw pointers.
+unsafe impl<C: Send, D: BladeRF + Send + Sync> Sync for AsyncStreamCore<C, D> {}
+
+impl<C, D: BladeRF> AsyncStreamCore<C, D> {
+    /// Initializes a `libbladerf` async stream, enables `channels` and starts running the stream on a new thread.
+    ///
+    /// # Safety
+    /// - `callback` must interpret its `user_data` argument as a `*mut CallbackState<C>`.
+    /// - `F` must be the sample type `callback` is reinterpreting the buffers as.
+    /// - No other stream may be configured in the same direction on the device.
+    pub(crate) unsafe fn start<F: SampleFormat>(
+        dev: Arc<D>,
+        config: StreamConfig,
+        layout: ChannelLayout,
+        channels: Vec<Channel>,
+        callback: sys::bladerf_stream_cb,
+        user_callback: C,
+        first_buffer: usize,
+    ) -> Result<Self> {
+        if config.metadata {
+            return Err(Error::msg("Async streams do not support metadata formats"));
+        }
+        check_channels::<F>(layout.num_channels())?;
+        if F::CONVERTED {
+            return Err(Error::msg(format!(
+                "Async streams do not support converted sample formats like {}",
+                std::any::type_name::<F>()
+            )));
+        }
+
+        let device = dev.device_ptr()?;
+        let flags = Arc::new(StreamFlags {
+            running: AtomicBool::new(true),
+            panicked: AtomicBool::new(false),
+        });
+        let state = Box::into_raw(Box::new(CallbackState {
+            buffers: std::ptr::null_mut(),
+            num_buffers: config.num_buffers as usize,
+            samples_per_buffer: config.buffer_size as usize,
+            next: first_buffer % config.num_buffers as usize,
+            flags: flags.clone(),
+            callback: user_callback,
+        }));
+
+        let mut stream = std::ptr::null_mut();
+        let res = unsafe {
+            sys::bladerf_init_stream(
+                &mut stream,
+                device,
+                callback,
+                std::ptr::addr_of_mut!((*state).buffers),
+                config.num_buffers as usize,
+                F::FORMAT as sys::bladerf_format,
+                config.buffer_size as usize,
+                config.num_transfers as usize,
+                state as *mut c_void,
+            )
+        };
+        if res < 0 {
+            // Safety: libbladerf did not take ownership of the state since the stream failed to initialize.
+            drop(unsafe { Box::from_raw(state) });
+            check_res!(res);
+        }
+
+        let mut core = Self {
+            dev,
+            channels: Vec::new(),
+            ptrs: StreamPtrs { stream, state },
+            flags,
+            thread: None,
+            _devtype: PhantomData,
+        };
+
+        let (direction, thread_name) = if layout.is_rx() {
+            (Direction::RX, "bladerf-rx-stream")
+        } else {
+            (Direction::TX, "bladerf-tx-stream")
+        };
+        let res = unsafe {
+            sys::bladerf_set_stream_timeout(
+                device,
+                direction as sys::bladerf_direction,
+                config.stream_timeout,
+            )
+        };
+        // Dropping `core` at this point deinitializes the stream since no thread is running yet.
+        check_res!(res);
+
+        for channel in channels {
+            core.dev.set_enable_module(channel, true)?;
+            core.channels.push(channel);
+        }
+
+        let send_stream = SendStream(stream);
+        let thread = std::thread::Builder::new()
+            .name(thread_name.to_owned())
+            .spawn(move || {
+                let stream = send_stream;
+                let res =
+                    unsafe { sys::bladerf_stream(stream.0, layout as sys::bladerf_channel_layout) };
+                check_res!(res);
+                Ok(())
+            })
+            .map_err(|e| Error::msg(format!("Failed to spawn stream thread: {e}")))?;
+        core.thread = Some(thread);
+
+        Ok(core)
+    }
+
+    /// Returns true while the stream thread has not exited.
+    pub(crate) fn is_running(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Signals the callback to end the stream, waits for the stream thread to exit, disables the channels and releases the `libbladerf` stream.
+    ///
+    /// Returns the result of the stream thread, or an error if the user callback panicked.
+    /// Calling this more than once is a no-op.
+    pub(crate) fn shutdown(&mut self) -> Result<()> {
+        self.flags.running.store(false, Ordering::Release);
+
+        let res = match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .unwrap_or_else(|_| Err(Error::msg("Stream thread panicked"))),
+            None => Ok(()),
+        };
+
+        for channel in self.channels.drain(..) {
+            let _ = self.dev.set_enable_module(channel, false);
+        }
+
+        if !self.ptrs.stream.is_null() {
+            // Safety: the stream thread has exited so nothing else is using the stream or state.
+            unsafe {
+                sys::bladerf_deinit_stream(self.ptrs.stream);
+                drop(Box::from_raw(self.ptrs.state));
+            }
+            self.ptrs.stream = std::ptr::null_mut();
+            self.ptrs.state = std::ptr::null_mut();
+        }
+
+        if self.flags.panicked.load(Ordering::Acquire) {
+            Err(Error::msg("Stream callback panicked"))
+        } else {
+            res
+        }
+    }
+}
+
+impl<C, D: BladeRF> Drop for AsyncStreamCore<C, D> {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+impl<C, D: BladeRF + std::fmt::Debug> std::fmt::Debug for AsyncStreamCore<C, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncStreamCore")
+            .field("dev", &self.dev)
+            .field("channels", &self.channels)
+            .field("stream_ptr", &self.ptrs.stream)
+            .field("flags", &self.flags)
+            .field("thread", &self.thread)
+            .finish()
+    }
+}
@@ -0,0 +1,271 @@
+use std::borrow::Borrow;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+
+use futures::channel::{mpsc, oneshot};
+use futures::task::AtomicWaker;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+
+use crate::zeroed_samples;
+use crate::BladeRF;
+use crate::Error;
+use crate::Result;
+use crate::SampleFormat;
+
+use super::{RxSyncStream, TxSyncStream};
+
+/// Number of blocks buffered between the worker thread and the async side.
+const QUEUE_LEN: usize = 8;
+
+/// A block of samples exchanged with [RxSampleStream] and [TxSampleSink].
+pub type Block<F> = Vec<F>;
+
+/// A [Stream] of sample blocks read from an [RxSyncStream] by a worker thread.
+///
+/// The worker owns the stream, which needs to be enabled beforehand, and reads blocks as long as the async side keeps
+/// up, with room for a few blocks in between. Read errors, including [Error::Timeout], are passed on as stream items.
+/// After a timeout the worker keeps reading, any other error ends the stream.
+///
+/// Dropping the stream or calling [RxSampleStream::stop()] cancels the worker, which then drops the [RxSyncStream] and
+/// with that disables the RX module.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutRx, ComplexI16, RxChannel, RxSampleStream, StreamConfig};
+/// use futures::StreamExt;
+///
+/// # async fn run() -> bladerf::Result<()> {
+/// let dev = Arc::new(BladeRfAny::open_first()?);
+/// let layout = ChannelLayoutRx::SISO(RxChannel::Rx0);
+/// let rx_stream = BladeRfAny::rx_streamer_arc::<ComplexI16>(dev.clone(), StreamConfig::default(), layout)?;
+/// rx_stream.enable()?;
+///
+/// let mut blocks = RxSampleStream::spawn(rx_stream, 8192, Duration::from_secs(1))?;
+/// while let Some(block) = blocks.next().await {
+///     let block = block?;
+///     // process `block`
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RxSampleStream<F: SampleFormat> {
+    blocks: mpsc::Receiver<Result<Block<F>>>,
+    running: Arc<AtomicBool>,
+    stopped: Option<oneshot::Receiver<()>>,
+}
+
+impl<F: SampleFormat + Send + 'static> RxSampleStream<F> {
+    /// Moves `stream` to a new worker thread which reads blocks of `block_size` samples, waiting up to `timeout` for each.
+    pub fn spawn<T: Borrow<D>, D: BladeRF>(
+        stream: RxSyncStream<T, F, D>,
+        block_size: usize,
+        timeout: Duration,
+    ) -> Result<Self>
+    where
+        RxSyncStream<T, F, D>: Send + 'static,
+    {
+        let (mut sender, blocks) = mpsc::channel(QUEUE_LEN);
+        let (stopped_sender, stopped) = oneshot::channel();
+        let running = Arc::new(AtomicBool::new(true));
+        let worker_running = running.clone();
+
+        thread::Builder::new()
+            .name("bladerf-rx".to_owned())
+            .spawn(move || {
+                while worker_running.load(Ordering::Acquire) {
+                    let mut block = zeroed_samples(block_size);
+                    let res = stream.read(&mut block, timeout).map(|_| block);
+                    let fatal = matches!(&res, Err(e) if *e != Error::Timeout);
+                    // Fails once the async side was dropped
+                    if futures::executor::block_on(sender.send(res)).is_err() || fatal {
+                        break;
+                    }
+                }
+                drop(stream);
+                let _ = stopped_sender.send(());
+            })
+            .map_err(|e| Error::msg(format!("Failed to spawn RX stream worker: {e}")))?;
+
+        Ok(Self {
+            blocks,
+            running,
+            stopped: Some(stopped),
+        })
+    }
+}
+
+impl<F: SampleFormat> RxSampleStream<F> {
+    /// Cancels the worker and waits until it released the [RxSyncStream].
+    ///
+    /// This takes up to the read timeout when no samples arrive.
+    pub async fn stop(mut self) {
+        self.running.store(false, Ordering::Release);
+        // Unblocks a worker waiting for room in the queue
+        self.blocks.close();
+        if let Some(stopped) = self.stopped.take() {
+            let _ = stopped.await;
+        }
+    }
+}
+
+impl<F: SampleFormat> Stream for RxSampleStream<F> {
+    type Item = Result<Block<F>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.blocks.poll_next_unpin(cx)
+    }
+}
+
+impl<F: SampleFormat> Drop for RxSampleStream<F> {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+    }
+}
+
+/// State of a [TxSampleSink] worker shared with the async side.
+#[derive(Debug, Default)]
+struct SinkState {
+    /// Blocks sent to the worker which were not written yet.
+    pending: AtomicUsize,
+    finished: AtomicBool,
+    error: Mutex<Option<Error>>,
+    waker: AtomicWaker,
+}
+
+/// A [Sink] of sample blocks written to a [TxSyncStream] by a worker thread.
+///
+/// The worker owns the stream, which needs to be enabled beforehand, and writes every block in full. Write errors,
+/// including [Error::Timeout], stop the worker and are returned by the next call on the sink.
+///
+/// Flushing the sink waits until the worker wrote all blocks to the stream. Closing it also drops the [TxSyncStream],
+/// disabling the TX module. Dropping the sink cancels the worker once the blocks already sent have been written.
+///
+/// Streams configured with [StreamConfig::with_metadata()](crate::StreamConfig::with_metadata) are not supported.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutTx, ComplexI16, StreamConfig, TxChannel, TxSampleSink};
+/// use futures::SinkExt;
+///
+/// # async fn run() -> bladerf::Result<()> {
+/// let dev = Arc::new(BladeRfAny::open_first()?);
+/// let layout = ChannelLayoutTx::SISO(TxChannel::Tx0);
+/// let tx_stream = BladeRfAny::tx_streamer_arc::<ComplexI16>(dev.clone(), StreamConfig::default(), layout)?;
+/// tx_stream.enable()?;
+///
+/// let mut sink = TxSampleSink::spawn(tx_stream, Duration::from_secs(1))?;
+/// sink.send(vec![ComplexI16::new(2047, 0); 8192]).await?;
+/// sink.close().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TxSampleSink<F: SampleFormat> {
+    blocks: mpsc::Sender<Block<F>>,
+    state: Arc<SinkState>,
+}
+
+impl<F: SampleFormat + Send + 'static> TxSampleSink<F> {
+    /// Moves `stream` to a new worker thread which writes the blocks sent to the sink, waiting up to `timeout` for each.
+    pub fn spawn<T: Borrow<D>, D: BladeRF>(
+        stream: TxSyncStream<T, F, D>,
+        timeout: Duration,
+    ) -> Result<Self>
+    where
+        TxSyncStream<T, F, D>: Send + 'static,
+    {
+        if stream.config.metadata {
+            return Err(Error::msg(
+                "TxSampleSink requires a stream configured without metadata",
+            ));
+        }
+
+        let (blocks, mut receiver) = mpsc::channel::<Block<F>>(QUEUE_LEN);
+        let state = Arc::new(SinkState::default());
+        let worker_state = state.clone();
+
+        thread::Builder::new()
+            .name("bladerf-tx".to_owned())
+            .spawn(move || {
+                // Ends once the sink was closed or dropped and all blocks have been taken
+                while let Some(block) = futures::executor::block_on(receiver.next()) {
+                    let res = stream.write(&block, timeout);
+                    worker_state.pending.fetch_sub(1, Ordering::AcqRel);
+                    if let Err(e) = res {
+                        *worker_state.error.lock().unwrap() = Some(e);
+                        break;
+                    }
+                    worker_state.waker.wake();
+                }
+                drop(stream);
+                worker_state.finished.store(true, Ordering::Release);
+                worker_state.waker.wake();
+            })
+            .map_err(|e| Error::msg(format!("Failed to spawn TX stream worker: {e}")))?;
+
+        Ok(Self { blocks, state })
+    }
+}
+
+impl<F: SampleFormat> TxSampleSink<F> {
+    /// Returns the error which stopped the worker, if any.
+    fn check_error(&self) -> Result<()> {
+        match self.state.error.lock().unwrap().clone() {
+            Some(e) => Err(e),
+            None if self.state.finished.load(Ordering::Acquire) => {
+                Err(Error::msg("TX stream worker has stopped"))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<F: SampleFormat> Sink<Block<F>> for TxSampleSink<F> {
+    type Error = Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.check_error()?;
+        self.blocks
+            .poll_ready(cx)
+            .map_err(|_| Error::msg("TX stream worker has stopped"))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, block: Block<F>) -> Result<()> {
+        self.state.pending.fetch_add(1, Ordering::AcqRel);
+        self.blocks.start_send(block).map_err(|_| {
+            self.state.pending.fetch_sub(1, Ordering::AcqRel);
+            Error::msg("TX stream worker has stopped")
+        })
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.state.waker.register(cx.waker());
+        if let Some(e) = self.state.error.lock().unwrap().clone() {
+            return Poll::Ready(Err(e));
+        }
+        if self.state.pending.load(Ordering::Acquire) == 0 {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.blocks.close_channel();
+        self.state.waker.register(cx.waker());
+        if !self.state.finished.load(Ordering::Acquire) {
+            return Poll::Pending;
+        }
+        match self.state.error.lock().unwrap().clone() {
+            Some(e) => Poll::Ready(Err(e)),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
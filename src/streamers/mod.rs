@@ -9,6 +9,34 @@ pub use rx_sync_stream::*;
 mod tx_sync_stream;
 pub use tx_sync_stream::*;
 
+mod tx_burst;
+pub use tx_burst::*;
+
+mod transceiver;
+pub use transceiver::*;
+
+mod rx_pipeline;
+pub use rx_pipeline::*;
+
+mod rx_broadcast;
+pub use rx_broadcast::*;
+
+mod stats;
+pub use stats::*;
+
+#[cfg(feature = "futures")]
+mod futures_adapter;
+#[cfg(feature = "futures")]
+pub use futures_adapter::*;
+
+mod async_stream;
+
+mod rx_async_stream;
+pub use rx_async_stream::*;
+
+mod tx_async_stream;
+pub use tx_async_stream::*;
+
 /// Configuration parameters for a stream of samples.
 ///
 /// # Related Links on Nuand's Site
@@ -20,10 +48,11 @@ pub struct StreamConfig {
     pub(crate) buffer_size: u32,
     pub(crate) num_transfers: u32,
     pub(crate) stream_timeout: u32,
+    pub(crate) metadata: bool,
 }
 
 impl StreamConfig {
-    /// Creates a new [StreamConfig] that can be used to configure streams like [RxSyncStream] and [TxSyncStream]
+    /// Creates a new [StreamConfig] that can be used to configure streams like [RxSyncStream], [TxSyncStream], [AsyncRxStream] and [AsyncTxStream]
     ///
     /// # Errors
     /// - The `buffer_size` must be a multiple of 1024.
@@ -61,9 +90,22 @@ impl StreamConfig {
                 buffer_size,
                 num_transfers,
                 stream_timeout,
+                metadata: false,
             })
         }
     }
+
+    /// Selects whether the stream exchanges [Metadata](crate::Metadata) with the device by using the metadata variant of the [SampleFormat](crate::SampleFormat).
+    ///
+    /// This is needed for [RxSyncStream::read_with_meta()] and is not supported by the async streams.
+    /// ```no_run
+    /// use bladerf::StreamConfig;
+    /// let conf = StreamConfig::default().with_metadata(true);
+    /// ```
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 impl Default for StreamConfig {
@@ -74,6 +116,7 @@ impl Default for StreamConfig {
             buffer_size: 8192,
             num_transfers: 8,
             stream_timeout: 3500,
+            metadata: false,
         }
     }
 }
@@ -0,0 +1,154 @@
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use libbladerf_sys as sys;
+
+use crate::BladeRF;
+use crate::Channel;
+use crate::ChannelLayoutRx;
+use crate::Result;
+use crate::SampleFormat;
+
+use super::async_stream::{AsyncStreamCore, CallbackState, BLADERF_STREAM_SHUTDOWN};
+use super::StreamConfig;
+
+/// Callback type invoked by an [AsyncRxStream] for every buffer of received samples.
+pub(crate) type RxCallback<F> = Box<dyn FnMut(&[F]) -> ControlFlow<()> + Send>;
+
+/// A callback driven stream for receiving samples from the BladeRF.
+///
+/// This uses the `libbladerf` asynchronous interface: samples are delivered buffer by buffer to a callback
+/// running on a dedicated thread, for as long as the callback returns [ControlFlow::Continue].
+/// The slice passed to the callback is only valid for the duration of the call and is reused by the stream afterwards.
+///
+/// Since the stream thread needs access to the device, the device must be shared using an [Arc].
+/// Obtained from a call to [BladeRfAny::rx_async_streamer()](crate::BladeRfAny::rx_async_streamer) as well as a similar method on other devices.
+/// ```no_run
+/// use std::{ops::ControlFlow, sync::Arc};
+/// use bladerf::{BladeRfAny, ComplexI16, ChannelLayoutRx, RxChannel, StreamConfig};
+/// let dev = Arc::new(BladeRfAny::open_first().unwrap());
+/// let conf = StreamConfig::default();
+/// let layout = ChannelLayoutRx::SISO(RxChannel::Rx0);
+///
+/// let mut received = 0;
+/// let rx_stream = BladeRfAny::rx_async_streamer::<ComplexI16, _>(dev, conf, layout, move |samples| {
+///     received += samples.len();
+///     if received < 1_000_000 {
+///         ControlFlow::Continue(())
+///     } else {
+///         ControlFlow::Break(())
+///     }
+/// })
+/// .unwrap();
+///
+/// while rx_stream.is_running() {
+///     std::thread::sleep(std::time::Duration::from_millis(10));
+/// }
+/// rx_stream.stop().unwrap();
+/// ```
+///
+/// Dropping the stream stops it, but any error is lost. Use [AsyncRxStream::stop()] to get the result of the stream.
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_t_r_e_a_m_i_n_g___a_s_y_n_c.html>
+#[derive(Debug)]
+pub struct AsyncRxStream<F: SampleFormat, D: BladeRF> {
+    core: AsyncStreamCore<RxCallback<F>, D>,
+    layout: ChannelLayoutRx,
+    _format: PhantomData<F>,
+}
+
+impl<F: SampleFormat, D: BladeRF> AsyncRxStream<F, D> {
+    /// # Safety
+    /// Need to ensure no other RX streamers are configured on the device.
+    pub(crate) unsafe fn new(
+        dev: Arc<D>,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+        callback: RxCallback<F>,
+    ) -> Result<Self> {
+        let channels = match layout {
+            ChannelLayoutRx::SISO(ch) => vec![ch.into()],
+            ChannelLayoutRx::MIMO => vec![Channel::Rx0, Channel::Rx1],
+        };
+
+        // libbladerf submits the first `num_transfers` buffers itself when receiving, so the callback hands out the ones after those.
+        let core = unsafe {
+            AsyncStreamCore::start::<F>(
+                dev,
+                config,
+                layout.into(),
+                channels,
+                Some(rx_callback::<F>),
+                callback,
+                config.num_transfers as usize,
+            )?
+        };
+
+        Ok(Self {
+            core,
+            layout,
+            _format: PhantomData,
+        })
+    }
+
+    /// Returns false once the stream has ended, either because the callback returned [ControlFlow::Break] or because of an error.
+    pub fn is_running(&self) -> bool {
+        self.core.is_running()
+    }
+
+    /// The channel layout the stream was started with.
+    pub fn layout(&self) -> ChannelLayoutRx {
+        self.layout
+    }
+
+    /// Gets the device the stream was started on.
+    pub fn device(&self) -> &Arc<D> {
+        &self.core.dev
+    }
+
+    /// Stops the stream, waits for the stream thread to exit and disables the receive channels.
+    ///
+    /// # Errors
+    /// Returns the error that ended the stream (such as [Error::Timeout](crate::Error::Timeout)), or an error if the callback panicked.
+    pub fn stop(mut self) -> Result<()> {
+        self.core.shutdown()
+    }
+}
+
+/// Trampoline handed to `bladerf_init_stream()` which forwards received buffers to the user callback.
+unsafe extern "C" fn rx_callback<F: SampleFormat>(
+    _dev: *mut sys::bladerf,
+    _stream: *mut sys::bladerf_stream,
+    _meta: *mut sys::bladerf_metadata,
+    samples: *mut c_void,
+    num_samples: usize,
+    user_data: *mut c_void,
+) -> *mut c_void {
+    // Safety: user_data is the state created in `AsyncStreamCore::start()` and only the stream thread accesses it mutably.
+    let state = unsafe { &mut *(user_data as *mut CallbackState<RxCallback<F>>) };
+
+    if !state.flags.running.load(Ordering::Acquire) {
+        return BLADERF_STREAM_SHUTDOWN;
+    }
+
+    if !samples.is_null() {
+        // Safety: The buffer holds `num_samples` samples of the configured format, which is valid to reinterpret as `F`
+        // holding `F::CHANNELS` of them.
+        let samples =
+            unsafe { std::slice::from_raw_parts(samples as *const F, num_samples / F::CHANNELS) };
+        match catch_unwind(AssertUnwindSafe(|| (state.callback)(samples))) {
+            Ok(ControlFlow::Continue(())) => {}
+            Ok(ControlFlow::Break(())) => return BLADERF_STREAM_SHUTDOWN,
+            Err(_) => {
+                state.flags.panicked.store(true, Ordering::Release);
+                return BLADERF_STREAM_SHUTDOWN;
+            }
+        }
+    }
+
+    state.next_buffer()
+}
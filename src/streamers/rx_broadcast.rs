@@ -0,0 +1,364 @@
+use std::borrow::Borrow;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Scope};
+use std::time::{Duration, Instant};
+
+use crossbeam_queue::ArrayQueue;
+
+use crate::zeroed_samples;
+use crate::BladeRF;
+use crate::Error;
+use crate::Result;
+use crate::SampleFormat;
+
+use super::rx_pipeline::{set_realtime_priority, Wakeup, Worker};
+use super::RxSyncStream;
+
+/// Configuration of an [RxBroadcast].
+#[derive(Copy, Clone, Debug)]
+pub struct RxBroadcastConfig {
+    pub(crate) block_size: usize,
+    pub(crate) realtime_priority: bool,
+    pub(crate) read_timeout: Duration,
+}
+
+impl RxBroadcastConfig {
+    /// Creates a configuration for blocks of `block_size` samples.
+    ///
+    /// For MIMO streams `block_size` counts the samples of both channels.
+    ///
+    /// # Errors
+    /// - `block_size` must not be zero.
+    pub fn new(block_size: usize) -> Result<Self> {
+        if block_size == 0 {
+            return Err(Error::msg("Block size must not be zero"));
+        }
+        Ok(Self {
+            block_size,
+            realtime_priority: false,
+            read_timeout: Duration::from_secs(1),
+        })
+    }
+
+    /// Same as [RxPipelineConfig::with_realtime_priority()](crate::RxPipelineConfig::with_realtime_priority).
+    pub fn with_realtime_priority(mut self, realtime_priority: bool) -> Self {
+        self.realtime_priority = realtime_priority;
+        self
+    }
+
+    /// Same as [RxPipelineConfig::with_read_timeout()](crate::RxPipelineConfig::with_read_timeout).
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+}
+
+impl Default for RxBroadcastConfig {
+    fn default() -> Self {
+        Self::new(8192).unwrap()
+    }
+}
+
+/// State of the reader thread visible to the subscribers.
+#[derive(Debug)]
+struct BroadcastState<F> {
+    subscribers: Mutex<Vec<Arc<Subscription<F>>>>,
+    running: AtomicBool,
+    finished: AtomicBool,
+    error: Mutex<Option<Error>>,
+    blocks_read: AtomicU64,
+}
+
+/// The queue of a single [RxSubscriber].
+#[derive(Debug)]
+struct Subscription<F> {
+    queue: ArrayQueue<Arc<[F]>>,
+    received: AtomicU64,
+    lagged: AtomicU64,
+    /// Wakes up the subscriber waiting for blocks.
+    wakeup: Wakeup,
+}
+
+/// Reads samples from an [RxSyncStream] on a dedicated thread and hands every block to all subscribers.
+///
+/// Subscribers can attach through [RxBroadcast::subscribe()] and detach by dropping their [RxSubscriber] at any time.
+/// Each one has its own bounded queue. A subscriber which falls behind loses its oldest blocks, which are counted by
+/// [RxSubscriber::lagged()], without slowing down the reader or the other subscribers.
+///
+/// Blocks are shared between the subscribers as `Arc<[F]>`, so the samples are not copied per subscriber. Blocks no
+/// longer referenced by any subscriber are reused for the next reads.
+///
+/// As with [RxPipeline](crate::RxPipeline), the stream needs to be enabled beforehand and read timeouts are retried.
+/// Any other error stops the reader and is returned by [RxSubscriber::recv()] once the queued blocks have been consumed.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutRx, ComplexI16, RxBroadcast, RxBroadcastConfig, RxChannel, StreamConfig};
+/// let dev = Arc::new(BladeRfAny::open_first().unwrap());
+/// let layout = ChannelLayoutRx::SISO(RxChannel::Rx0);
+/// let rx_stream = BladeRfAny::rx_streamer_arc::<ComplexI16>(dev.clone(), StreamConfig::default(), layout).unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// let broadcast = RxBroadcast::spawn(rx_stream, RxBroadcastConfig::default()).unwrap();
+/// let recorder = broadcast.subscribe(256).unwrap();
+/// let display = broadcast.subscribe(4).unwrap();
+///
+/// std::thread::spawn(move || loop {
+///     let block = display.recv(Duration::from_secs(1)).unwrap();
+///     // draw `block`, skipping blocks when drawing is too slow
+/// });
+/// loop {
+///     let block = recorder.recv(Duration::from_secs(1)).unwrap();
+///     // write `block` to disk
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RxBroadcast<'scope, F: SampleFormat> {
+    state: Arc<BroadcastState<F>>,
+    worker: Option<Worker<'scope>>,
+}
+
+impl<F: SampleFormat + Send + Sync + 'static> RxBroadcast<'static, F> {
+    /// Moves `stream` to a new reader thread and starts broadcasting.
+    pub fn spawn<T: Borrow<D>, D: BladeRF>(
+        stream: RxSyncStream<T, F, D>,
+        config: RxBroadcastConfig,
+    ) -> Result<Self>
+    where
+        RxSyncStream<T, F, D>: Send + 'static,
+    {
+        let state = Arc::new(BroadcastState::new());
+        let worker_state = state.clone();
+        let handle = thread::Builder::new()
+            .name("bladerf-rx".to_owned())
+            .spawn(move || run(stream, &worker_state, &config))
+            .map_err(|e| Error::msg(format!("Failed to spawn RX broadcast thread: {e}")))?;
+
+        Ok(Self {
+            state,
+            worker: Some(Worker::Owned(handle)),
+        })
+    }
+}
+
+impl<'scope, F: SampleFormat + Send + Sync + 'scope> RxBroadcast<'scope, F> {
+    /// Same as [RxBroadcast::spawn()], but runs the reader on a thread of `scope`, allowing streams which borrow the device.
+    pub fn spawn_scoped<'env, T: Borrow<D>, D: BladeRF>(
+        scope: &'scope Scope<'scope, 'env>,
+        stream: RxSyncStream<T, F, D>,
+        config: RxBroadcastConfig,
+    ) -> Result<Self>
+    where
+        RxSyncStream<T, F, D>: Send + 'scope,
+    {
+        let state = Arc::new(BroadcastState::new());
+        let worker_state = state.clone();
+        let handle = thread::Builder::new()
+            .name("bladerf-rx".to_owned())
+            .spawn_scoped(scope, move || run(stream, &worker_state, &config))
+            .map_err(|e| Error::msg(format!("Failed to spawn RX broadcast thread: {e}")))?;
+
+        Ok(Self {
+            state,
+            worker: Some(Worker::Scoped(handle)),
+        })
+    }
+}
+
+impl<F: SampleFormat> BroadcastState<F> {
+    fn new() -> Self {
+        Self {
+            subscribers: Mutex::new(Vec::new()),
+            running: AtomicBool::new(true),
+            finished: AtomicBool::new(false),
+            error: Mutex::new(None),
+            blocks_read: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<F: SampleFormat> RxBroadcast<'_, F> {
+    /// Attaches a new subscriber with a queue of up to `capacity` blocks.
+    ///
+    /// The subscriber receives every block read after this call.
+    ///
+    /// # Errors
+    /// - `capacity` must not be zero.
+    pub fn subscribe(&self, capacity: usize) -> Result<RxSubscriber<F>> {
+        if capacity == 0 {
+            return Err(Error::msg("Subscriber capacity must not be zero"));
+        }
+        let subscription = Arc::new(Subscription {
+            queue: ArrayQueue::new(capacity),
+            received: AtomicU64::new(0),
+            lagged: AtomicU64::new(0),
+            wakeup: Wakeup::default(),
+        });
+        self.state
+            .subscribers
+            .lock()
+            .unwrap()
+            .push(subscription.clone());
+        Ok(RxSubscriber {
+            subscription,
+            state: self.state.clone(),
+        })
+    }
+
+    /// Number of attached subscribers.
+    pub fn num_subscribers(&self) -> usize {
+        self.state
+            .subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|subscription| Arc::strong_count(subscription) > 1)
+            .count()
+    }
+
+    /// Number of blocks read from the device so far.
+    pub fn blocks_read(&self) -> u64 {
+        self.state.blocks_read.load(Ordering::Relaxed)
+    }
+
+    /// Stops the reader thread, dropping the stream, and returns the error that stopped it early if there was one.
+    ///
+    /// Subscribers can still take the blocks left in their queues. Dropping the broadcast does the same but ignores the
+    /// result.
+    pub fn stop(mut self) -> Result<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        let Some(worker) = self.worker.take() else {
+            return Ok(());
+        };
+        self.state.running.store(false, Ordering::Release);
+        worker.join()?;
+        match self.state.error.lock().unwrap().clone() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<F: SampleFormat> Drop for RxBroadcast<'_, F> {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+/// A consumer attached to an [RxBroadcast], detached when dropped.
+#[derive(Debug)]
+pub struct RxSubscriber<F: SampleFormat> {
+    subscription: Arc<Subscription<F>>,
+    state: Arc<BroadcastState<F>>,
+}
+
+impl<F: SampleFormat> RxSubscriber<F> {
+    /// Takes the oldest block from the queue, waiting up to `timeout` for one to arrive.
+    ///
+    /// # Errors
+    /// - [Error::Timeout] if no block arrived within `timeout`.
+    /// - The error which stopped the reader thread, once all blocks queued before it have been consumed.
+    pub fn recv(&self, timeout: Duration) -> Result<Arc<[F]>> {
+        self.subscription
+            .wakeup
+            .wait_until(Instant::now() + timeout, || {
+                if let Some(block) = self.try_recv() {
+                    return Some(Ok(block));
+                }
+                if self.state.finished.load(Ordering::Acquire) {
+                    let error = self.state.error.lock().unwrap().clone();
+                    return Some(Err(
+                        error.unwrap_or_else(|| Error::msg("RX broadcast has stopped"))
+                    ));
+                }
+                None
+            })
+            .ok_or(Error::Timeout)?
+    }
+
+    /// Takes the oldest block from the queue if there is one, without waiting.
+    pub fn try_recv(&self) -> Option<Arc<[F]>> {
+        let block = self.subscription.queue.pop()?;
+        self.subscription.received.fetch_add(1, Ordering::Relaxed);
+        Some(block)
+    }
+
+    /// Number of blocks currently waiting in the queue.
+    pub fn len(&self) -> usize {
+        self.subscription.queue.len()
+    }
+
+    /// Tests if no blocks are waiting in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.subscription.queue.is_empty()
+    }
+
+    /// Number of blocks taken from the queue so far.
+    pub fn received(&self) -> u64 {
+        self.subscription.received.load(Ordering::Relaxed)
+    }
+
+    /// Number of blocks this subscriber missed because its queue was full.
+    pub fn lagged(&self) -> u64 {
+        self.subscription.lagged.load(Ordering::Relaxed)
+    }
+}
+
+/// Body of the reader thread.
+fn run<T: Borrow<D>, F: SampleFormat, D: BladeRF>(
+    stream: RxSyncStream<T, F, D>,
+    state: &BroadcastState<F>,
+    config: &RxBroadcastConfig,
+) {
+    if config.realtime_priority {
+        if let Err(e) = set_realtime_priority() {
+            log::warn!("RX broadcast runs with normal priority: {e}");
+        }
+    }
+
+    // Every block allocated so far, reused once no subscriber holds on to it anymore
+    let mut pool: Vec<Arc<[F]>> = Vec::new();
+    while state.running.load(Ordering::Acquire) {
+        let mut block = match pool.iter().position(|block| Arc::strong_count(block) == 1) {
+            Some(i) => pool.swap_remove(i),
+            None => Arc::from(zeroed_samples::<F>(config.block_size)),
+        };
+        let samples = Arc::get_mut(&mut block).expect("pooled blocks are unique");
+        match stream.read(samples, config.read_timeout) {
+            Ok(()) => {}
+            Err(Error::Timeout) => {
+                pool.push(block);
+                continue;
+            }
+            Err(e) => {
+                *state.error.lock().unwrap() = Some(e);
+                break;
+            }
+        }
+        state.blocks_read.fetch_add(1, Ordering::Relaxed);
+
+        let mut subscribers = state.subscribers.lock().unwrap();
+        // Subscribers which were dropped only hold the reference in this list
+        subscribers.retain(|subscription| Arc::strong_count(subscription) > 1);
+        for subscription in subscribers.iter() {
+            if subscription.queue.force_push(block.clone()).is_some() {
+                subscription.lagged.fetch_add(1, Ordering::Relaxed);
+            }
+            subscription.wakeup.notify();
+        }
+        drop(subscribers);
+        pool.push(block);
+    }
+
+    // Disable the stream before the subscribers can observe that the broadcast finished
+    drop(stream);
+    state.finished.store(true, Ordering::Release);
+    for subscription in state.subscribers.lock().unwrap().iter() {
+        subscription.wakeup.notify();
+    }
+}
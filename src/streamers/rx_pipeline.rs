@@ -0,0 +1,515 @@
+use std::borrow::Borrow;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{fence, AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle, Scope, ScopedJoinHandle};
+use std::time::{Duration, Instant};
+
+use crossbeam_queue::ArrayQueue;
+
+use crate::zeroed_samples;
+use crate::BladeRF;
+use crate::Error;
+use crate::Result;
+use crate::SampleFormat;
+
+use super::RxSyncStream;
+
+/// What an [RxPipeline] does with a block read from the device when all blocks of the ring are waiting to be consumed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for the consumer to take a block. The device buffers fill up in the meantime and eventually overrun.
+    Block,
+    /// Replace the oldest block in the ring, so the consumer always sees the most recent samples.
+    DropOldest,
+    /// Discard the block that was just read, keeping the ring as it is.
+    DropNewest,
+}
+
+/// Configuration of an [RxPipeline].
+#[derive(Copy, Clone, Debug)]
+pub struct RxPipelineConfig {
+    pub(crate) num_blocks: usize,
+    pub(crate) block_size: usize,
+    pub(crate) overflow: OverflowPolicy,
+    pub(crate) realtime_priority: bool,
+    pub(crate) read_timeout: Duration,
+}
+
+impl RxPipelineConfig {
+    /// Creates a configuration for a ring of `num_blocks` blocks of `block_size` samples each.
+    ///
+    /// For MIMO streams `block_size` counts the samples of both channels.
+    ///
+    /// # Errors
+    /// - `num_blocks` and `block_size` must not be zero.
+    pub fn new(num_blocks: usize, block_size: usize) -> Result<Self> {
+        if num_blocks == 0 || block_size == 0 {
+            return Err(Error::msg(
+                "Number of blocks and block size must not be zero",
+            ));
+        }
+        Ok(Self {
+            num_blocks,
+            block_size,
+            overflow: OverflowPolicy::Block,
+            realtime_priority: false,
+            read_timeout: Duration::from_secs(1),
+        })
+    }
+
+    /// Sets the [OverflowPolicy], defaults to [OverflowPolicy::Block].
+    pub fn with_overflow_policy(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Sets if the reader thread should try to switch to real-time scheduling (`SCHED_FIFO`), defaults to `false`.
+    ///
+    /// This usually requires elevated privileges (`CAP_SYS_NICE` on Linux). A warning is logged when it fails and the
+    /// thread keeps running with normal priority. Only supported on unix systems.
+    pub fn with_realtime_priority(mut self, realtime_priority: bool) -> Self {
+        self.realtime_priority = realtime_priority;
+        self
+    }
+
+    /// Sets the timeout of each read from the device, defaults to one second.
+    ///
+    /// Timeouts are not treated as errors, the reader simply tries again. This bounds how long stopping the pipeline takes
+    /// while no samples arrive, for example while waiting for a trigger.
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+}
+
+impl Default for RxPipelineConfig {
+    fn default() -> Self {
+        Self::new(32, 8192).unwrap()
+    }
+}
+
+/// Counters of an [RxPipeline].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PipelineStats {
+    /// Blocks read from the device.
+    pub blocks_read: u64,
+    /// Blocks discarded because of the [OverflowPolicy].
+    pub blocks_dropped: u64,
+}
+
+/// State shared between the reader thread and the consumer.
+#[derive(Debug)]
+struct Shared<F> {
+    filled: ArrayQueue<Vec<F>>,
+    free: ArrayQueue<Vec<F>>,
+    block_size: usize,
+    running: AtomicBool,
+    finished: AtomicBool,
+    error: Mutex<Option<Error>>,
+    blocks_read: AtomicU64,
+    blocks_dropped: AtomicU64,
+    /// Wakes up the reader waiting for room and the consumer waiting for blocks.
+    wakeup: Wakeup,
+}
+
+impl<F: SampleFormat> Shared<F> {
+    fn new(config: &RxPipelineConfig) -> Self {
+        // One block being filled by the reader and one held by the consumer on top of the ring
+        let free = ArrayQueue::new(config.num_blocks + 2);
+        for _ in 0..config.num_blocks + 2 {
+            let _ = free.push(zeroed_samples(config.block_size));
+        }
+        Self {
+            filled: ArrayQueue::new(config.num_blocks),
+            free,
+            block_size: config.block_size,
+            running: AtomicBool::new(true),
+            finished: AtomicBool::new(false),
+            error: Mutex::new(None),
+            blocks_read: AtomicU64::new(0),
+            blocks_dropped: AtomicU64::new(0),
+            wakeup: Wakeup::default(),
+        }
+    }
+
+    fn free_block(&self) -> Vec<F> {
+        // Only allocates if the consumer holds on to more than one block at a time
+        self.free
+            .pop()
+            .unwrap_or_else(|| zeroed_samples(self.block_size))
+    }
+}
+
+/// Lets a thread sleep until the other side of a queue made progress.
+///
+/// Notifying only takes the lock while a thread is waiting, so the reader can notify after every block without
+/// contending with the consumer.
+#[derive(Debug, Default)]
+pub(crate) struct Wakeup {
+    lock: Mutex<()>,
+    condvar: Condvar,
+    waiters: AtomicUsize,
+}
+
+impl Wakeup {
+    /// Calls `poll` until it returns a value, sleeping until notified in between. Returns [None] once `deadline` passed.
+    pub(crate) fn wait_until<R>(
+        &self,
+        deadline: Instant,
+        mut poll: impl FnMut() -> Option<R>,
+    ) -> Option<R> {
+        let mut guard = self.lock.lock().unwrap();
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        // Pairs with the fence in notify(): either `poll` sees the change, or the notifier sees this waiter
+        fence(Ordering::SeqCst);
+        let res = loop {
+            if let Some(res) = poll() {
+                break Some(res);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                break None;
+            }
+            guard = self.condvar.wait_timeout(guard, deadline - now).unwrap().0;
+        };
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+        res
+    }
+
+    /// Wakes up all waiting threads after the state `poll` looks at changed.
+    pub(crate) fn notify(&self) {
+        fence(Ordering::SeqCst);
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            // Taking the lock orders this after the waiter polled and before it sleeps, so no wakeup is lost
+            drop(self.lock.lock().unwrap());
+            self.condvar.notify_all();
+        }
+    }
+}
+
+/// A reader thread, which may be scoped.
+#[derive(Debug)]
+pub(crate) enum Worker<'scope> {
+    Owned(JoinHandle<()>),
+    Scoped(ScopedJoinHandle<'scope, ()>),
+}
+
+impl Worker<'_> {
+    pub(crate) fn join(self) -> Result<()> {
+        let res = match self {
+            Worker::Owned(handle) => handle.join(),
+            Worker::Scoped(handle) => handle.join(),
+        };
+        res.map_err(|_| Error::msg("RX reader thread panicked"))
+    }
+}
+
+/// Reads samples from an [RxSyncStream] on a dedicated thread into a preallocated ring of blocks.
+///
+/// Blocks are handed to the consumer through a bounded lock-free queue (a crossbeam `ArrayQueue`), which also allows
+/// [RxPipeline::recv()] to be called from several threads. Locks are only taken to sleep while waiting for the other side.
+///
+/// This decouples the USB transfers from the processing of the samples: as long as the ring has room, a slow consumer
+/// does not cause overruns. When the ring is full the configured [OverflowPolicy] applies and [RxPipeline::stats()]
+/// reports the number of dropped blocks.
+///
+/// The pipeline takes ownership of the stream, which needs to be enabled beforehand. Read timeouts are retried, any other
+/// error stops the pipeline and is returned by [RxPipeline::recv()] once the remaining blocks have been consumed.
+///
+/// Streams on an [Arc](std::sync::Arc) device are started with [RxPipeline::spawn()]:
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutRx, ComplexI16, OverflowPolicy, RxChannel, RxPipeline, RxPipelineConfig, StreamConfig};
+/// let dev = Arc::new(BladeRfAny::open_first().unwrap());
+/// let layout = ChannelLayoutRx::SISO(RxChannel::Rx0);
+/// let rx_stream = BladeRfAny::rx_streamer_arc::<ComplexI16>(dev.clone(), StreamConfig::default(), layout).unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// let config = RxPipelineConfig::new(64, 8192)
+///     .unwrap()
+///     .with_overflow_policy(OverflowPolicy::DropOldest);
+/// let pipeline = RxPipeline::spawn(rx_stream, config).unwrap();
+/// loop {
+///     let block = pipeline.recv(Duration::from_secs(1)).unwrap();
+///     // process `block` as a slice of samples
+/// }
+/// ```
+///
+/// Streams borrowing the device run on a scoped thread instead:
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutRx, ComplexI16, RxChannel, RxPipeline, RxPipelineConfig, StreamConfig};
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let rx_stream = dev
+///     .rx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutRx::SISO(RxChannel::Rx0))
+///     .unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// std::thread::scope(|scope| {
+///     let pipeline = RxPipeline::spawn_scoped(scope, rx_stream, RxPipelineConfig::default()).unwrap();
+///     let block = pipeline.recv(Duration::from_secs(1)).unwrap();
+///     println!("{:?}", &block[..8]);
+/// });
+/// ```
+#[derive(Debug)]
+pub struct RxPipeline<'scope, F: SampleFormat> {
+    shared: Arc<Shared<F>>,
+    worker: Option<Worker<'scope>>,
+    overflow: OverflowPolicy,
+}
+
+impl<F: SampleFormat + Send + 'static> RxPipeline<'static, F> {
+    /// Moves `stream` to a new reader thread and starts filling the ring.
+    pub fn spawn<T: Borrow<D>, D: BladeRF>(
+        stream: RxSyncStream<T, F, D>,
+        config: RxPipelineConfig,
+    ) -> Result<Self>
+    where
+        RxSyncStream<T, F, D>: Send + 'static,
+    {
+        let shared = Arc::new(Shared::new(&config));
+        let worker_shared = shared.clone();
+        let handle = thread::Builder::new()
+            .name("bladerf-rx".to_owned())
+            .spawn(move || run(stream, &worker_shared, &config))
+            .map_err(|e| Error::msg(format!("Failed to spawn RX pipeline thread: {e}")))?;
+
+        Ok(Self {
+            shared,
+            worker: Some(Worker::Owned(handle)),
+            overflow: config.overflow,
+        })
+    }
+}
+
+impl<'scope, F: SampleFormat + Send + 'scope> RxPipeline<'scope, F> {
+    /// Same as [RxPipeline::spawn()], but runs the reader on a thread of `scope`, allowing streams which borrow the device.
+    pub fn spawn_scoped<'env, T: Borrow<D>, D: BladeRF>(
+        scope: &'scope Scope<'scope, 'env>,
+        stream: RxSyncStream<T, F, D>,
+        config: RxPipelineConfig,
+    ) -> Result<Self>
+    where
+        RxSyncStream<T, F, D>: Send + 'scope,
+    {
+        let shared = Arc::new(Shared::new(&config));
+        let worker_shared = shared.clone();
+        let handle = thread::Builder::new()
+            .name("bladerf-rx".to_owned())
+            .spawn_scoped(scope, move || run(stream, &worker_shared, &config))
+            .map_err(|e| Error::msg(format!("Failed to spawn RX pipeline thread: {e}")))?;
+
+        Ok(Self {
+            shared,
+            worker: Some(Worker::Scoped(handle)),
+            overflow: config.overflow,
+        })
+    }
+}
+
+impl<F: SampleFormat> RxPipeline<'_, F> {
+    /// Takes the oldest block from the ring, waiting up to `timeout` for one to arrive.
+    ///
+    /// The block goes back to the ring when the returned [RxBlock] is dropped.
+    ///
+    /// # Errors
+    /// - [Error::Timeout] if no block arrived within `timeout`.
+    /// - The error which stopped the reader thread, once all blocks read before it have been consumed.
+    pub fn recv(&self, timeout: Duration) -> Result<RxBlock<'_, F>> {
+        let received = self
+            .shared
+            .wakeup
+            .wait_until(Instant::now() + timeout, || {
+                if let Some(samples) = self.shared.filled.pop() {
+                    return Some(Ok(samples));
+                }
+                if self.shared.finished.load(Ordering::Acquire) {
+                    let error = self.shared.error.lock().unwrap().clone();
+                    return Some(Err(
+                        error.unwrap_or_else(|| Error::msg("RX pipeline has stopped"))
+                    ));
+                }
+                None
+            })
+            .ok_or(Error::Timeout)?;
+        received.map(|samples| self.take(samples))
+    }
+
+    /// Takes the oldest block from the ring if there is one, without waiting.
+    pub fn try_recv(&self) -> Option<RxBlock<'_, F>> {
+        self.shared.filled.pop().map(|samples| self.take(samples))
+    }
+
+    fn take(&self, samples: Vec<F>) -> RxBlock<'_, F> {
+        if self.overflow == OverflowPolicy::Block {
+            // The reader may be waiting for room in the ring
+            self.shared.wakeup.notify();
+        }
+        RxBlock {
+            samples: Some(samples),
+            shared: &self.shared,
+        }
+    }
+
+    /// Number of blocks currently waiting in the ring.
+    pub fn len(&self) -> usize {
+        self.shared.filled.len()
+    }
+
+    /// Tests if no blocks are waiting in the ring.
+    pub fn is_empty(&self) -> bool {
+        self.shared.filled.is_empty()
+    }
+
+    /// The number of blocks read and dropped so far.
+    pub fn stats(&self) -> PipelineStats {
+        PipelineStats {
+            blocks_read: self.shared.blocks_read.load(Ordering::Relaxed),
+            blocks_dropped: self.shared.blocks_dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Stops the reader thread, dropping the stream, and returns the error that stopped it early if there was one.
+    ///
+    /// Dropping the pipeline does the same but ignores the result.
+    pub fn stop(mut self) -> Result<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        let Some(worker) = self.worker.take() else {
+            return Ok(());
+        };
+        self.shared.running.store(false, Ordering::Release);
+        self.shared.wakeup.notify();
+        worker.join()?;
+        match self.shared.error.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<F: SampleFormat> Drop for RxPipeline<'_, F> {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+/// A block of samples taken from an [RxPipeline], dereferences to a slice of samples.
+///
+/// The block is returned to the ring when dropped, so it should not be held on to for longer than needed.
+#[derive(Debug)]
+pub struct RxBlock<'a, F: SampleFormat> {
+    samples: Option<Vec<F>>,
+    shared: &'a Shared<F>,
+}
+
+impl<F: SampleFormat> Deref for RxBlock<'_, F> {
+    type Target = [F];
+
+    fn deref(&self) -> &[F] {
+        self.samples.as_deref().unwrap()
+    }
+}
+
+impl<F: SampleFormat> DerefMut for RxBlock<'_, F> {
+    fn deref_mut(&mut self) -> &mut [F] {
+        self.samples.as_deref_mut().unwrap()
+    }
+}
+
+impl<F: SampleFormat> Drop for RxBlock<'_, F> {
+    fn drop(&mut self) {
+        if let Some(samples) = self.samples.take() {
+            // The pool only overflows if the reader had to allocate extra blocks
+            let _ = self.shared.free.push(samples);
+        }
+    }
+}
+
+/// Body of the reader thread.
+fn run<T: Borrow<D>, F: SampleFormat, D: BladeRF>(
+    stream: RxSyncStream<T, F, D>,
+    shared: &Shared<F>,
+    config: &RxPipelineConfig,
+) {
+    if config.realtime_priority {
+        if let Err(e) = set_realtime_priority() {
+            log::warn!("RX pipeline runs with normal priority: {e}");
+        }
+    }
+
+    let mut block = shared.free_block();
+    'read: while shared.running.load(Ordering::Acquire) {
+        match stream.read(&mut block, config.read_timeout) {
+            Ok(()) => {}
+            Err(Error::Timeout) => continue,
+            Err(e) => {
+                *shared.error.lock().unwrap() = Some(e);
+                break;
+            }
+        }
+        shared.blocks_read.fetch_add(1, Ordering::Relaxed);
+
+        block = match config.overflow {
+            OverflowPolicy::Block => loop {
+                match shared.filled.push(block) {
+                    Ok(()) => break shared.free_block(),
+                    Err(full) => block = full,
+                }
+                // Woken up by the consumer taking a block or by stop()
+                let running =
+                    shared
+                        .wakeup
+                        .wait_until(Instant::now() + config.read_timeout, || {
+                            let running = shared.running.load(Ordering::Acquire);
+                            (!running || !shared.filled.is_full()).then_some(running)
+                        });
+                if running == Some(false) {
+                    break 'read;
+                }
+            },
+            OverflowPolicy::DropOldest => match shared.filled.force_push(block) {
+                None => shared.free_block(),
+                Some(oldest) => {
+                    shared.blocks_dropped.fetch_add(1, Ordering::Relaxed);
+                    oldest
+                }
+            },
+            OverflowPolicy::DropNewest => match shared.filled.push(block) {
+                Ok(()) => shared.free_block(),
+                Err(newest) => {
+                    shared.blocks_dropped.fetch_add(1, Ordering::Relaxed);
+                    newest
+                }
+            },
+        };
+        shared.wakeup.notify();
+    }
+
+    // Disable the stream before the consumer can observe that the pipeline finished
+    drop(stream);
+    shared.finished.store(true, Ordering::Release);
+    shared.wakeup.notify();
+}
+
+#[cfg(unix)]
+pub(crate) fn set_realtime_priority() -> std::result::Result<(), String> {
+    // Safety: Only changes the scheduling of the calling thread
+    unsafe {
+        let mut param: libc::sched_param = std::mem::zeroed();
+        param.sched_priority = libc::sched_get_priority_min(libc::SCHED_FIFO);
+        match libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) {
+            0 => Ok(()),
+            code => Err(std::io::Error::from_raw_os_error(code).to_string()),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn set_realtime_priority() -> std::result::Result<(), String> {
+    Err("real-time priority is only supported on unix systems".to_owned())
+}
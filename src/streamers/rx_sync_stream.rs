@@ -1,21 +1,34 @@
 use std::borrow::Borrow;
 use std::marker::PhantomData;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
 use libbladerf_sys as sys;
 
+use crate::check_channels;
+use crate::deinterleave_mimo;
+use crate::zeroed_samples;
+use crate::ArmedTrigger;
 use crate::BladeRF;
 use crate::BladeRf1;
 use crate::BladeRf2;
 use crate::BladeRfAny;
 use crate::Channel;
 use crate::ChannelLayoutRx;
+use crate::ComplexI16;
+use crate::Error;
+use crate::FrequencyHopper;
+use crate::Hz;
+use crate::Metadata;
+use crate::QuickTune;
 use crate::Result;
 use crate::RxChannel;
 use crate::SampleFormat;
+use crate::SimulatedBladeRf;
 
+use super::StatsRecorder;
 use super::StreamConfig;
+use super::StreamStats;
 
 /// A synchronous stream from receiving samples from the BladeRF
 ///
@@ -43,12 +56,20 @@ use super::StreamConfig;
 /// let rx_stream_b = rx_stream_a.reconfigure::<ComplexI8>(conf, layout).unwrap();
 /// ```
 ///
+/// Samples can also be read as [Complex32](num_complex::Complex32), in which case the stream reads [ComplexI16] from the
+/// device into an internal buffer and converts them to [-1.0, 1.0) with [brf_ci16_to_cf32_slice()](crate::brf_ci16_to_cf32_slice).
+///
 /// The methods for an [RxSyncStream] are a bit different for [BladeRf1] as they won't take the layout parameter.
 #[derive(Debug)]
 pub struct RxSyncStream<T: Borrow<D>, F: SampleFormat, D: BladeRF> {
     pub(crate) dev: T,
     pub(crate) layout: ChannelLayoutRx,
     pub(crate) config: StreamConfig,
+    /// Samples as read from the device, for formats which are [converted](SampleFormat::CONVERTED).
+    pub(crate) raw_buffer: Mutex<Vec<ComplexI16>>,
+    pub(crate) stats: Mutex<StatsRecorder>,
+    /// Interleaved samples for [RxSyncStream::read_mimo()].
+    pub(crate) mimo_buffer: Mutex<Vec<F>>,
     pub(crate) _devtype: PhantomData<D>,
     pub(crate) _format: PhantomData<F>,
 }
@@ -58,21 +79,293 @@ impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> RxSyncStream<T, F, D> {
     ///
     /// This method will error if a call to [RxSyncStream::enable()] as not been made.
     ///
+    /// If the stream was configured with [StreamConfig::with_metadata()], this reads the next available samples and discards the [Metadata].
+    ///
     /// Relevant `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_t_r_e_a_m_i_n_g___s_y_n_c.html#gacbe845827dd4ad717f3cbc812e66b204>
     pub fn read(&self, buffer: &mut [F], timeout: Duration) -> Result<()> {
-        let res = unsafe {
-            sys::bladerf_sync_rx(
-                self.dev.borrow().get_device_ptr(),
-                buffer.as_mut_ptr() as *mut _,
-                buffer.len() as u32,
-                std::ptr::null_mut(),
+        if self.config.metadata {
+            return self.read_with_meta(buffer, None, timeout).map(|_| ());
+        }
+
+        let started = Instant::now();
+        // Safety: No metadata is exchanged without the metadata format
+        let res = unsafe { self.sync_rx(buffer, std::ptr::null_mut(), timeout) };
+        self.lock_stats()
+            .record(started, buffer.len() * F::CHANNELS, &res);
+        res
+    }
+
+    /// Reads `rx0.len()` samples of each channel of a MIMO stream into separate buffers.
+    ///
+    /// The interleaved samples are split up with [deinterleave_mimo()]. To avoid the copy, the stream can instead be
+    /// created with [MimoFrame](crate::MimoFrame)s as the sample format, which are read with [RxSyncStream::read()].
+    ///
+    /// # Errors
+    /// - The stream is not configured with [ChannelLayoutRx::MIMO], or its sample format already holds both channels.
+    /// - `rx0` and `rx1` have different lengths.
+    pub fn read_mimo(&self, rx0: &mut [F], rx1: &mut [F], timeout: Duration) -> Result<()>
+    where
+        F: Copy,
+    {
+        if !self.layout.is_mimo() || F::CHANNELS != 1 {
+            return Err(Error::msg(
+                "read_mimo() requires a MIMO layout with a single channel sample format",
+            ));
+        }
+        if rx0.len() != rx1.len() {
+            return Err(Error::msg(
+                "Both channel buffers need to have the same length",
+            ));
+        }
+
+        let mut interleaved = self
+            .mimo_buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if interleaved.len() != rx0.len() * 2 {
+            *interleaved = zeroed_samples(rx0.len() * 2);
+        }
+        self.read(&mut interleaved, timeout)?;
+        deinterleave_mimo(&interleaved, rx0, rx1);
+        Ok(())
+    }
+
+    /// Reads IQ samples into a buffer of [[SampleFormat]] and returns the [Metadata] describing them.
+    ///
+    /// With `at` set to [None], the next available samples are read (`BLADERF_META_FLAG_RX_NOW`).
+    /// Otherwise the read starts at the given device timestamp: samples before it are discarded and the call blocks until
+    /// the timestamp is reached, so `timeout` needs to account for how far in the future it is.
+    ///
+    /// If the stream overran, [Metadata::status] has `overrun` set and [Metadata::actual_count] is the number of valid samples in `buffer`.
+    ///
+    /// # Errors
+    /// - The stream was not configured with [StreamConfig::with_metadata()].
+    /// - [Error::TimePast](crate::Error::TimePast) if `at` has already passed.
+    /// - [Error::Timeout](crate::Error::Timeout) if the samples were not received within `timeout`.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use bladerf::{BladeRfAny, ComplexI16, ChannelLayoutRx, RxChannel, StreamConfig};
+    /// let dev = BladeRfAny::open_first().unwrap();
+    /// let conf = StreamConfig::default().with_metadata(true);
+    /// let layout = ChannelLayoutRx::SISO(RxChannel::Rx0);
+    ///
+    /// let rx_stream = dev.rx_streamer::<ComplexI16>(conf, layout).unwrap();
+    /// rx_stream.enable().unwrap();
+    ///
+    /// let mut buffer = vec![ComplexI16::ZERO; 4096];
+    /// let meta = rx_stream.read_with_meta(&mut buffer, None, Duration::from_secs(1)).unwrap();
+    /// // Read the block starting one million samples later
+    /// let later = rx_stream
+    ///     .read_with_meta(&mut buffer, Some(meta.timestamp + 1_000_000), Duration::from_secs(2))
+    ///     .unwrap();
+    /// assert_eq!(later.timestamp, meta.timestamp + 1_000_000);
+    /// ```
+    ///
+    /// Relevant `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/sync_rx_meta.html>
+    pub fn read_with_meta(
+        &self,
+        buffer: &mut [F],
+        at: Option<u64>,
+        timeout: Duration,
+    ) -> Result<Metadata> {
+        if !self.config.metadata {
+            return Err(Error::msg(
+                "Stream must be configured with metadata to read metadata",
+            ));
+        }
+
+        let meta = match at {
+            Some(timestamp) => Metadata::rx_at(timestamp),
+            None => Metadata::rx_now(),
+        };
+        let mut meta = sys::bladerf_metadata::from(&meta);
+        let started = Instant::now();
+        // Safety: `meta` is valid for the duration of the call
+        let res =
+            unsafe { self.sync_rx(buffer, &mut meta, timeout) }.map(|_| Metadata::from(&meta));
+
+        let mut stats = self.lock_stats();
+        let num_samples = res.as_ref().map_or(0, |meta| meta.actual_count as usize);
+        stats.record(started, num_samples, &res);
+        if let Ok(meta) = &res {
+            stats.record_rx_meta(meta, self.layout.num_channels(), at.is_some());
+        }
+        res
+    }
+
+    /// Returns a snapshot of the statistics gathered since the stream was created or [RxSyncStream::reset_stats()] was called.
+    ///
+    /// Overruns and discontinuities are only detected when the stream is configured with [StreamConfig::with_metadata()].
+    ///
+    /// ```no_run
+    /// use bladerf::{BladeRfAny, ComplexI16, ChannelLayoutRx, RxChannel, StreamConfig};
+    /// let dev = BladeRfAny::open_first().unwrap();
+    /// let conf = StreamConfig::default().with_metadata(true);
+    /// let rx_stream = dev.rx_streamer::<ComplexI16>(conf, ChannelLayoutRx::SISO(RxChannel::Rx0)).unwrap();
+    ///
+    /// // ... read samples ...
+    ///
+    /// let stats = rx_stream.stats();
+    /// if stats.has_lost_samples() {
+    ///     eprintln!("Lost {} samples in {} overruns", stats.lost_samples, stats.overruns);
+    /// }
+    /// ```
+    pub fn stats(&self) -> StreamStats {
+        self.lock_stats().snapshot(self.layout.num_channels())
+    }
+
+    /// Clears the statistics returned by [RxSyncStream::stats()].
+    ///
+    /// This also queries the sample rate reported as [StreamStats::configured_sample_rate] again, which is otherwise only
+    /// done when the stream is created.
+    pub fn reset_stats(&self) {
+        let sample_rate = self.dev.borrow().get_sample_rate(self.channel()).ok();
+        *self.lock_stats() = StatsRecorder::new(sample_rate);
+    }
+
+    /// Schedules a retune of the stream's channel to `frequency` once the RX timestamp reaches `at`.
+    ///
+    /// With a MIMO layout this retunes Rx0, which shares its oscillator with Rx1 on the bladeRF 2.0.
+    /// Passing a [QuickTune] from [BladeRF::get_quick_tune()] skips the tuning algorithm, see [BladeRF::schedule_retune()]
+    /// for details. For hopping through a list of frequencies, see [RxSyncStream::frequency_hopper()].
+    ///
+    /// # Errors
+    /// [Error::QueueFull] if too many retunes are pending already.
+    pub fn schedule_retune(
+        &self,
+        at: u64,
+        frequency: impl Into<Hz>,
+        quick_tune: Option<&QuickTune>,
+    ) -> Result<()> {
+        let mut quick_tune = quick_tune.cloned();
+        self.dev
+            .borrow()
+            .schedule_retune(self.channel(), at, frequency, quick_tune.as_mut())
+    }
+
+    /// Cancels all retunes scheduled for the stream's channel.
+    pub fn cancel_scheduled_retunes(&self) -> Result<()> {
+        self.dev.borrow().cancel_scheduled_retune(self.channel())
+    }
+
+    /// Creates a [FrequencyHopper] retuning the stream's channel through `frequencies`, dwelling `dwell` samples on each.
+    ///
+    /// See [FrequencyHopper::new()].
+    pub fn frequency_hopper(
+        &self,
+        frequencies: &[impl Into<Hz> + Copy],
+        dwell: u64,
+    ) -> Result<FrequencyHopper<'_, D>> {
+        FrequencyHopper::new(self.dev.borrow(), self.channel(), frequencies, dwell)
+    }
+
+    /// The channel of a SISO stream, or the first channel of a MIMO stream.
+    fn channel(&self) -> Channel {
+        match self.layout {
+            ChannelLayoutRx::SISO(ch) => ch.into(),
+            ChannelLayoutRx::MIMO => Channel::Rx0,
+        }
+    }
+
+    fn lock_stats(&self) -> MutexGuard<'_, StatsRecorder> {
+        self.stats.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reads into `buffer`, through the conversion buffer if the format is converted.
+    ///
+    /// # Safety
+    /// `meta` must be null or point to valid metadata.
+    unsafe fn sync_rx(
+        &self,
+        buffer: &mut [F],
+        meta: *mut sys::bladerf_metadata,
+        timeout: Duration,
+    ) -> Result<()> {
+        let num_samples = buffer.len() * F::CHANNELS;
+        if !F::CONVERTED {
+            // Safety: `buffer` holds `num_samples` samples in the configured format
+            return unsafe {
+                self.dev.borrow().sync_rx(
+                    buffer.as_mut_ptr() as *mut _,
+                    num_samples as u32,
+                    meta,
+                    timeout.as_millis() as u32,
+                )
+            };
+        }
+
+        let mut raw = self
+            .raw_buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        raw.resize(num_samples, ComplexI16::ZERO);
+        // Safety: Converted formats are configured as Sc16Q11, which `raw` holds `num_samples` samples of
+        unsafe {
+            self.dev.borrow().sync_rx(
+                raw.as_mut_ptr() as *mut _,
+                num_samples as u32,
+                meta,
                 timeout.as_millis() as u32,
             )
-        };
-        check_res!(res);
+        }?;
+        F::from_ci16(&raw, buffer);
         Ok(())
     }
 
+    /// Enables the stream (and the relevant hardware) so samples can be read.
+    pub fn enable(&self) -> Result<()> {
+        // Safety, should be find to do a reconfigure here, nothing changes about the config, we just need to do this because disable will uninitialize the config
+        unsafe {
+            self.dev
+                .borrow()
+                .set_sync_config::<F>(&self.config, self.layout.into())?;
+        }
+
+        match self.layout {
+            ChannelLayoutRx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), true),
+            ChannelLayoutRx::MIMO => {
+                self.dev.borrow().set_enable_module(Channel::Rx0, true)?;
+                self.dev.borrow().set_enable_module(Channel::Rx1, true)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Enables the stream gated by an armed trigger, so samples are only delivered once the trigger fires.
+    ///
+    /// Until then, reads block and eventually time out. See [TriggerMaster](crate::TriggerMaster) for an example.
+    ///
+    /// # Errors
+    /// - The trigger is on a different device than the stream or is not for a receive channel.
+    /// - The trigger is not armed anymore, such as after it already fired.
+    pub fn enable_armed(&self, trigger: &impl ArmedTrigger) -> Result<()> {
+        if trigger.device_id() != self.dev.borrow().device_id() {
+            return Err(Error::msg("Trigger is configured on a different device"));
+        }
+        if !trigger.trigger().channel.is_rx() {
+            return Err(Error::msg(
+                "Trigger is not configured for a receive channel",
+            ));
+        }
+        if !trigger.state()?.armed {
+            return Err(Error::msg("Trigger is not armed"));
+        }
+        self.enable()
+    }
+
+    /// Disables the stream (and the relevant hardware).
+    pub fn disable(&self) -> Result<()> {
+        match self.layout {
+            ChannelLayoutRx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), false),
+            ChannelLayoutRx::MIMO => {
+                self.dev.borrow().set_enable_module(Channel::Rx0, false)?;
+                self.dev.borrow().set_enable_module(Channel::Rx1, false)?;
+                Ok(())
+            }
+        }
+    }
+
     /// # Safety
     /// Need to ensure multiple streamers are not configured since a reconfiguration of one can change the sample type leading to our of bounds memory accesses.
     pub(crate) unsafe fn new(
@@ -80,17 +373,24 @@ impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> RxSyncStream<T, F, D> {
         config: StreamConfig,
         layout: ChannelLayoutRx,
     ) -> Result<RxSyncStream<T, F, D>> {
+        check_channels::<F>(layout.num_channels())?;
         unsafe {
             dev.borrow().set_sync_config::<F>(&config, layout.into())?;
         }
 
-        Ok(RxSyncStream {
+        let stream = RxSyncStream {
             dev,
             layout,
             config,
+            raw_buffer: Mutex::new(Vec::new()),
+            stats: Mutex::new(StatsRecorder::new(None)),
+            mimo_buffer: Mutex::new(Vec::new()),
             _devtype: PhantomData,
             _format: PhantomData,
-        })
+        };
+        // Queries the sample rate once, instead of on every call to stats()
+        stream.reset_stats();
+        Ok(stream)
     }
 }
 
@@ -135,24 +435,6 @@ impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> Drop for RxSyncStream<T, F, D> {
 ////////////////////////////////////////////////////////////////////////////////
 // RX Stream Brf1
 
-impl<T: Borrow<BladeRf1> + Clone, F: SampleFormat> RxSyncStream<T, F, BladeRf1> {
-    /// Enables the stream (and the relevant hardware) so samples can be read.
-    pub fn enable(&self) -> Result<()> {
-        // Safety, should be find to do a reconfigure here, nothing changes about the config, we just need to do this because disable will uninitialize the config
-        unsafe {
-            self.dev
-                .borrow()
-                .set_sync_config::<F>(&self.config, self.layout.into())?;
-        }
-        self.dev.borrow().set_enable_module(Channel::Rx0, true)
-    }
-
-    /// Disables the stream (and the relevant hardware).
-    pub fn disable(&self) -> Result<()> {
-        self.dev.borrow().set_enable_module(Channel::Rx0, false)
-    }
-}
-
 impl<'a, F: SampleFormat> RxSyncStream<&'a BladeRf1, F, BladeRf1> {
     /// Allows reconfiguring a stream to change either the [StreamConfig] or [SampleFormat]
     ///
@@ -180,39 +462,6 @@ impl<F: SampleFormat> RxSyncStream<Arc<BladeRf1>, F, BladeRf1> {
 ////////////////////////////////////////////////////////////////////////////////
 // RX Stream Brf2
 
-impl<T: Borrow<BladeRf2> + Clone, F: SampleFormat> RxSyncStream<T, F, BladeRf2> {
-    /// Enables the stream (and the relevant hardware) so samples can be read.
-    pub fn enable(&self) -> Result<()> {
-        // Safety, should be find to do a reconfigure here, nothing changes about the config, we just need to do this because disable will uninitialize the config
-        unsafe {
-            self.dev
-                .borrow()
-                .set_sync_config::<F>(&self.config, self.layout.into())?;
-        }
-
-        match self.layout {
-            ChannelLayoutRx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), true),
-            ChannelLayoutRx::MIMO => {
-                self.dev.borrow().set_enable_module(Channel::Rx0, true)?;
-                self.dev.borrow().set_enable_module(Channel::Rx1, true)?;
-                Ok(())
-            }
-        }
-    }
-
-    /// Disables the stream (and the relevant hardware).
-    pub fn disable(&self) -> Result<()> {
-        match self.layout {
-            ChannelLayoutRx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), false),
-            ChannelLayoutRx::MIMO => {
-                self.dev.borrow().set_enable_module(Channel::Rx0, false)?;
-                self.dev.borrow().set_enable_module(Channel::Rx1, false)?;
-                Ok(())
-            }
-        }
-    }
-}
-
 impl<'a, F: SampleFormat> RxSyncStream<&'a BladeRf2, F, BladeRf2> {
     /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutRx]
     ///
@@ -242,39 +491,36 @@ impl<F: SampleFormat> RxSyncStream<Arc<BladeRf2>, F, BladeRf2> {
 ////////////////////////////////////////////////////////////////////////////////
 // RX Stream BrfAny
 
-impl<T: Borrow<BladeRfAny> + Clone, F: SampleFormat> RxSyncStream<T, F, BladeRfAny> {
-    /// Enables the stream (and the relevant hardware) so samples can be read.
-    pub fn enable(&self) -> Result<()> {
-        // Safety, should be find to do a reconfigure here, nothing changes about the config, we just need to do this because disable will uninitialize the config
-        unsafe {
-            self.dev
-                .borrow()
-                .set_sync_config::<F>(&self.config, self.layout.into())?;
-        }
-        match self.layout {
-            ChannelLayoutRx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), true),
-            ChannelLayoutRx::MIMO => {
-                self.dev.borrow().set_enable_module(Channel::Rx0, true)?;
-                self.dev.borrow().set_enable_module(Channel::Rx1, true)?;
-                Ok(())
-            }
-        }
+impl<'a, F: SampleFormat> RxSyncStream<&'a BladeRfAny, F, BladeRfAny> {
+    /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutRx]
+    ///
+    /// See the general [RxSyncStream] docs for usage example.
+    pub fn reconfigure<NF: SampleFormat>(
+        self,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+    ) -> Result<RxSyncStream<&'a BladeRfAny, NF, BladeRfAny>> {
+        self.reconfigure_inner(config, layout)
     }
+}
 
-    /// Disables the stream (and the relevant hardware).
-    pub fn disable(&self) -> Result<()> {
-        match self.layout {
-            ChannelLayoutRx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), false),
-            ChannelLayoutRx::MIMO => {
-                self.dev.borrow().set_enable_module(Channel::Rx0, false)?;
-                self.dev.borrow().set_enable_module(Channel::Rx1, false)?;
-                Ok(())
-            }
-        }
+impl<F: SampleFormat> RxSyncStream<Arc<BladeRfAny>, F, BladeRfAny> {
+    /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutRx]
+    ///
+    /// See the general [RxSyncStream] docs for usage example.
+    pub fn reconfigure<NF: SampleFormat>(
+        self,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+    ) -> Result<RxSyncStream<Arc<BladeRfAny>, NF, BladeRfAny>> {
+        self.reconfigure_inner(config, layout)
     }
 }
 
-impl<'a, F: SampleFormat> RxSyncStream<&'a BladeRfAny, F, BladeRfAny> {
+////////////////////////////////////////////////////////////////////////////////
+// RX Stream Simulated
+
+impl<'a, F: SampleFormat> RxSyncStream<&'a SimulatedBladeRf, F, SimulatedBladeRf> {
     /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutRx]
     ///
     /// See the general [RxSyncStream] docs for usage example.
@@ -282,12 +528,12 @@ impl<'a, F: SampleFormat> RxSyncStream<&'a BladeRfAny, F, BladeRfAny> {
         self,
         config: StreamConfig,
         layout: ChannelLayoutRx,
-    ) -> Result<RxSyncStream<&'a BladeRfAny, NF, BladeRfAny>> {
+    ) -> Result<RxSyncStream<&'a SimulatedBladeRf, NF, SimulatedBladeRf>> {
         self.reconfigure_inner(config, layout)
     }
 }
 
-impl<F: SampleFormat> RxSyncStream<Arc<BladeRfAny>, F, BladeRfAny> {
+impl<F: SampleFormat> RxSyncStream<Arc<SimulatedBladeRf>, F, SimulatedBladeRf> {
     /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutRx]
     ///
     /// See the general [RxSyncStream] docs for usage example.
@@ -295,7 +541,7 @@ impl<F: SampleFormat> RxSyncStream<Arc<BladeRfAny>, F, BladeRfAny> {
         self,
         config: StreamConfig,
         layout: ChannelLayoutRx,
-    ) -> Result<RxSyncStream<Arc<BladeRfAny>, NF, BladeRfAny>> {
+    ) -> Result<RxSyncStream<Arc<SimulatedBladeRf>, NF, SimulatedBladeRf>> {
         self.reconfigure_inner(config, layout)
     }
 }
@@ -0,0 +1,222 @@
+use std::time::{Duration, Instant};
+
+use crate::Error;
+use crate::Metadata;
+use crate::Result;
+
+/// Number of most recent calls the latency percentiles are computed over.
+const LATENCY_WINDOW: usize = 1024;
+
+/// Percentiles of the time spent in read or write calls of a stream.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Median latency.
+    pub p50: Duration,
+    /// 90th percentile latency.
+    pub p90: Duration,
+    /// 99th percentile latency.
+    pub p99: Duration,
+    /// Highest latency.
+    pub max: Duration,
+}
+
+/// A snapshot of the statistics of an [RxSyncStream](crate::RxSyncStream) or [TxSyncStream](crate::TxSyncStream).
+///
+/// Overruns and timestamp discontinuities are only known when the stream exchanges [Metadata], see
+/// [StreamConfig::with_metadata()](crate::StreamConfig::with_metadata). Without it, a drop in
+/// [StreamStats::achieved_sample_rate] is the only sign of lost samples. Underruns are not reported by `libbladerf` at
+/// all, see [StreamStats::underruns].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StreamStats {
+    /// Samples transferred by successful calls, counting all channels.
+    pub samples: u64,
+    /// Successful read or write calls.
+    pub transfers: u64,
+    /// Calls which failed with [Error::Timeout].
+    pub timeouts: u64,
+    /// Reads which reported an overrun in their [Metadata].
+    pub overruns: u64,
+    /// Writes which reported an underrun in their [Metadata].
+    ///
+    /// `libbladerf` documents `BLADERF_META_STATUS_UNDERRUN` as reserved for future use and never sets it, so this stays
+    /// zero on hardware.
+    pub underruns: u64,
+    /// Reads whose timestamp did not continue where the previous read ended.
+    ///
+    /// Reads scheduled at a timestamp are expected to skip ahead and are not counted.
+    pub discontinuities: u64,
+    /// Samples per channel skipped by the discontinuities.
+    pub lost_samples: u64,
+    /// Sample rate the channel was configured for when the statistics were last reset, if it could be queried.
+    pub configured_sample_rate: Option<u32>,
+    /// Samples per channel per second transferred since the first transfer.
+    pub achieved_sample_rate: Option<f64>,
+    /// Latency of the most recent calls.
+    pub latency: LatencyStats,
+}
+
+impl StreamStats {
+    /// Tests if any samples are known to be lost, through overruns, underruns or discontinuities.
+    pub fn has_lost_samples(&self) -> bool {
+        self.overruns > 0 || self.underruns > 0 || self.discontinuities > 0
+    }
+}
+
+/// Accumulates the [StreamStats] of a stream.
+#[derive(Debug)]
+pub(crate) struct StatsRecorder {
+    stats: StreamStats,
+    first_transfer: Option<Instant>,
+    last_transfer: Option<Instant>,
+    next_timestamp: Option<u64>,
+    latencies: Vec<Duration>,
+    next_latency: usize,
+}
+
+impl StatsRecorder {
+    pub(crate) fn new(configured_sample_rate: Option<u32>) -> Self {
+        Self {
+            stats: StreamStats {
+                configured_sample_rate,
+                ..StreamStats::default()
+            },
+            first_transfer: None,
+            last_transfer: None,
+            next_timestamp: None,
+            latencies: Vec::with_capacity(LATENCY_WINDOW),
+            next_latency: 0,
+        }
+    }
+
+    /// Records a call which started at `started` and tried to transfer `num_samples`.
+    pub(crate) fn record<T>(&mut self, started: Instant, num_samples: usize, res: &Result<T>) {
+        let now = Instant::now();
+        match res {
+            Ok(_) => {
+                self.stats.samples += num_samples as u64;
+                self.stats.transfers += 1;
+                self.first_transfer.get_or_insert(started);
+                self.last_transfer = Some(now);
+            }
+            Err(Error::Timeout) => self.stats.timeouts += 1,
+            Err(_) => {}
+        }
+
+        let latency = now - started;
+        if self.latencies.len() < LATENCY_WINDOW {
+            self.latencies.push(latency);
+        } else {
+            self.latencies[self.next_latency] = latency;
+        }
+        self.next_latency = (self.next_latency + 1) % LATENCY_WINDOW;
+    }
+
+    /// Records the [Metadata] returned by a read of `channels` interleaved channels.
+    ///
+    /// `scheduled` reads start at a requested timestamp, so they are not checked for discontinuities.
+    pub(crate) fn record_rx_meta(&mut self, meta: &Metadata, channels: usize, scheduled: bool) {
+        if meta.status.overrun {
+            self.stats.overruns += 1;
+        }
+        if let (Some(expected), false) = (self.next_timestamp, scheduled) {
+            if meta.timestamp != expected {
+                self.stats.discontinuities += 1;
+                self.stats.lost_samples += meta.timestamp.saturating_sub(expected);
+            }
+        }
+        self.next_timestamp = Some(meta.timestamp + (meta.actual_count as usize / channels) as u64);
+    }
+
+    /// Records the [Metadata] returned by a write.
+    pub(crate) fn record_tx_meta(&mut self, meta: &Metadata) {
+        if meta.status.underrun {
+            self.stats.underruns += 1;
+        }
+    }
+
+    pub(crate) fn snapshot(&self, channels: usize) -> StreamStats {
+        let achieved_sample_rate = match (self.first_transfer, self.last_transfer) {
+            (Some(first), Some(last)) if last > first => {
+                Some(self.stats.samples as f64 / channels as f64 / (last - first).as_secs_f64())
+            }
+            _ => None,
+        };
+
+        let mut latencies = self.latencies.clone();
+        latencies.sort_unstable();
+        let percentile = |p: usize| {
+            latencies
+                .get((latencies.len() * p / 100).min(latencies.len().saturating_sub(1)))
+                .copied()
+                .unwrap_or_default()
+        };
+
+        StreamStats {
+            achieved_sample_rate,
+            latency: LatencyStats {
+                p50: percentile(50),
+                p90: percentile(90),
+                p99: percentile(99),
+                max: latencies.last().copied().unwrap_or_default(),
+            },
+            ..self.stats
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MetadataStatus;
+
+    fn rx_meta(timestamp: u64, actual_count: u32, overrun: bool) -> Metadata {
+        Metadata {
+            timestamp,
+            actual_count,
+            status: MetadataStatus {
+                overrun,
+                underrun: false,
+            },
+            ..Metadata::default()
+        }
+    }
+
+    #[test]
+    fn discontinuities() {
+        let mut recorder = StatsRecorder::new(None);
+        recorder.record_rx_meta(&rx_meta(1000, 4096, false), 1, false);
+        recorder.record_rx_meta(&rx_meta(5096, 4096, false), 1, false);
+        assert_eq!(recorder.snapshot(1).discontinuities, 0);
+
+        // 904 samples went missing
+        recorder.record_rx_meta(&rx_meta(10_096, 1000, true), 1, false);
+        // Scheduled reads may skip ahead
+        recorder.record_rx_meta(&rx_meta(50_000, 4096, false), 1, true);
+        // MIMO timestamps advance by the samples per channel
+        recorder.record_rx_meta(&rx_meta(54_096, 4096, false), 2, false);
+        recorder.record_rx_meta(&rx_meta(56_144, 4096, false), 2, false);
+
+        let stats = recorder.snapshot(1);
+        assert_eq!(stats.overruns, 1);
+        assert_eq!(stats.discontinuities, 1);
+        assert_eq!(stats.lost_samples, 904);
+        assert!(stats.has_lost_samples());
+    }
+
+    #[test]
+    fn latency_percentiles() {
+        let mut recorder = StatsRecorder::new(Some(1_000_000));
+        let started = Instant::now();
+        recorder.record(started, 100, &Ok(()));
+        recorder.record(started, 100, &Result::<()>::Err(Error::Timeout));
+
+        let stats = recorder.snapshot(1);
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.transfers, 1);
+        assert_eq!(stats.timeouts, 1);
+        assert_eq!(stats.configured_sample_rate, Some(1_000_000));
+        assert!(stats.latency.p50 <= stats.latency.p99);
+        assert!(stats.latency.p99 <= stats.latency.max);
+        assert!(stats.latency.max <= started.elapsed());
+    }
+}
@@ -0,0 +1,182 @@
+use std::borrow::Borrow;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use crate::BladeRF;
+use crate::Direction;
+use crate::Error;
+use crate::Metadata;
+use crate::Result;
+use crate::SampleFormat;
+
+use super::{RxSyncStream, TxBurst, TxSyncStream};
+
+/// A full-duplex pair of an [RxSyncStream] and a [TxSyncStream] on the same device.
+///
+/// Both streams need to be configured with [StreamConfig::with_metadata()](crate::StreamConfig::with_metadata), since
+/// received samples are located and replies are scheduled by device timestamp. The layouts are independent, so any
+/// combination of SISO and MIMO works, with MIMO samples interleaved as for the individual streams.
+///
+/// RX and TX timestamps count the same sample clock but are separate counters. [Transceiver::enable()] measures the
+/// offset between them, so an RX timestamp can be turned into the TX timestamp of the same instant with
+/// [Transceiver::tx_timestamp()]. This is what [Transceiver::reply()] uses to transmit a burst a fixed number of samples
+/// after something was received.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use bladerf::{BladeRf2, BladeRfAny, ChannelLayoutRx, ChannelLayoutTx, ComplexI16, RxChannel, StreamConfig, Transceiver, TxChannel};
+///
+/// let dev = Arc::new(BladeRf2::try_from(BladeRfAny::open_first().unwrap()).unwrap());
+/// let conf = StreamConfig::default().with_metadata(true);
+/// let rx = BladeRf2::rx_streamer_arc::<ComplexI16>(dev.clone(), conf, ChannelLayoutRx::SISO(RxChannel::Rx0)).unwrap();
+/// let tx = BladeRf2::tx_streamer_arc::<ComplexI16>(dev.clone(), conf, ChannelLayoutTx::SISO(TxChannel::Tx0)).unwrap();
+///
+/// let trx = Transceiver::new(rx, tx).unwrap();
+/// trx.enable().unwrap();
+///
+/// let mut buffer = vec![ComplexI16::ZERO; 4096];
+/// let reply = vec![ComplexI16::new(1024, 0); 2000];
+/// let timeout = Duration::from_secs(1);
+/// let meta = trx.receive(&mut buffer, timeout).unwrap();
+/// // Answer 10k samples after the start of the received block
+/// trx.reply(&reply, meta.timestamp, 10_000, timeout).unwrap();
+/// ```
+///
+/// Dropping the transceiver disables TX before RX, the same order as [Transceiver::disable()].
+#[derive(Debug)]
+pub struct Transceiver<T: Borrow<D>, F: SampleFormat, D: BladeRF> {
+    // Declared before `rx` so it is dropped, and with that disabled, first
+    tx: TxSyncStream<T, F, D>,
+    rx: RxSyncStream<T, F, D>,
+    /// TX timestamp minus the RX timestamp of the same instant.
+    timestamp_offset: AtomicI64,
+}
+
+impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> Transceiver<T, F, D> {
+    /// Pairs `rx` and `tx`, which need to be streams of the same device configured with metadata.
+    pub fn new(rx: RxSyncStream<T, F, D>, tx: TxSyncStream<T, F, D>) -> Result<Self> {
+        if rx.dev.borrow().device_id() != tx.dev.borrow().device_id() {
+            return Err(Error::msg(
+                "RX and TX streams need to be on the same device",
+            ));
+        }
+        if !rx.config.metadata || !tx.config.metadata {
+            return Err(Error::msg(
+                "Transceiver requires streams configured with metadata",
+            ));
+        }
+
+        Ok(Self {
+            tx,
+            rx,
+            timestamp_offset: AtomicI64::new(0),
+        })
+    }
+
+    /// The receiving half.
+    pub fn rx(&self) -> &RxSyncStream<T, F, D> {
+        &self.rx
+    }
+
+    /// The transmitting half.
+    pub fn tx(&self) -> &TxSyncStream<T, F, D> {
+        &self.tx
+    }
+
+    /// Separates the transceiver into its streams again.
+    pub fn into_parts(self) -> (RxSyncStream<T, F, D>, TxSyncStream<T, F, D>) {
+        (self.rx, self.tx)
+    }
+
+    /// Enables RX and then TX, and measures the offset between their timestamps.
+    ///
+    /// RX goes first so nothing is transmitted before the receiver is running.
+    pub fn enable(&self) -> Result<()> {
+        self.rx.enable()?;
+        if let Err(e) = self.tx.enable() {
+            let _ = self.rx.disable();
+            return Err(e);
+        }
+        self.sync_timestamps()
+    }
+
+    /// Disables TX and then RX, so nothing is transmitted without the receiver running.
+    ///
+    /// RX is disabled even if disabling TX failed, the first error is returned.
+    pub fn disable(&self) -> Result<()> {
+        let tx = self.tx.disable();
+        let rx = self.rx.disable();
+        tx.and(rx)
+    }
+
+    /// Measures the offset between the RX and TX timestamps again, which [Transceiver::enable()] already does.
+    ///
+    /// The TX timestamp is read between two reads of the RX timestamp and compared to their midpoint, so the latency of
+    /// reading them mostly cancels out.
+    pub fn sync_timestamps(&self) -> Result<()> {
+        let dev = self.rx.dev.borrow();
+        let rx_before = dev.get_timestamp(Direction::RX)?;
+        let tx = dev.get_timestamp(Direction::TX)?;
+        let rx_after = dev.get_timestamp(Direction::RX)?;
+        let rx = rx_before + (rx_after.saturating_sub(rx_before)) / 2;
+        self.timestamp_offset
+            .store(tx as i64 - rx as i64, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Difference of the TX timestamp to the RX timestamp of the same instant.
+    pub fn timestamp_offset(&self) -> i64 {
+        self.timestamp_offset.load(Ordering::Relaxed)
+    }
+
+    /// Converts an RX timestamp to the TX timestamp of the same instant.
+    pub fn tx_timestamp(&self, rx_timestamp: u64) -> u64 {
+        rx_timestamp.saturating_add_signed(self.timestamp_offset())
+    }
+
+    /// Converts a TX timestamp to the RX timestamp of the same instant.
+    pub fn rx_timestamp(&self, tx_timestamp: u64) -> u64 {
+        tx_timestamp.saturating_add_signed(-self.timestamp_offset())
+    }
+
+    /// Reads the next available samples, see [RxSyncStream::read_with_meta()].
+    pub fn receive(&self, buffer: &mut [F], timeout: Duration) -> Result<Metadata> {
+        self.rx.read_with_meta(buffer, None, timeout)
+    }
+
+    /// Reads the samples starting at the RX timestamp `at`, see [RxSyncStream::read_with_meta()].
+    pub fn receive_at(&self, buffer: &mut [F], at: u64, timeout: Duration) -> Result<Metadata> {
+        self.rx.read_with_meta(buffer, Some(at), timeout)
+    }
+
+    /// Transmits `samples` as a burst starting `delay` samples after the RX timestamp `rx_timestamp`.
+    ///
+    /// # Errors
+    /// - [Error::TimePast] if the reply would start before the current TX timestamp, so `delay` was too short.
+    /// - See [TxSyncStream::write_burst()].
+    pub fn reply(
+        &self,
+        samples: &[F],
+        rx_timestamp: u64,
+        delay: u64,
+        timeout: Duration,
+    ) -> Result<Metadata> {
+        self.reply_burst(rx_timestamp, delay)?
+            .finish(samples, timeout)
+    }
+
+    /// Starts a burst `delay` samples after the RX timestamp `rx_timestamp`, which can be assembled from multiple writes.
+    ///
+    /// See [TxBurst] for writing it and [Transceiver::reply()] for a single write.
+    ///
+    /// # Errors
+    /// - [Error::TimePast] if the burst would start before the current TX timestamp, so `delay` was too short.
+    pub fn reply_burst(&self, rx_timestamp: u64, delay: u64) -> Result<TxBurst<'_, T, F, D>> {
+        let at = self.tx_timestamp(rx_timestamp.saturating_add(delay));
+        if at < self.tx.dev.borrow().get_timestamp(Direction::TX)? {
+            return Err(Error::TimePast);
+        }
+        Ok(self.tx.burst(Some(at)))
+    }
+}
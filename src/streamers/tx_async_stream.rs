@@ -0,0 +1,149 @@
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use libbladerf_sys as sys;
+
+use crate::BladeRF;
+use crate::Channel;
+use crate::ChannelLayoutTx;
+use crate::Result;
+use crate::SampleFormat;
+
+use super::async_stream::{AsyncStreamCore, CallbackState, BLADERF_STREAM_SHUTDOWN};
+use super::StreamConfig;
+
+/// Callback type invoked by an [AsyncTxStream] to fill every buffer of samples to transmit.
+pub(crate) type TxCallback<F> = Box<dyn FnMut(&mut [F]) -> ControlFlow<()> + Send>;
+
+/// A callback driven stream for transmitting samples with the BladeRF.
+///
+/// This uses the `libbladerf` asynchronous interface: a callback running on a dedicated thread is handed an entire
+/// buffer to fill every time the stream needs more samples, for as long as the callback returns [ControlFlow::Continue].
+/// The buffer passed to the callback is only valid for the duration of the call. Returning [ControlFlow::Break] ends the
+/// stream and the buffer from that call is not transmitted.
+///
+/// Since the stream thread needs access to the device, the device must be shared using an [Arc].
+/// Obtained from a call to [BladeRfAny::tx_async_streamer()](crate::BladeRfAny::tx_async_streamer) as well as a similar method on other devices.
+/// ```no_run
+/// use std::{ops::ControlFlow, sync::Arc};
+/// use bladerf::{BladeRfAny, ComplexI16, ChannelLayoutTx, TxChannel, StreamConfig};
+/// let dev = Arc::new(BladeRfAny::open_first().unwrap());
+/// let conf = StreamConfig::default();
+/// let layout = ChannelLayoutTx::SISO(TxChannel::Tx0);
+///
+/// let tx_stream = BladeRfAny::tx_async_streamer::<ComplexI16, _>(dev, conf, layout, |buffer| {
+///     buffer.fill(ComplexI16::new(1024, 0));
+///     ControlFlow::Continue(())
+/// })
+/// .unwrap();
+///
+/// std::thread::sleep(std::time::Duration::from_secs(1));
+/// tx_stream.stop().unwrap();
+/// ```
+///
+/// Dropping the stream stops it, but any error is lost. Use [AsyncTxStream::stop()] to get the result of the stream.
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_t_r_e_a_m_i_n_g___a_s_y_n_c.html>
+#[derive(Debug)]
+pub struct AsyncTxStream<F: SampleFormat, D: BladeRF> {
+    core: AsyncStreamCore<TxCallback<F>, D>,
+    layout: ChannelLayoutTx,
+    _format: PhantomData<F>,
+}
+
+impl<F: SampleFormat, D: BladeRF> AsyncTxStream<F, D> {
+    /// # Safety
+    /// Need to ensure no other TX streamers are configured on the device.
+    pub(crate) unsafe fn new(
+        dev: Arc<D>,
+        config: StreamConfig,
+        layout: ChannelLayoutTx,
+        callback: TxCallback<F>,
+    ) -> Result<Self> {
+        let channels = match layout {
+            ChannelLayoutTx::SISO(ch) => vec![ch.into()],
+            ChannelLayoutTx::MIMO => vec![Channel::Tx0, Channel::Tx1],
+        };
+
+        // When transmitting, libbladerf asks the callback for every buffer including the initial ones.
+        let core = unsafe {
+            AsyncStreamCore::start::<F>(
+                dev,
+                config,
+                layout.into(),
+                channels,
+                Some(tx_callback::<F>),
+                callback,
+                0,
+            )?
+        };
+
+        Ok(Self {
+            core,
+            layout,
+            _format: PhantomData,
+        })
+    }
+
+    /// Returns false once the stream has ended, either because the callback returned [ControlFlow::Break] or because of an error.
+    pub fn is_running(&self) -> bool {
+        self.core.is_running()
+    }
+
+    /// The channel layout the stream was started with.
+    pub fn layout(&self) -> ChannelLayoutTx {
+        self.layout
+    }
+
+    /// Gets the device the stream was started on.
+    pub fn device(&self) -> &Arc<D> {
+        &self.core.dev
+    }
+
+    /// Stops the stream, waits for the stream thread to exit and disables the transmit channels.
+    ///
+    /// # Errors
+    /// Returns the error that ended the stream (such as [Error::Timeout](crate::Error::Timeout)), or an error if the callback panicked.
+    pub fn stop(mut self) -> Result<()> {
+        self.core.shutdown()
+    }
+}
+
+/// Trampoline handed to `bladerf_init_stream()` which asks the user callback to fill the next buffer.
+///
+/// `samples` points to the buffer that finished transmitting (or is null for the initial buffers), it is not needed
+/// since the buffers are handed out in a fixed order.
+unsafe extern "C" fn tx_callback<F: SampleFormat>(
+    _dev: *mut sys::bladerf,
+    _stream: *mut sys::bladerf_stream,
+    _meta: *mut sys::bladerf_metadata,
+    _samples: *mut c_void,
+    _num_samples: usize,
+    user_data: *mut c_void,
+) -> *mut c_void {
+    // Safety: user_data is the state created in `AsyncStreamCore::start()` and only the stream thread accesses it mutably.
+    let state = unsafe { &mut *(user_data as *mut CallbackState<TxCallback<F>>) };
+
+    if !state.flags.running.load(Ordering::Acquire) {
+        return BLADERF_STREAM_SHUTDOWN;
+    }
+
+    let buffer = state.next_buffer();
+    // Safety: Every buffer holds `samples_per_buffer` samples of the configured format, which is valid to reinterpret as
+    // `F` holding `F::CHANNELS` of them. The buffer is not in flight since there are more buffers than transfers.
+    let samples = unsafe {
+        std::slice::from_raw_parts_mut(buffer as *mut F, state.samples_per_buffer / F::CHANNELS)
+    };
+    match catch_unwind(AssertUnwindSafe(|| (state.callback)(samples))) {
+        Ok(ControlFlow::Continue(())) => buffer,
+        Ok(ControlFlow::Break(())) => BLADERF_STREAM_SHUTDOWN,
+        Err(_) => {
+            state.flags.panicked.store(true, Ordering::Release);
+            BLADERF_STREAM_SHUTDOWN
+        }
+    }
+}
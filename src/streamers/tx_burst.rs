@@ -0,0 +1,192 @@
+use std::borrow::Borrow;
+use std::time::Duration;
+
+use libbladerf_sys as sys;
+
+use crate::BladeRF;
+use crate::Error;
+use crate::Metadata;
+use crate::MetadataStatus;
+use crate::Result;
+use crate::SampleFormat;
+
+use super::TxSyncStream;
+
+/// A burst of samples being written to a [TxSyncStream] configured with metadata.
+///
+/// Obtained from [TxSyncStream::burst()]. The first write marks the start of the burst (`BLADERF_META_FLAG_TX_BURST_START`),
+/// either transmitted right away (`BLADERF_META_FLAG_TX_NOW`) or at the requested timestamp, and [TxBurst::finish()] marks
+/// the end of it (`BLADERF_META_FLAG_TX_BURST_END`) after zero-padding to the buffer size.
+/// Gaps can be inserted in the middle of a burst with [TxBurst::write_at()] (`BLADERF_META_FLAG_TX_UPDATE_TIMESTAMP`).
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRF, BladeRfAny, ComplexI16, ChannelLayoutTx, Direction, TxChannel, StreamConfig};
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let conf = StreamConfig::default().with_metadata(true);
+/// let layout = ChannelLayoutTx::SISO(TxChannel::Tx0);
+///
+/// let tx_stream = dev.tx_streamer::<ComplexI16>(conf, layout).unwrap();
+/// tx_stream.enable().unwrap();
+///
+/// let preamble = vec![ComplexI16::new(1024, 0); 1000];
+/// let payload = vec![ComplexI16::new(0, 1024); 5000];
+/// let start = dev.get_timestamp(Direction::TX).unwrap() + 100_000;
+/// let timeout = Duration::from_secs(1);
+///
+/// let mut burst = tx_stream.burst(Some(start));
+/// burst.write(&preamble, timeout).unwrap();
+/// // Leave some silence between the preamble and the payload
+/// burst.write_at(start + 2000, &payload, timeout).unwrap();
+/// let meta = burst.finish(&[], timeout).unwrap();
+/// assert!(!meta.status.underrun);
+/// ```
+///
+/// Dropping an unfinished burst ends it, ignoring any errors.
+///
+/// Relevant `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/sync_tx_meta_bursts.html>
+#[derive(Debug)]
+pub struct TxBurst<'s, T: Borrow<D>, F: SampleFormat, D: BladeRF> {
+    stream: &'s TxSyncStream<T, F, D>,
+    at: Option<u64>,
+    started: bool,
+    finished: bool,
+    /// Samples written since the start of the burst (or the last timestamp update), counting every channel, used to pad
+    /// to the buffer size.
+    written: usize,
+    status: MetadataStatus,
+}
+
+impl<'s, T: Borrow<D>, F: SampleFormat, D: BladeRF> TxBurst<'s, T, F, D> {
+    pub(crate) fn new(stream: &'s TxSyncStream<T, F, D>, at: Option<u64>) -> Self {
+        Self {
+            stream,
+            at,
+            started: false,
+            finished: false,
+            written: 0,
+            status: MetadataStatus::default(),
+        }
+    }
+
+    /// Appends samples to the burst, directly following the previously written ones.
+    pub fn write(&mut self, samples: &[F], timeout: Duration) -> Result<()> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        let meta = self.next_meta(0, None);
+        self.write_samples(samples, &meta, timeout)?;
+        Ok(())
+    }
+
+    /// Appends samples to the burst which are transmitted at the given device timestamp.
+    ///
+    /// The gap since the previously written samples is filled with zeros. If this is the first write of the burst,
+    /// this overrides the timestamp the burst was created with.
+    pub fn write_at(&mut self, timestamp: u64, samples: &[F], timeout: Duration) -> Result<()> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        let meta = self.next_meta(0, Some(timestamp));
+        self.write_samples(samples, &meta, timeout)?;
+        if meta.flags & sys::BLADERF_META_FLAG_TX_UPDATE_TIMESTAMP != 0 {
+            self.written = samples.len() * F::CHANNELS;
+        }
+        Ok(())
+    }
+
+    /// Writes the final samples of the burst, zero-pads it up to a multiple of the buffer size and ends it.
+    ///
+    /// Returns the [Metadata] of the burst, with [Metadata::status] combined over every write of the burst.
+    ///
+    /// # Errors
+    /// Fails if no samples were written to the burst at all, since `libbladerf` does not allow empty bursts.
+    pub fn finish(mut self, samples: &[F], timeout: Duration) -> Result<Metadata> {
+        self.finish_inner(samples, timeout)
+    }
+
+    fn finish_inner(&mut self, samples: &[F], timeout: Duration) -> Result<Metadata> {
+        if !self.started && samples.is_empty() {
+            return Err(Error::msg("Cannot transmit an empty burst"));
+        }
+        self.finished = true;
+
+        let buffer_size = self.stream.config.buffer_size as usize;
+        let mut padding = (buffer_size
+            - (self.written + samples.len() * F::CHANNELS) % buffer_size)
+            % buffer_size;
+        if padding == 0 && samples.is_empty() {
+            // The end of burst flag needs to be sent along with samples
+            padding = buffer_size;
+        }
+
+        let last = if padding == 0 {
+            let meta = self.next_meta(sys::BLADERF_META_FLAG_TX_BURST_END, None);
+            self.write_samples(samples, &meta, timeout)?
+        } else {
+            if !samples.is_empty() {
+                let meta = self.next_meta(0, None);
+                self.write_samples(samples, &meta, timeout)?;
+            }
+            // All zero bytes are a zero sample for every format, and a converted sample is at least as large as the one sent to the device
+            let zeros = vec![0u8; padding * std::mem::size_of::<F>()];
+            let meta = self.next_meta(sys::BLADERF_META_FLAG_TX_BURST_END, None);
+            // Safety: `zeros` holds `padding` zeroed samples of the format used on the device.
+            let sent = unsafe {
+                self.stream
+                    .write_raw_with_meta(zeros.as_ptr().cast(), padding, &meta, timeout)
+            }?;
+            self.record(padding, sent)
+        };
+
+        Ok(Metadata {
+            status: self.status,
+            ..last
+        })
+    }
+
+    /// Builds the metadata for the next write, adding the start of burst flags if needed.
+    fn next_meta(&self, flags: u32, timestamp: Option<u64>) -> Metadata {
+        let mut meta = Metadata::new();
+        meta.flags = flags;
+        if !self.started {
+            meta.flags |= sys::BLADERF_META_FLAG_TX_BURST_START;
+            match timestamp.or(self.at) {
+                Some(timestamp) => meta.timestamp = timestamp,
+                None => meta.flags |= sys::BLADERF_META_FLAG_TX_NOW,
+            }
+        } else if let Some(timestamp) = timestamp {
+            meta.flags |= sys::BLADERF_META_FLAG_TX_UPDATE_TIMESTAMP;
+            meta.timestamp = timestamp;
+        }
+        meta
+    }
+
+    fn write_samples(
+        &mut self,
+        samples: &[F],
+        meta: &Metadata,
+        timeout: Duration,
+    ) -> Result<Metadata> {
+        let sent = self.stream.write_with_meta(samples, meta, timeout)?;
+        Ok(self.record(samples.len() * F::CHANNELS, sent))
+    }
+
+    /// Keeps track of the samples written and the combined status of the burst.
+    fn record(&mut self, num_samples: usize, meta: Metadata) -> Metadata {
+        self.started = true;
+        self.written += num_samples;
+        self.status.overrun |= meta.status.overrun;
+        self.status.underrun |= meta.status.underrun;
+        meta
+    }
+}
+
+impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> Drop for TxBurst<'_, T, F, D> {
+    fn drop(&mut self) {
+        if self.started && !self.finished {
+            let timeout = Duration::from_millis(self.stream.config.stream_timeout.into());
+            let _ = self.finish_inner(&[], timeout);
+        }
+    }
+}
@@ -1,21 +1,38 @@
 use std::borrow::Borrow;
+use std::ffi::c_void;
 use std::marker::PhantomData;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
 use libbladerf_sys as sys;
+use num_complex::Complex32;
 
+use crate::check_channels;
+use crate::fill_format;
+use crate::interleave_mimo;
+use crate::zeroed_samples;
 use crate::BladeRF;
 use crate::BladeRf1;
 use crate::BladeRf2;
 use crate::BladeRfAny;
 use crate::Channel;
 use crate::ChannelLayoutTx;
+use crate::ComplexI16;
+use crate::Error;
+use crate::FrequencyHopper;
+use crate::Hz;
+use crate::Metadata;
+use crate::QuickTune;
 use crate::Result;
 use crate::SampleFormat;
+use crate::SimulatedBladeRf;
 use crate::TxChannel;
+use crate::Waveform;
 
+use super::StatsRecorder;
 use super::StreamConfig;
+use super::StreamStats;
+use super::TxBurst;
 
 /// A synchronous stream from transmitting samples with the BladeRF
 ///
@@ -43,12 +60,20 @@ use super::StreamConfig;
 /// let tx_stream_b = tx_stream_a.reconfigure::<ComplexI8>(conf, layout).unwrap();
 /// ```
 ///
+/// Samples can also be written as [Complex32](num_complex::Complex32), in which case the stream converts them to
+/// [ComplexI16] in an internal buffer with [brf_cf32_to_ci16_slice()](crate::brf_cf32_to_ci16_slice) before writing them to the device.
+///
 /// The methods for an [TxSyncStream] are a bit different for [BladeRf1] as they won't take the layout parameter.
 #[derive(Debug)]
 pub struct TxSyncStream<T: Borrow<D>, F: SampleFormat, D: BladeRF> {
     pub(crate) dev: T,
     pub(crate) layout: ChannelLayoutTx,
     pub(crate) config: StreamConfig,
+    /// Samples as written to the device, for formats which are [converted](SampleFormat::CONVERTED).
+    pub(crate) raw_buffer: Mutex<Vec<ComplexI16>>,
+    pub(crate) stats: Mutex<StatsRecorder>,
+    /// Interleaved samples for [TxSyncStream::write_mimo()].
+    pub(crate) mimo_buffer: Mutex<Vec<F>>,
     pub(crate) _devtype: PhantomData<D>,
     pub(crate) _format: PhantomData<F>,
 }
@@ -58,19 +83,359 @@ impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> TxSyncStream<T, F, D> {
     ///
     /// This method will error if a call to [TxSyncStream::enable()] as not been made.
     ///
+    /// If the stream was configured with [StreamConfig::with_metadata()], this transmits `buffer` as a burst as soon as
+    /// possible, see [TxSyncStream::write_burst()].
+    ///
     /// Relevant `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_t_r_e_a_m_i_n_g___s_y_n_c.html#ga9717092f3390080ed70f6dfb874a1dea>
     pub fn write(&self, buffer: &[F], timeout: Duration) -> Result<()> {
+        if self.config.metadata {
+            return self.write_burst(buffer, None, timeout).map(|_| ());
+        }
+
+        let started = Instant::now();
+        // Safety: No metadata is exchanged without the metadata format
+        let res = unsafe { self.sync_tx(buffer, std::ptr::null_mut(), timeout) };
+        self.lock_stats()
+            .record(started, buffer.len() * F::CHANNELS, &res);
+        res
+    }
+
+    /// Writes the samples of each channel of a MIMO stream from separate buffers.
+    ///
+    /// The samples are interleaved with [interleave_mimo()]. To avoid the copy, the stream can instead be created with
+    /// [MimoFrame](crate::MimoFrame)s as the sample format, which are written with [TxSyncStream::write()].
+    ///
+    /// # Errors
+    /// - The stream is not configured with [ChannelLayoutTx::MIMO], or its sample format already holds both channels.
+    /// - `tx0` and `tx1` have different lengths.
+    pub fn write_mimo(&self, tx0: &[F], tx1: &[F], timeout: Duration) -> Result<()>
+    where
+        F: Copy,
+    {
+        if !self.layout.is_mimo() || F::CHANNELS != 1 {
+            return Err(Error::msg(
+                "write_mimo() requires a MIMO layout with a single channel sample format",
+            ));
+        }
+        if tx0.len() != tx1.len() {
+            return Err(Error::msg(
+                "Both channel buffers need to have the same length",
+            ));
+        }
+
+        let mut interleaved = self
+            .mimo_buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if interleaved.len() != tx0.len() * 2 {
+            *interleaved = zeroed_samples(tx0.len() * 2);
+        }
+        interleave_mimo(tx0, tx1, &mut interleaved);
+        self.write(&interleaved, timeout)
+    }
+
+    /// Writes IQ samples along with [Metadata] controlling burst boundaries and timing, returning the [Metadata] reported back by `libbladerf`.
+    ///
+    /// This is the low level building block of [TxSyncStream::write_burst()], `meta.flags` is passed through as is.
+    ///
+    /// # Errors
+    /// - The stream was not configured with [StreamConfig::with_metadata()].
+    /// - [Error::TimePast](crate::Error::TimePast) if the requested timestamp has already passed.
+    ///
+    /// Relevant `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/sync_tx_meta_bursts.html>
+    pub fn write_with_meta(
+        &self,
+        buffer: &[F],
+        meta: &Metadata,
+        timeout: Duration,
+    ) -> Result<Metadata> {
+        if !self.config.metadata {
+            return Err(Error::msg(
+                "Stream must be configured with metadata to write metadata",
+            ));
+        }
+
+        let mut meta = sys::bladerf_metadata::from(meta);
+        let started = Instant::now();
+        // Safety: `meta` is valid for the duration of the call
+        let res =
+            unsafe { self.sync_tx(buffer, &mut meta, timeout) }.map(|_| Metadata::from(&meta));
+        self.record_meta(started, buffer.len() * F::CHANNELS, &res);
+        res
+    }
+
+    /// Transmits `samples` as a single burst, either as soon as possible or at the given device timestamp.
+    ///
+    /// The burst is zero-padded up to a multiple of the configured buffer size so the device returns to
+    /// transmitting nothing once it is done. For bursts assembled from multiple pieces, see [TxSyncStream::burst()].
+    ///
+    /// The returned [Metadata] reports if the device underran while transmitting the burst in [Metadata::status].
+    ///
+    /// # Errors
+    /// - The stream was not configured with [StreamConfig::with_metadata()].
+    /// - [Error::TimePast](crate::Error::TimePast) if `at` has already passed.
+    /// - `samples` is empty.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use bladerf::{BladeRF, BladeRfAny, ComplexI16, ChannelLayoutTx, Direction, TxChannel, StreamConfig};
+    /// let dev = BladeRfAny::open_first().unwrap();
+    /// let conf = StreamConfig::default().with_metadata(true);
+    /// let layout = ChannelLayoutTx::SISO(TxChannel::Tx0);
+    ///
+    /// let tx_stream = dev.tx_streamer::<ComplexI16>(conf, layout).unwrap();
+    /// tx_stream.enable().unwrap();
+    ///
+    /// let slot = vec![ComplexI16::new(1024, 0); 10_000];
+    /// let now = dev.get_timestamp(Direction::TX).unwrap();
+    /// // Transmit a slot every 100k samples
+    /// for n in 1..10 {
+    ///     let meta = tx_stream
+    ///         .write_burst(&slot, Some(now + n * 100_000), Duration::from_secs(1))
+    ///         .unwrap();
+    ///     assert!(!meta.status.underrun);
+    /// }
+    /// ```
+    pub fn write_burst(
+        &self,
+        samples: &[F],
+        at: Option<u64>,
+        timeout: Duration,
+    ) -> Result<Metadata> {
+        self.burst(at).finish(samples, timeout)
+    }
+
+    /// Starts a burst which can be assembled from multiple writes, see [TxBurst].
+    ///
+    /// The burst is sent as soon as possible if `at` is [None], otherwise at the given device timestamp.
+    pub fn burst(&self, at: Option<u64>) -> TxBurst<'_, T, F, D> {
+        TxBurst::new(self, at)
+    }
+
+    /// Writes `buffer`, through the conversion buffer if the format is converted.
+    ///
+    /// # Safety
+    /// `meta` must be null or point to valid metadata.
+    unsafe fn sync_tx(
+        &self,
+        buffer: &[F],
+        meta: *mut sys::bladerf_metadata,
+        timeout: Duration,
+    ) -> Result<()> {
+        let num_samples = buffer.len() * F::CHANNELS;
+        if !F::CONVERTED {
+            // Safety: `buffer` holds `num_samples` samples in the configured format
+            return unsafe {
+                self.dev.borrow().sync_tx(
+                    buffer.as_ptr() as *const _,
+                    num_samples as u32,
+                    meta,
+                    timeout.as_millis() as u32,
+                )
+            };
+        }
+
+        let mut raw = self
+            .raw_buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        raw.resize(num_samples, ComplexI16::ZERO);
+        F::to_ci16(buffer, &mut raw);
+        // Safety: Converted formats are configured as Sc16Q11, which `raw` holds `num_samples` samples of
+        unsafe {
+            self.dev.borrow().sync_tx(
+                raw.as_ptr() as *const _,
+                num_samples as u32,
+                meta,
+                timeout.as_millis() as u32,
+            )
+        }
+    }
+
+    /// Writes `num_samples` samples of the configured format from `samples` along with `meta`.
+    ///
+    /// # Safety
+    /// `samples` must point to `num_samples` samples as exchanged with the device, which is [ComplexI16] for
+    /// [converted](SampleFormat::CONVERTED) formats.
+    pub(crate) unsafe fn write_raw_with_meta(
+        &self,
+        samples: *const c_void,
+        num_samples: usize,
+        meta: &Metadata,
+        timeout: Duration,
+    ) -> Result<Metadata> {
+        if !self.config.metadata {
+            return Err(Error::msg(
+                "Stream must be configured with metadata to write metadata",
+            ));
+        }
+
+        let mut meta = sys::bladerf_metadata::from(meta);
+        let started = Instant::now();
         let res = unsafe {
-            sys::bladerf_sync_tx(
-                self.dev.borrow().get_device_ptr(),
-                buffer.as_ptr() as *const _,
-                buffer.len() as u32,
-                std::ptr::null_mut(),
+            self.dev.borrow().sync_tx(
+                samples,
+                num_samples as u32,
+                &mut meta,
                 timeout.as_millis() as u32,
             )
-        };
-        check_res!(res);
-        Ok(())
+        }
+        .map(|_| Metadata::from(&meta));
+        self.record_meta(started, num_samples, &res);
+        res
+    }
+
+    /// Returns a snapshot of the statistics gathered since the stream was created or [TxSyncStream::reset_stats()] was called.
+    ///
+    /// Underruns would only be counted when the stream is configured with [StreamConfig::with_metadata()], but
+    /// `libbladerf` does not report them, see [StreamStats::underruns].
+    pub fn stats(&self) -> StreamStats {
+        self.lock_stats().snapshot(self.layout.num_channels())
+    }
+
+    /// Clears the statistics returned by [TxSyncStream::stats()].
+    ///
+    /// This also queries the sample rate reported as [StreamStats::configured_sample_rate] again, which is otherwise only
+    /// done when the stream is created.
+    pub fn reset_stats(&self) {
+        let sample_rate = self.dev.borrow().get_sample_rate(self.channel()).ok();
+        *self.lock_stats() = StatsRecorder::new(sample_rate);
+    }
+
+    fn record_meta(&self, started: Instant, num_samples: usize, res: &Result<Metadata>) {
+        let mut stats = self.lock_stats();
+        stats.record(started, num_samples, res);
+        if let Ok(meta) = res {
+            stats.record_tx_meta(meta);
+        }
+    }
+
+    /// Schedules a retune of the stream's channel to `frequency` once the TX timestamp reaches `at`.
+    ///
+    /// With a MIMO layout this retunes Tx0, which shares its oscillator with Tx1 on the bladeRF 2.0.
+    /// Passing a [QuickTune] from [BladeRF::get_quick_tune()] skips the tuning algorithm, see [BladeRF::schedule_retune()]
+    /// for details. For hopping through a list of frequencies, see [TxSyncStream::frequency_hopper()].
+    ///
+    /// # Errors
+    /// [Error::QueueFull] if too many retunes are pending already.
+    pub fn schedule_retune(
+        &self,
+        at: u64,
+        frequency: impl Into<Hz>,
+        quick_tune: Option<&QuickTune>,
+    ) -> Result<()> {
+        let mut quick_tune = quick_tune.cloned();
+        self.dev
+            .borrow()
+            .schedule_retune(self.channel(), at, frequency, quick_tune.as_mut())
+    }
+
+    /// Cancels all retunes scheduled for the stream's channel.
+    pub fn cancel_scheduled_retunes(&self) -> Result<()> {
+        self.dev.borrow().cancel_scheduled_retune(self.channel())
+    }
+
+    /// Creates a [FrequencyHopper] retuning the stream's channel through `frequencies`, dwelling `dwell` samples on each.
+    ///
+    /// See [FrequencyHopper::new()].
+    pub fn frequency_hopper(
+        &self,
+        frequencies: &[impl Into<Hz> + Copy],
+        dwell: u64,
+    ) -> Result<FrequencyHopper<'_, D>> {
+        FrequencyHopper::new(self.dev.borrow(), self.channel(), frequencies, dwell)
+    }
+
+    /// Transmits `duration` worth of samples from `waveform` at the channel's sample rate, returning the number of
+    /// samples written per channel.
+    ///
+    /// The samples are written in blocks of the configured buffer size, each waiting up to the configured stream timeout.
+    /// With a MIMO layout, every channel transmits the same waveform. Streams configured with
+    /// [StreamConfig::with_metadata()] transmit it as a single burst starting right away, see [TxSyncStream::burst()].
+    ///
+    /// Since the [Waveform] is borrowed, calling this again continues it without a phase jump.
+    pub fn transmit_from<W: Waveform + ?Sized>(
+        &self,
+        waveform: &mut W,
+        duration: Duration,
+    ) -> Result<u64> {
+        let sample_rate = self.dev.borrow().get_sample_rate(self.channel())?;
+        let total = (duration.as_secs_f64() * sample_rate as f64).round() as u64;
+        let timeout = Duration::from_millis(self.config.stream_timeout.into());
+        let channels = self.layout.num_channels();
+        let block_len = (self.config.buffer_size as usize / channels).max(1);
+
+        let mut samples = vec![Complex32::ZERO; block_len];
+        let mut raw = Vec::with_capacity(block_len * channels);
+        let mut block = zeroed_samples::<F>(block_len * channels / F::CHANNELS);
+        let mut burst = self.config.metadata.then(|| self.burst(None));
+
+        let mut written = 0;
+        while written < total {
+            let len = block_len.min((total - written) as usize);
+            let values = len * channels / F::CHANNELS;
+            fill_format(
+                waveform,
+                &mut samples[..len],
+                channels,
+                &mut raw,
+                &mut block[..values],
+            );
+            match &mut burst {
+                Some(burst) => burst.write(&block[..values], timeout)?,
+                None => self.write(&block[..values], timeout)?,
+            }
+            written += len as u64;
+        }
+
+        if let Some(burst) = burst {
+            if written > 0 {
+                burst.finish(&[], timeout)?;
+            }
+        }
+        Ok(written)
+    }
+
+    /// The channel of a SISO stream, or the first channel of a MIMO stream.
+    fn channel(&self) -> Channel {
+        match self.layout {
+            ChannelLayoutTx::SISO(ch) => ch.into(),
+            ChannelLayoutTx::MIMO => Channel::Tx0,
+        }
+    }
+
+    fn lock_stats(&self) -> MutexGuard<'_, StatsRecorder> {
+        self.stats.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Enables the stream (and the relevant hardware) so samples can be written.
+    pub fn enable(&self) -> Result<()> {
+        // Safety, should be find to do a reconfigure here, nothing changes about the config, we just need to do this because disable will uninitialize the config
+        unsafe {
+            self.dev
+                .borrow()
+                .set_sync_config::<F>(&self.config, self.layout.into())?;
+        }
+        match self.layout {
+            ChannelLayoutTx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), true),
+            ChannelLayoutTx::MIMO => {
+                self.dev.borrow().set_enable_module(Channel::Tx0, true)?;
+                self.dev.borrow().set_enable_module(Channel::Tx1, true)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Disables the stream (and the relevant hardware).
+    pub fn disable(&self) -> Result<()> {
+        match self.layout {
+            ChannelLayoutTx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), false),
+            ChannelLayoutTx::MIMO => {
+                self.dev.borrow().set_enable_module(Channel::Tx0, false)?;
+                self.dev.borrow().set_enable_module(Channel::Tx1, false)?;
+                Ok(())
+            }
+        }
     }
 
     /// # Safety
@@ -80,17 +445,24 @@ impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> TxSyncStream<T, F, D> {
         config: StreamConfig,
         layout: ChannelLayoutTx,
     ) -> Result<TxSyncStream<T, F, D>> {
+        check_channels::<F>(layout.num_channels())?;
         unsafe {
             dev.borrow().set_sync_config::<F>(&config, layout.into())?;
         }
 
-        Ok(TxSyncStream {
+        let stream = TxSyncStream {
             dev,
             layout,
             config,
+            raw_buffer: Mutex::new(Vec::new()),
+            stats: Mutex::new(StatsRecorder::new(None)),
+            mimo_buffer: Mutex::new(Vec::new()),
             _devtype: PhantomData,
             _format: PhantomData,
-        })
+        };
+        // Queries the sample rate once, instead of on every call to stats()
+        stream.reset_stats();
+        Ok(stream)
     }
 }
 
@@ -127,24 +499,6 @@ impl<T: Borrow<D>, F: SampleFormat, D: BladeRF> Drop for TxSyncStream<T, F, D> {
 ////////////////////////////////////////////////////////////////////////////////
 // RX Stream Brf1
 
-impl<T: Borrow<BladeRf1>, F: SampleFormat> TxSyncStream<T, F, BladeRf1> {
-    /// Enables the stream (and the relevant hardware) so samples can be written.
-    pub fn enable(&self) -> Result<()> {
-        // Safety, should be find to do a reconfigure here, nothing changes about the config, we just need to do this because disable will uninitialize the config
-        unsafe {
-            self.dev
-                .borrow()
-                .set_sync_config::<F>(&self.config, self.layout.into())?;
-        }
-        self.dev.borrow().set_enable_module(Channel::Tx0, true)
-    }
-
-    /// Disables the stream (and the relevant hardware).
-    pub fn disable(&self) -> Result<()> {
-        self.dev.borrow().set_enable_module(Channel::Tx0, false)
-    }
-}
-
 impl<'a, F: SampleFormat> TxSyncStream<&'a BladeRf1, F, BladeRf1> {
     /// Allows reconfiguring a stream to change either the [StreamConfig] or [SampleFormat]
     ///
@@ -172,38 +526,6 @@ impl<F: SampleFormat> TxSyncStream<Arc<BladeRf1>, F, BladeRf1> {
 ////////////////////////////////////////////////////////////////////////////////
 // RX Stream Brf2
 
-impl<T: Borrow<BladeRf2> + Clone, F: SampleFormat> TxSyncStream<T, F, BladeRf2> {
-    /// Enables the stream (and the relevant hardware) so samples can be written.
-    pub fn enable(&self) -> Result<()> {
-        // Safety, should be find to do a reconfigure here, nothing changes about the config, we just need to do this because disable will uninitialize the config
-        unsafe {
-            self.dev
-                .borrow()
-                .set_sync_config::<F>(&self.config, self.layout.into())?;
-        }
-        match self.layout {
-            ChannelLayoutTx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), true),
-            ChannelLayoutTx::MIMO => {
-                self.dev.borrow().set_enable_module(Channel::Tx0, true)?;
-                self.dev.borrow().set_enable_module(Channel::Tx1, true)?;
-                Ok(())
-            }
-        }
-    }
-
-    /// Disables the stream (and the relevant hardware).
-    pub fn disable(&self) -> Result<()> {
-        match self.layout {
-            ChannelLayoutTx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), false),
-            ChannelLayoutTx::MIMO => {
-                self.dev.borrow().set_enable_module(Channel::Tx0, false)?;
-                self.dev.borrow().set_enable_module(Channel::Tx1, false)?;
-                Ok(())
-            }
-        }
-    }
-}
-
 impl<'a, F: SampleFormat> TxSyncStream<&'a BladeRf2, F, BladeRf2> {
     /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutTx]
     ///
@@ -233,39 +555,36 @@ impl<F: SampleFormat> TxSyncStream<Arc<BladeRf2>, F, BladeRf2> {
 ////////////////////////////////////////////////////////////////////////////////
 // RX Stream BrfAny
 
-impl<T: Borrow<BladeRfAny> + Clone, F: SampleFormat> TxSyncStream<T, F, BladeRfAny> {
-    /// Enables the stream (and the relevant hardware) so samples can be written.
-    pub fn enable(&self) -> Result<()> {
-        // Safety, should be find to do a reconfigure here, nothing changes about the config, we just need to do this because disable will uninitialize the config
-        unsafe {
-            self.dev
-                .borrow()
-                .set_sync_config::<F>(&self.config, self.layout.into())?;
-        }
-        match self.layout {
-            ChannelLayoutTx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), true),
-            ChannelLayoutTx::MIMO => {
-                self.dev.borrow().set_enable_module(Channel::Tx0, true)?;
-                self.dev.borrow().set_enable_module(Channel::Tx1, true)?;
-                Ok(())
-            }
-        }
+impl<'a, F: SampleFormat> TxSyncStream<&'a BladeRfAny, F, BladeRfAny> {
+    /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutTx]
+    ///
+    /// See the general [TxSyncStream] docs for usage example.
+    pub fn reconfigure<NF: SampleFormat>(
+        self,
+        config: StreamConfig,
+        layout: ChannelLayoutTx,
+    ) -> Result<TxSyncStream<&'a BladeRfAny, NF, BladeRfAny>> {
+        self.reconfigure_inner(config, layout)
     }
+}
 
-    /// Disables the stream (and the relevant hardware).
-    pub fn disable(&self) -> Result<()> {
-        match self.layout {
-            ChannelLayoutTx::SISO(ch) => self.dev.borrow().set_enable_module(ch.into(), false),
-            ChannelLayoutTx::MIMO => {
-                self.dev.borrow().set_enable_module(Channel::Tx0, false)?;
-                self.dev.borrow().set_enable_module(Channel::Tx1, false)?;
-                Ok(())
-            }
-        }
+impl<F: SampleFormat> TxSyncStream<Arc<BladeRfAny>, F, BladeRfAny> {
+    /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutTx]
+    ///
+    /// See the general [TxSyncStream] docs for usage example.
+    pub fn reconfigure<NF: SampleFormat>(
+        self,
+        config: StreamConfig,
+        layout: ChannelLayoutTx,
+    ) -> Result<TxSyncStream<Arc<BladeRfAny>, NF, BladeRfAny>> {
+        self.reconfigure_inner(config, layout)
     }
 }
 
-impl<'a, F: SampleFormat> TxSyncStream<&'a BladeRfAny, F, BladeRfAny> {
+////////////////////////////////////////////////////////////////////////////////
+// TX Stream Simulated
+
+impl<'a, F: SampleFormat> TxSyncStream<&'a SimulatedBladeRf, F, SimulatedBladeRf> {
     /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutTx]
     ///
     /// See the general [TxSyncStream] docs for usage example.
@@ -273,12 +592,12 @@ impl<'a, F: SampleFormat> TxSyncStream<&'a BladeRfAny, F, BladeRfAny> {
         self,
         config: StreamConfig,
         layout: ChannelLayoutTx,
-    ) -> Result<TxSyncStream<&'a BladeRfAny, NF, BladeRfAny>> {
+    ) -> Result<TxSyncStream<&'a SimulatedBladeRf, NF, SimulatedBladeRf>> {
         self.reconfigure_inner(config, layout)
     }
 }
 
-impl<F: SampleFormat> TxSyncStream<Arc<BladeRfAny>, F, BladeRfAny> {
+impl<F: SampleFormat> TxSyncStream<Arc<SimulatedBladeRf>, F, SimulatedBladeRf> {
     /// Allows reconfiguring a stream to change either the [StreamConfig]/[SampleFormat]/[ChannelLayoutTx]
     ///
     /// See the general [TxSyncStream] docs for usage example.
@@ -286,7 +605,7 @@ impl<F: SampleFormat> TxSyncStream<Arc<BladeRfAny>, F, BladeRfAny> {
         self,
         config: StreamConfig,
         layout: ChannelLayoutTx,
-    ) -> Result<TxSyncStream<Arc<BladeRfAny>, NF, BladeRfAny>> {
+    ) -> Result<TxSyncStream<Arc<SimulatedBladeRf>, NF, SimulatedBladeRf>> {
         self.reconfigure_inner(config, layout)
     }
 }
@@ -0,0 +1,448 @@
+use std::borrow::Borrow;
+use std::fmt::Write as _;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use num_complex::Complex32;
+
+use crate::dsp::{to_db, PowerSpectrum, Window};
+use crate::hopping::compute_hops;
+use crate::time::civil_datetime;
+use crate::BladeRF;
+use crate::Channel;
+use crate::ChannelLayoutRx;
+use crate::Direction;
+use crate::Error;
+use crate::Hz;
+use crate::QuickTune;
+use crate::Result;
+use crate::RxSyncStream;
+use crate::SampleRate;
+
+/// Parameters of a [Sweeper].
+///
+/// Every step captures `fft_size` samples at `sample_rate` and keeps the center [SweepConfig::with_usable_fraction()] of
+/// the resulting bins, so a step covers `sample_rate * usable_fraction` Hz of the span.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SweepConfig {
+    pub(crate) start: u64,
+    pub(crate) stop: u64,
+    pub(crate) sample_rate: u32,
+    pub(crate) fft_size: usize,
+    pub(crate) usable_fraction: f64,
+    pub(crate) averages: usize,
+    pub(crate) settle: u64,
+    pub(crate) retune_lead: Duration,
+}
+
+impl SweepConfig {
+    /// Sweeps from `start` to `stop` Hz at `sample_rate` with `fft_size` bins per step.
+    ///
+    /// Takes the frequencies and the sample rate either as bare integers or as [Hz] and [SampleRate].
+    ///
+    /// # Errors
+    /// - `start` is not below `stop`.
+    /// - `fft_size` is below 16.
+    pub fn new(
+        start: impl Into<Hz>,
+        stop: impl Into<Hz>,
+        sample_rate: impl Into<SampleRate>,
+        fft_size: usize,
+    ) -> Result<Self> {
+        let (start, stop) = (start.into().0, stop.into().0);
+        if start >= stop {
+            return Err(Error::msg("Sweep start must be below its stop frequency"));
+        }
+        if fft_size < 16 {
+            return Err(Error::msg("FFT size must be at least 16"));
+        }
+        Ok(Self {
+            start,
+            stop,
+            sample_rate: sample_rate.into().0,
+            fft_size,
+            usable_fraction: 0.75,
+            averages: 1,
+            settle: fft_size as u64,
+            retune_lead: Duration::from_millis(5),
+        })
+    }
+
+    /// Sets the fraction of the bins in the middle of every step which are kept, 0.75 by default.
+    ///
+    /// The bins towards the edges are attenuated by the anti-aliasing filters and are discarded.
+    pub fn with_usable_fraction(mut self, fraction: f64) -> Self {
+        self.usable_fraction = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the number of consecutive FFTs averaged at every step, 1 by default.
+    pub fn with_averages(mut self, averages: usize) -> Self {
+        self.averages = averages.max(1);
+        self
+    }
+
+    /// Sets the number of samples discarded after every retune while the LO settles, `fft_size` by default.
+    pub fn with_settle_samples(mut self, settle: u64) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    /// Sets how far ahead of the device timestamp scheduled retunes are placed, 5 ms by default.
+    ///
+    /// This only applies to streams with metadata, and needs to cover the latency of scheduling the retune.
+    pub fn with_retune_lead(mut self, lead: Duration) -> Self {
+        self.retune_lead = lead;
+        self
+    }
+}
+
+/// The power spectrum of a single step of a sweep, in the shape of a `hackrf_sweep` output line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepLine {
+    /// When the samples were captured.
+    pub time: SystemTime,
+    /// Number of the sweep this line belongs to, counting from zero.
+    pub sweep: u64,
+    /// Frequency of the lower edge of the first bin.
+    pub hz_low: f64,
+    /// Frequency of the upper edge of the last bin.
+    pub hz_high: f64,
+    /// Width of every bin.
+    pub bin_width: f64,
+    /// Number of samples the spectrum was computed from.
+    pub num_samples: usize,
+    /// Power of every bin in dBFS.
+    pub power_db: Vec<f32>,
+}
+
+impl SweepLine {
+    /// Formats the line the way `hackrf_sweep` does: date, time, hz_low, hz_high, hz_bin_width, num_samples, dB, dB, ...
+    ///
+    /// The time is in UTC and the line has no trailing newline.
+    pub fn to_csv(&self) -> String {
+        let (year, month, day, secs_of_day) = civil_datetime(self.time);
+        let micros = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_micros();
+        let mut line = format!(
+            "{year:04}-{month:02}-{day:02}, {:02}:{:02}:{:02}.{micros:06}, {:.0}, {:.0}, {:.2}, {}",
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60,
+            self.hz_low,
+            self.hz_high,
+            self.bin_width,
+            self.num_samples,
+        );
+        for power in &self.power_db {
+            let _ = write!(line, ", {power:.2}");
+        }
+        line
+    }
+}
+
+/// The power spectrum of a complete sweep, stitched together from its [SweepLine]s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spectrum {
+    /// Frequency of the lower edge of the first bin.
+    pub start: f64,
+    /// Width of every bin.
+    pub bin_width: f64,
+    /// Power of every bin in dBFS.
+    pub power_db: Vec<f32>,
+}
+
+impl Spectrum {
+    /// Center frequency of bin `index`.
+    pub fn frequency(&self, index: usize) -> f64 {
+        self.start + (index as f64 + 0.5) * self.bin_width
+    }
+
+    /// Center frequency and power of the strongest bin.
+    pub fn peak(&self) -> Option<(f64, f32)> {
+        self.power_db
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(index, power)| (self.frequency(index), *power))
+    }
+}
+
+/// Measures the power spectrum of a span wider than the sample rate by stepping the RX LO across it, like `hackrf_sweep`.
+///
+/// At every step, the sweeper tunes the channel, waits for the LO to settle, captures a block and computes its
+/// Hann-windowed FFT power spectrum. The bins at the band edges are discarded and the DC bin is replaced by the mean of
+/// its neighbours, which hides the LO leakage. What is left is contiguous with the neighbouring steps.
+///
+/// If the stream is configured with [StreamConfig::with_metadata()](crate::StreamConfig::with_metadata), the
+/// [QuickTune] parameters of every step are computed up front and each retune is scheduled with
+/// [RxSyncStream::schedule_retune()], reading the samples right after it by timestamp. Otherwise the sweeper tunes with
+/// [BladeRF::set_frequency()] and discards the samples which were already buffered by the stream.
+///
+/// The stream needs to use a SISO layout and be enabled.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutRx, RxChannel, StreamConfig, SweepConfig, Sweeper};
+/// use num_complex::Complex32;
+///
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let conf = StreamConfig::default().with_metadata(true);
+/// let rx_stream = dev.rx_streamer::<Complex32>(conf, ChannelLayoutRx::SISO(RxChannel::Rx0)).unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// let config = SweepConfig::new(2_400_000_000, 2_500_000_000, 20_000_000, 1024).unwrap();
+/// let mut sweeper = Sweeper::new(&rx_stream, config, Duration::from_secs(1)).unwrap();
+/// for line in sweeper.lines().take(100) {
+///     println!("{}", line.unwrap().to_csv());
+/// }
+/// ```
+pub struct Sweeper<'s, T: Borrow<D>, D: BladeRF> {
+    stream: &'s RxSyncStream<T, Complex32, D>,
+    channel: Channel,
+    config: SweepConfig,
+    timeout: Duration,
+    /// Center frequency of every step.
+    centers: Vec<u64>,
+    /// Precomputed tuning parameters of every step, if retunes are scheduled.
+    quick_tunes: Option<Vec<QuickTune>>,
+    spectrum: PowerSpectrum,
+    /// Bins of the spectrum kept at every step.
+    kept: usize,
+    bin_width: f64,
+    samples: Vec<Complex32>,
+    step: usize,
+    sweep: u64,
+}
+
+impl<T: Borrow<D>, D: BladeRF> std::fmt::Debug for Sweeper<'_, T, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sweeper")
+            .field("channel", &self.channel)
+            .field("config", &self.config)
+            .field("centers", &self.centers)
+            .field("step", &self.step)
+            .field("sweep", &self.sweep)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s, T: Borrow<D>, D: BladeRF> Sweeper<'s, T, D> {
+    /// Sets up the channel of `stream` for the sweep, waiting up to `timeout` for every block.
+    ///
+    /// This sets the sample rate and bandwidth of the channel and, for streams with metadata, tunes to every step once
+    /// to compute its [QuickTune].
+    ///
+    /// # Errors
+    /// - The stream does not use a SISO layout.
+    /// - The span reaches outside of [BladeRF::get_frequency_range()] of the channel.
+    /// - The usable fraction leaves less than two bins per step.
+    pub fn new(
+        stream: &'s RxSyncStream<T, Complex32, D>,
+        config: SweepConfig,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let channel = match stream.layout {
+            ChannelLayoutRx::SISO(channel) => Channel::from(channel),
+            ChannelLayoutRx::MIMO => {
+                return Err(Error::msg("Sweeping requires a SISO stream"));
+            }
+        };
+        let dev = stream.dev.borrow();
+
+        let sample_rate = dev.set_sample_rate(channel, config.sample_rate)?;
+        dev.set_bandwidth(channel, sample_rate)?;
+
+        let fft_size = config.fft_size;
+        // An even number of bins, so the steps stay centered on their LO
+        let kept = ((fft_size as f64 * config.usable_fraction) as usize / 2 * 2).min(fft_size);
+        if kept < 2 {
+            return Err(Error::msg("Usable fraction leaves no bins to keep"));
+        }
+        let bin_width = sample_rate as f64 / fft_size as f64;
+        let step_width = kept as f64 * bin_width;
+        let num_steps = ((config.stop - config.start) as f64 / step_width).ceil() as usize;
+        let centers: Vec<u64> = (0..num_steps)
+            .map(|step| (config.start as f64 + step_width * (step as f64 + 0.5)).round() as u64)
+            .collect();
+
+        let range = dev.get_frequency_range(channel)?;
+        let lowest = centers[0] as f64 - step_width / 2.0;
+        let highest = centers[num_steps - 1] as f64 + step_width / 2.0;
+        if lowest < range.min || highest > range.max {
+            return Err(Error::msg(format!(
+                "Sweep from {lowest:.0} to {highest:.0} Hz exceeds the tuning range {range}"
+            )));
+        }
+
+        let quick_tunes = if stream.config.metadata {
+            let hops = compute_hops(dev, channel, centers.iter().copied())?;
+            Some(hops.into_iter().map(|hop| hop.quick_tune).collect())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            stream,
+            channel,
+            config: SweepConfig {
+                sample_rate,
+                ..config
+            },
+            timeout,
+            centers,
+            quick_tunes,
+            spectrum: PowerSpectrum::new(fft_size, Window::Hann)?,
+            kept,
+            bin_width,
+            samples: vec![Complex32::default(); fft_size * config.averages],
+            step: 0,
+            sweep: 0,
+        })
+    }
+
+    /// Number of steps, and with that [SweepLine]s, of every sweep.
+    pub fn num_steps(&self) -> usize {
+        self.centers.len()
+    }
+
+    /// Width of the frequency bins.
+    pub fn bin_width(&self) -> f64 {
+        self.bin_width
+    }
+
+    /// Captures the next step of the sweep, starting over at the lowest step after the highest one.
+    pub fn next_line(&mut self) -> Result<SweepLine> {
+        let center = self.centers[self.step];
+        let time = SystemTime::now();
+        self.capture()?;
+
+        let mut power = self.spectrum.compute(&self.samples)?;
+        // Hide the LO leakage
+        let dc = self.config.fft_size / 2;
+        power[dc] = (power[dc - 1] + power[dc + 1]) / 2.0;
+
+        let first_bin = (self.config.fft_size - self.kept) / 2;
+        let hz_low = center as f64 - (self.kept / 2) as f64 * self.bin_width;
+        // The highest step may reach past the end of the span
+        let in_span = ((self.config.stop as f64 - hz_low) / self.bin_width).ceil() as usize;
+        let num_bins = self.kept.min(in_span);
+
+        let line = SweepLine {
+            time,
+            sweep: self.sweep,
+            hz_low,
+            hz_high: hz_low + num_bins as f64 * self.bin_width,
+            bin_width: self.bin_width,
+            num_samples: self.samples.len(),
+            power_db: power[first_bin..first_bin + num_bins]
+                .iter()
+                .map(|&power| to_db(power))
+                .collect(),
+        };
+
+        self.step += 1;
+        if self.step == self.centers.len() {
+            self.step = 0;
+            self.sweep += 1;
+        }
+        Ok(line)
+    }
+
+    /// An endless iterator over the [SweepLine]s of consecutive sweeps.
+    pub fn lines(&mut self) -> SweepLines<'_, 's, T, D> {
+        SweepLines { sweeper: self }
+    }
+
+    /// Runs a complete sweep, starting at the lowest step, and stitches its lines into one [Spectrum].
+    pub fn sweep(&mut self) -> Result<Spectrum> {
+        self.step = 0;
+        let mut power_db = Vec::new();
+        let mut start = 0.0;
+        for step in 0..self.centers.len() {
+            let line = self.next_line()?;
+            if step == 0 {
+                start = line.hz_low;
+            }
+            power_db.extend(line.power_db);
+        }
+        Ok(Spectrum {
+            start,
+            bin_width: self.bin_width,
+            power_db,
+        })
+    }
+
+    /// Tunes to the current step and reads the samples after the LO settled.
+    fn capture(&mut self) -> Result<()> {
+        let center = self.centers[self.step];
+        let dev = self.stream.dev.borrow();
+        match &self.quick_tunes {
+            Some(quick_tunes) => {
+                let lead =
+                    (self.config.retune_lead.as_secs_f64() * self.config.sample_rate as f64) as u64;
+                let at = dev.get_timestamp(Direction::RX)? + lead;
+                self.stream
+                    .schedule_retune(at, center, Some(&quick_tunes[self.step]))?;
+                self.stream.read_with_meta(
+                    &mut self.samples,
+                    Some(at + self.config.settle),
+                    self.timeout,
+                )?;
+            }
+            None => {
+                dev.set_frequency(self.channel, center)?;
+                // Samples buffered by the stream were received before the retune
+                let stale = self.stream.config.num_buffers as u64
+                    * self.stream.config.buffer_size as u64
+                    + self.config.settle;
+                let mut left = stale;
+                while left > 0 {
+                    let len = (left as usize).min(self.samples.len());
+                    self.stream.read(&mut self.samples[..len], self.timeout)?;
+                    left -= len as u64;
+                }
+                self.stream.read(&mut self.samples, self.timeout)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An endless iterator over the [SweepLine]s of a [Sweeper], obtained from [Sweeper::lines()].
+#[derive(Debug)]
+pub struct SweepLines<'a, 's, T: Borrow<D>, D: BladeRF> {
+    sweeper: &'a mut Sweeper<'s, T, D>,
+}
+
+impl<T: Borrow<D>, D: BladeRF> Iterator for SweepLines<'_, '_, T, D> {
+    type Item = Result<SweepLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.sweeper.next_line())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_line() {
+        let line = SweepLine {
+            time: UNIX_EPOCH + Duration::from_micros(1_546_516_654_967_805),
+            sweep: 0,
+            hz_low: 2_400_000_000.0,
+            hz_high: 2_400_000_000.0 + 2.0 * 1_000_000.0,
+            bin_width: 1_000_000.0,
+            num_samples: 20,
+            power_db: vec![-64.724, -70.0],
+        };
+        assert_eq!(
+            line.to_csv(),
+            "2019-01-03, 11:57:34.967805, 2400000000, 2402000000, 1000000.00, 20, -64.72, -70.00"
+        );
+    }
+}
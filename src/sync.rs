@@ -0,0 +1,464 @@
+//! Synchronized reception across several devices sharing a reference clock and a trigger signal.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::streamers::{RxSyncStream, StreamConfig};
+use crate::{
+    BladeRF, BladeRf1, BladeRf2, BladeRfAny, Channel, ChannelLayoutRx, ClockSelect, Direction,
+    Error, Metadata, Result, RxChannel, SampleFormat, SmbMode, TriggerMaster, TriggerSignal,
+    TriggerSlave,
+};
+
+/// Role of a device in the reference clock distribution between several devices.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClockRole {
+    /// The device runs from its onboard reference and does not output it.
+    Independent,
+    /// The device runs from its onboard reference and outputs it to the other devices.
+    Master,
+    /// The device runs from the reference provided by the master.
+    Slave,
+}
+
+/// Devices which can share their reference clock and be started by a [SyncCoordinator].
+///
+/// | Role                       | [BladeRf1]             | [BladeRf2]                                      |
+/// |----------------------------|------------------------|-------------------------------------------------|
+/// | [ClockRole::Independent]   | [SmbMode::Disabled]    | [ClockSelect::Onboard], clock output disabled   |
+/// | [ClockRole::Master]        | [SmbMode::Output]      | [ClockSelect::Onboard], clock output enabled    |
+/// | [ClockRole::Slave]         | [SmbMode::Input]       | [ClockSelect::External], clock output disabled  |
+///
+/// [BladeRfAny] picks the configuration based on [BladeRF::get_board_name()].
+pub trait SyncDevice: BladeRF {
+    /// Configures the reference clock of the device for the given role.
+    fn set_clock_role(&self, role: ClockRole) -> Result<()>;
+
+    /// Opens a receive stream on a shared device.
+    ///
+    /// Only [ChannelLayoutRx::SISO] with [RxChannel::Rx0] is supported for [BladeRf1].
+    fn sync_rx_streamer<F: SampleFormat>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+    ) -> Result<RxSyncStream<Arc<Self>, F, Self>>;
+}
+
+impl SyncDevice for BladeRf1 {
+    fn set_clock_role(&self, role: ClockRole) -> Result<()> {
+        set_smb_role(self, role)
+    }
+
+    fn sync_rx_streamer<F: SampleFormat>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+    ) -> Result<RxSyncStream<Arc<Self>, F, Self>> {
+        if layout != ChannelLayoutRx::SISO(RxChannel::Rx0) {
+            return Err(Error::Unsupported);
+        }
+        BladeRf1::rx_streamer_arc(device, config)
+    }
+}
+
+impl SyncDevice for BladeRf2 {
+    fn set_clock_role(&self, role: ClockRole) -> Result<()> {
+        set_clkout_role(self, role)
+    }
+
+    fn sync_rx_streamer<F: SampleFormat>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+    ) -> Result<RxSyncStream<Arc<Self>, F, Self>> {
+        BladeRf2::rx_streamer_arc(device, config, layout)
+    }
+}
+
+impl SyncDevice for BladeRfAny {
+    fn set_clock_role(&self, role: ClockRole) -> Result<()> {
+        match self.get_board_name() {
+            "bladerf1" => set_smb_role(self, role),
+            "bladerf2" => set_clkout_role(self, role),
+            _ => Err(Error::Unsupported),
+        }
+    }
+
+    fn sync_rx_streamer<F: SampleFormat>(
+        device: Arc<Self>,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+    ) -> Result<RxSyncStream<Arc<Self>, F, Self>> {
+        BladeRfAny::rx_streamer_arc(device, config, layout)
+    }
+}
+
+/// Clock distribution of the bladeRF 1 through the SMB clock port (J62).
+fn set_smb_role<D: BladeRF>(dev: &D, role: ClockRole) -> Result<()> {
+    let mode = match role {
+        ClockRole::Independent => SmbMode::Disabled,
+        ClockRole::Master => SmbMode::Output,
+        ClockRole::Slave => SmbMode::Input,
+    };
+    dev.set_smb_mode(mode)
+}
+
+/// Clock distribution of the bladeRF 2.0 through the CLKOUT and CLKIN connectors.
+fn set_clkout_role<D: BladeRF>(dev: &D, role: ClockRole) -> Result<()> {
+    let (select, output) = match role {
+        ClockRole::Independent => (ClockSelect::Onboard, false),
+        ClockRole::Master => (ClockSelect::Onboard, true),
+        ClockRole::Slave => (ClockSelect::External, false),
+    };
+    dev.set_clock_select(select)?;
+    dev.set_clock_output(output)
+}
+
+/// Checks the first samples of all devices were taken at the same instant, given the number of samples between reading
+/// the armed timestamp of each device and its first sample. The armed timestamps were read within `read_spread`.
+fn check_common_start(delays: &[u64], sample_rates: &[f64], read_spread: Duration) -> Result<()> {
+    let seconds: Vec<f64> = delays
+        .iter()
+        .zip(sample_rates)
+        .map(|(&delay, &rate)| delay as f64 / rate)
+        .collect();
+    let (Some(earliest), Some(latest)) = (
+        seconds.iter().copied().reduce(f64::min),
+        seconds.iter().copied().reduce(f64::max),
+    ) else {
+        return Ok(());
+    };
+    // One sample of quantization on either side
+    let slowest = sample_rates.iter().copied().fold(f64::INFINITY, f64::min);
+    let tolerance = read_spread.as_secs_f64() + 2.0 / slowest;
+    if latest - earliest > tolerance {
+        return Err(Error::msg(format!(
+            "The devices started {:.1} us apart, more than the {:.1} us tolerance",
+            (latest - earliest) * 1e6,
+            tolerance * 1e6
+        )));
+    }
+    Ok(())
+}
+
+/// Starts receive streams on several devices at the same instant.
+///
+/// One device is designated the master: it outputs its reference clock to the other (slave) devices and fires the
+/// trigger which starts the streams on all of them. The devices need to be wired together accordingly: the clock output
+/// of the master to the clock input of every slave, and the trigger signal pins of all devices to each other.
+///
+/// All devices have to be of the same type, use [BladeRfAny] to mix different boards.
+///
+/// ```no_run
+/// use std::{sync::Arc, time::Duration};
+/// use bladerf::{BladeRfAny, ChannelLayoutRx, ComplexI16, RxChannel, StreamConfig, SyncCoordinator};
+/// let devices = vec![
+///     Arc::new(BladeRfAny::open_identifier("*:serial=a1b2").unwrap()),
+///     Arc::new(BladeRfAny::open_identifier("*:serial=c3d4").unwrap()),
+/// ];
+/// let coordinator = SyncCoordinator::new(devices, 0).unwrap();
+///
+/// let capture = coordinator
+///     .start_rx::<ComplexI16>(
+///         StreamConfig::default(),
+///         ChannelLayoutRx::SISO(RxChannel::Rx0),
+///         Duration::from_secs(1),
+///     )
+///     .unwrap();
+///
+/// // Both buffers start with a sample taken at the same instant
+/// let mut buffers = vec![vec![ComplexI16::ZERO; 4096]; 2];
+/// capture.read_at(1000, &mut buffers, Duration::from_secs(1)).unwrap();
+/// ```
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_r_i_g.html>
+#[derive(Debug)]
+pub struct SyncCoordinator<D: SyncDevice> {
+    devices: Vec<Arc<D>>,
+    master: usize,
+    signal: TriggerSignal,
+}
+
+impl<D: SyncDevice> SyncCoordinator<D> {
+    /// Creates a coordinator for `devices`, where `devices[master]` is the clock and trigger master.
+    ///
+    /// The trigger signal defaults to [TriggerSignal::MiniExp1], see [SyncCoordinator::with_signal()].
+    ///
+    /// # Errors
+    /// - `master` is not a valid index into `devices`.
+    /// - The same device is given more than once.
+    pub fn new(devices: Vec<Arc<D>>, master: usize) -> Result<Self> {
+        if master >= devices.len() {
+            return Err(Error::msg(format!(
+                "Master index {master} out of range for {} devices",
+                devices.len()
+            )));
+        }
+
+        let mut seen = BTreeSet::new();
+        if !devices.iter().all(|dev| seen.insert(dev.device_id())) {
+            return Err(Error::msg("The same device was given more than once"));
+        }
+
+        Ok(Self {
+            devices,
+            master,
+            signal: TriggerSignal::MiniExp1,
+        })
+    }
+
+    /// Selects the signal the trigger is shared on.
+    pub fn with_signal(mut self, signal: TriggerSignal) -> Self {
+        self.signal = signal;
+        self
+    }
+
+    /// The coordinated devices, in the order they were given.
+    pub fn devices(&self) -> &[Arc<D>] {
+        &self.devices
+    }
+
+    /// The clock and trigger master.
+    pub fn master(&self) -> &Arc<D> {
+        &self.devices[self.master]
+    }
+
+    /// Configures the master to output its reference clock and every slave to use it.
+    ///
+    /// This is done by [SyncCoordinator::start_rx()] as well, but doing it early gives the slaves time to settle.
+    pub fn configure_clocks(&self) -> Result<()> {
+        // The master goes first so the slaves have a reference to switch to
+        self.master().set_clock_role(ClockRole::Master)?;
+        for (_, dev) in self.slaves() {
+            dev.set_clock_role(ClockRole::Slave)?;
+        }
+        Ok(())
+    }
+
+    /// Configures the clocks, arms the triggers on all devices, fires the master and returns the started streams.
+    ///
+    /// The streams are always configured with [StreamConfig::with_metadata()] to establish the timestamp base of each
+    /// device, which consumes the first received sample of every device. See [AlignedRxStream] for how timestamps are mapped.
+    ///
+    /// The device timestamp counters are unrelated, so the first timestamps can not be compared directly. Instead, each
+    /// device's timestamp is read while armed, and the time from there to its first sample has to agree between all
+    /// devices. The tolerance is the time it took to read the armed timestamps plus two samples, so this catches a
+    /// device which started well before or after the others, but not an offset of a sample or two.
+    ///
+    /// `timeout` bounds how long to wait for the trigger to reach every device and for the first samples to arrive.
+    ///
+    /// # Errors
+    /// - Opening or enabling a stream fails, such as when a device already has an RX stream open.
+    /// - The trigger did not reach every device within `timeout`.
+    /// - A device delivered samples from before the trigger fired, which means its stream was not gated by the trigger.
+    /// - The first samples of the devices were not taken at the same instant, as described above.
+    pub fn start_rx<F: SampleFormat + Default>(
+        &self,
+        config: StreamConfig,
+        layout: ChannelLayoutRx,
+        timeout: Duration,
+    ) -> Result<SyncedRx<F, D>> {
+        self.configure_clocks()?;
+
+        let config = config.with_metadata(true);
+        let streams = self
+            .devices
+            .iter()
+            .map(|dev| D::sync_rx_streamer::<F>(dev.clone(), config, layout))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Slaves are armed before the master, so none of them can miss the trigger
+        let slaves = self
+            .slaves()
+            .map(|(_, dev)| TriggerSlave::new_arc(dev.clone(), Channel::Rx0, self.signal))
+            .collect::<Result<Vec<_>>>()?;
+        let master = TriggerMaster::new_arc(self.master().clone(), Channel::Rx0, self.signal)?;
+
+        let mut slave_triggers = slaves.iter();
+        for (i, stream) in streams.iter().enumerate() {
+            if i == self.master {
+                stream.enable_armed(&master)?;
+            } else {
+                // There is one slave trigger for every device besides the master, in the same order
+                stream.enable_armed(slave_triggers.next().unwrap())?;
+            }
+        }
+
+        let reading = Instant::now();
+        let armed_at = self
+            .devices
+            .iter()
+            .map(|dev| dev.get_timestamp(Direction::RX))
+            .collect::<Result<Vec<_>>>()?;
+        let read_spread = reading.elapsed();
+        let sample_rates = self
+            .devices
+            .iter()
+            .map(|dev| Ok(dev.get_rational_sample_rate(Channel::Rx0)?.as_f64()))
+            .collect::<Result<Vec<_>>>()?;
+
+        master.fire()?;
+
+        let deadline = Instant::now() + timeout;
+        for (i, slave) in self.slaves().map(|(i, _)| i).zip(&slaves) {
+            while !slave.state()?.fired {
+                if Instant::now() >= deadline {
+                    return Err(Error::msg(format!("Trigger did not reach device {i}")));
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        let mut first = [F::default()];
+        let mut delays = Vec::with_capacity(streams.len());
+        let streams = streams
+            .into_iter()
+            .zip(armed_at)
+            .enumerate()
+            .map(|(i, (stream, armed_at))| {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let meta = stream.read_with_meta(&mut first, None, remaining)?;
+                if meta.timestamp < armed_at {
+                    return Err(Error::msg(format!(
+                        "Device {i} delivered samples from before the trigger fired"
+                    )));
+                }
+                delays.push(meta.timestamp - armed_at);
+                Ok(AlignedRxStream {
+                    stream,
+                    base: meta.timestamp,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        check_common_start(&delays, &sample_rates, read_spread)?;
+
+        Ok(SyncedRx {
+            streams,
+            _master: master,
+            _slaves: slaves,
+        })
+    }
+
+    fn slaves(&self) -> impl Iterator<Item = (usize, &Arc<D>)> {
+        self.devices
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| *i != self.master)
+    }
+}
+
+/// Receive streams started at the same instant by a [SyncCoordinator].
+///
+/// The streams are in the same order as the devices given to the coordinator. The triggers stay configured for as long
+/// as this is alive and are disabled when it is dropped (or by [SyncedRx::into_streams()]), which does not affect the
+/// running streams.
+#[derive(Debug)]
+pub struct SyncedRx<F: SampleFormat, D: SyncDevice> {
+    streams: Vec<AlignedRxStream<F, D>>,
+    _master: TriggerMaster<Arc<D>, D>,
+    _slaves: Vec<TriggerSlave<Arc<D>, D>>,
+}
+
+impl<F: SampleFormat, D: SyncDevice> SyncedRx<F, D> {
+    /// The aligned streams, one for each device.
+    pub fn streams(&self) -> &[AlignedRxStream<F, D>] {
+        &self.streams
+    }
+
+    /// Reads samples starting at the same aligned `timestamp` from every device, one buffer per device.
+    ///
+    /// # Errors
+    /// - The number of buffers does not match the number of devices.
+    /// - Any of the reads fails, see [AlignedRxStream::read_at()].
+    pub fn read_at<B: AsMut<[F]>>(
+        &self,
+        timestamp: u64,
+        buffers: &mut [B],
+        timeout: Duration,
+    ) -> Result<Vec<Metadata>> {
+        if buffers.len() != self.streams.len() {
+            return Err(Error::msg(format!(
+                "Expected {} buffers, got {}",
+                self.streams.len(),
+                buffers.len()
+            )));
+        }
+        self.streams
+            .iter()
+            .zip(buffers)
+            .map(|(stream, buffer)| stream.read_at(timestamp, buffer.as_mut(), timeout))
+            .collect()
+    }
+
+    /// Releases the triggers and returns the aligned streams.
+    pub fn into_streams(self) -> Vec<AlignedRxStream<F, D>> {
+        self.streams
+    }
+}
+
+/// A receive stream with timestamps relative to the instant a [SyncCoordinator] started all devices.
+///
+/// The device timestamp counters are not related to each other, so every device gets its own timestamp base: the device
+/// timestamp of the first sample received after the trigger fired. Subtracting it gives the aligned timestamp, which
+/// refers to the same instant on every device. Aligned timestamp 0 is consumed while starting, so reading starts at 1.
+#[derive(Debug)]
+pub struct AlignedRxStream<F: SampleFormat, D: BladeRF> {
+    stream: RxSyncStream<Arc<D>, F, D>,
+    base: u64,
+}
+
+impl<F: SampleFormat, D: BladeRF> AlignedRxStream<F, D> {
+    /// Reads the next available samples, returning [Metadata] with the aligned timestamp of the first sample.
+    pub fn read(&self, buffer: &mut [F], timeout: Duration) -> Result<Metadata> {
+        let meta = self.stream.read_with_meta(buffer, None, timeout)?;
+        self.align(meta)
+    }
+
+    /// Reads samples starting at the given aligned timestamp, see [RxSyncStream::read_with_meta()].
+    pub fn read_at(&self, timestamp: u64, buffer: &mut [F], timeout: Duration) -> Result<Metadata> {
+        let meta = self
+            .stream
+            .read_with_meta(buffer, Some(self.base + timestamp), timeout)?;
+        self.align(meta)
+    }
+
+    /// The device timestamp corresponding to aligned timestamp 0.
+    pub fn timestamp_base(&self) -> u64 {
+        self.base
+    }
+
+    /// Gets the underlying stream, which uses device timestamps.
+    pub fn stream(&self) -> &RxSyncStream<Arc<D>, F, D> {
+        &self.stream
+    }
+
+    /// Returns the underlying stream, which uses device timestamps.
+    pub fn into_inner(self) -> RxSyncStream<Arc<D>, F, D> {
+        self.stream
+    }
+
+    fn align(&self, meta: Metadata) -> Result<Metadata> {
+        let timestamp = meta
+            .timestamp
+            .checked_sub(self.base)
+            .ok_or_else(|| Error::msg("Received samples from before the timestamp base"))?;
+        Ok(Metadata { timestamp, ..meta })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_start() {
+        let rates = [1e6, 2e6];
+        // 1000 us after arming on both
+        assert!(check_common_start(&[1000, 2000], &rates, Duration::ZERO).is_ok());
+        // 100 us apart, covered by the time reading the armed timestamps took
+        assert!(check_common_start(&[1000, 2200], &rates, Duration::from_micros(150)).is_ok());
+        assert!(check_common_start(&[1000, 2200], &rates, Duration::from_micros(50)).is_err());
+        assert!(check_common_start(&[], &[], Duration::ZERO).is_ok());
+    }
+}
@@ -0,0 +1,62 @@
+//! Calendar dates for the timestamps written to recordings, without pulling in a date library.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Formats `time` as an ISO 8601 UTC timestamp, as required for SigMF `core:datetime`.
+#[cfg(feature = "sigmf")]
+pub(crate) fn iso8601(time: SystemTime) -> String {
+    let (year, month, day, secs_of_day) = civil_datetime(time);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        time.duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_millis(),
+    )
+}
+
+/// Splits `time` into the UTC year, month, day and seconds into that day.
+pub(crate) fn civil_datetime(time: SystemTime) -> (i64, i64, i64, u64) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day, secs_of_day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_datetime(UNIX_EPOCH), (1970, 1, 1, 0));
+        let time = UNIX_EPOCH + Duration::from_secs(951_825_600);
+        assert_eq!(civil_datetime(time), (2000, 2, 29, 43_200));
+    }
+
+    #[test]
+    #[cfg(feature = "sigmf")]
+    fn datetime_formatting() {
+        assert_eq!(iso8601(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(iso8601(time), "2024-02-29T12:34:56.789Z");
+    }
+}
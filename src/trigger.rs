@@ -0,0 +1,253 @@
+//! Safe wrappers around the `libbladerf` trigger functions.
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::{BladeRF, Channel, Error, Result, Trigger, TriggerRole, TriggerSignal, TriggerState};
+
+/// Devices (by their pointer) that currently have a [TriggerMaster], to avoid configuring two masters on one device.
+static TRIGGER_MASTERS: Mutex<BTreeSet<usize>> = Mutex::new(BTreeSet::new());
+
+/// Common interface of [TriggerMaster] and [TriggerSlave], used to start streams gated by a trigger.
+pub trait ArmedTrigger {
+    /// The trigger configuration as initialized on the device.
+    fn trigger(&self) -> &Trigger;
+
+    /// Queries the current state of the trigger from the device.
+    fn state(&self) -> Result<TriggerState>;
+
+    /// Identifies the device the trigger is configured on, see [BladeRF::device_id()].
+    #[doc(hidden)]
+    fn device_id(&self) -> usize;
+}
+
+/// An armed trigger which determines when all devices in the trigger chain start streaming.
+///
+/// The trigger signal of the master is an output, so only one device in the chain may be the master.
+/// Creating a second master for the same device in this process fails. Devices in other processes can not be checked,
+/// so make sure to designate a single master when wiring several devices together.
+///
+/// The trigger is disarmed and its role is set back to [TriggerRole::Disabled] when dropped, releasing the trigger signal.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, Channel, ChannelLayoutRx, ComplexI16, RxChannel, StreamConfig, TriggerMaster, TriggerSignal};
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let trigger = TriggerMaster::new(&dev, Channel::Rx0, TriggerSignal::MiniExp1).unwrap();
+///
+/// let rx_stream = dev
+///     .rx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutRx::SISO(RxChannel::Rx0))
+///     .unwrap();
+/// // No samples are delivered until the trigger fires
+/// rx_stream.enable_armed(&trigger).unwrap();
+///
+/// trigger.fire().unwrap();
+/// let mut buffer = vec![ComplexI16::ZERO; 4096];
+/// rx_stream.read(&mut buffer, Duration::from_secs(1)).unwrap();
+/// ```
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_r_i_g.html>
+#[derive(Debug)]
+pub struct TriggerMaster<T: Borrow<D>, D: BladeRF> {
+    dev: T,
+    trigger: Trigger,
+    _devtype: PhantomData<D>,
+}
+
+impl<'a, D: BladeRF> TriggerMaster<&'a D, D> {
+    /// Initializes a trigger on `channel` using `signal`, configures the device as the master of the chain and arms it.
+    pub fn new(dev: &'a D, channel: Channel, signal: TriggerSignal) -> Result<Self> {
+        // Safety: trigger_init only fills out the default trigger configuration
+        let trigger = unsafe { dev.trigger_init(channel, signal) }?;
+        Self::arm(dev, trigger)
+    }
+
+    /// Configures the device as the master using an existing [Trigger] configuration and arms it.
+    ///
+    /// The [Trigger::role] is overridden with [TriggerRole::Master].
+    pub fn from_trigger(dev: &'a D, trigger: Trigger) -> Result<Self> {
+        Self::arm(dev, trigger)
+    }
+}
+
+impl<D: BladeRF> TriggerMaster<Arc<D>, D> {
+    /// Same as [TriggerMaster::new()], but for a device shared through an [Arc].
+    pub fn new_arc(dev: Arc<D>, channel: Channel, signal: TriggerSignal) -> Result<Self> {
+        // Safety: trigger_init only fills out the default trigger configuration
+        let trigger = unsafe { dev.trigger_init(channel, signal) }?;
+        Self::arm(dev, trigger)
+    }
+
+    /// Same as [TriggerMaster::from_trigger()], but for a device shared through an [Arc].
+    pub fn from_trigger_arc(dev: Arc<D>, trigger: Trigger) -> Result<Self> {
+        Self::arm(dev, trigger)
+    }
+}
+
+impl<T: Borrow<D>, D: BladeRF> TriggerMaster<T, D> {
+    fn arm(dev: T, trigger: Trigger) -> Result<Self> {
+        let trigger = Trigger {
+            role: TriggerRole::Master,
+            ..trigger
+        };
+
+        let device_id = dev.borrow().device_id();
+        if !TRIGGER_MASTERS.lock().insert(device_id) {
+            return Err(Error::msg("Device already has a trigger master"));
+        }
+
+        // From here on, drop takes care of disarming the trigger and releasing the master slot.
+        let master = Self {
+            dev,
+            trigger,
+            _devtype: PhantomData,
+        };
+        // Safety: This is the only master on this device
+        unsafe { master.dev.borrow().trigger_arm(&master.trigger, true) }?;
+        Ok(master)
+    }
+
+    /// Fires the trigger, starting the gated streams on all devices of the chain.
+    pub fn fire(&self) -> Result<()> {
+        // Safety: The trigger has the master role and is armed
+        unsafe { self.dev.borrow().trigger_fire(&self.trigger) }
+    }
+
+    /// Queries the current state of the trigger from the device.
+    pub fn state(&self) -> Result<TriggerState> {
+        // Safety: Only reads the trigger state
+        unsafe { self.dev.borrow().trigger_state(&self.trigger) }.map(Into::into)
+    }
+
+    /// The trigger configuration as initialized on the device.
+    pub fn trigger(&self) -> &Trigger {
+        &self.trigger
+    }
+}
+
+impl<T: Borrow<D>, D: BladeRF> ArmedTrigger for TriggerMaster<T, D> {
+    fn trigger(&self) -> &Trigger {
+        &self.trigger
+    }
+
+    fn state(&self) -> Result<TriggerState> {
+        TriggerMaster::state(self)
+    }
+
+    fn device_id(&self) -> usize {
+        self.dev.borrow().device_id()
+    }
+}
+
+impl<T: Borrow<D>, D: BladeRF> Drop for TriggerMaster<T, D> {
+    fn drop(&mut self) {
+        disable_trigger(self.dev.borrow(), &self.trigger);
+        let device_id = self.dev.borrow().device_id();
+        TRIGGER_MASTERS.lock().remove(&device_id);
+    }
+}
+
+/// An armed trigger which waits for the master of the trigger chain to fire.
+///
+/// The trigger is disarmed and its role is set back to [TriggerRole::Disabled] when dropped.
+///
+/// See [TriggerMaster] for an example.
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_r_i_g.html>
+#[derive(Debug)]
+pub struct TriggerSlave<T: Borrow<D>, D: BladeRF> {
+    dev: T,
+    trigger: Trigger,
+    _devtype: PhantomData<D>,
+}
+
+impl<'a, D: BladeRF> TriggerSlave<&'a D, D> {
+    /// Initializes a trigger on `channel` using `signal`, configures the device as a slave in the chain and arms it.
+    pub fn new(dev: &'a D, channel: Channel, signal: TriggerSignal) -> Result<Self> {
+        // Safety: trigger_init only fills out the default trigger configuration
+        let trigger = unsafe { dev.trigger_init(channel, signal) }?;
+        Self::arm(dev, trigger)
+    }
+
+    /// Configures the device as a slave using an existing [Trigger] configuration and arms it.
+    ///
+    /// The [Trigger::role] is overridden with [TriggerRole::Slave].
+    pub fn from_trigger(dev: &'a D, trigger: Trigger) -> Result<Self> {
+        Self::arm(dev, trigger)
+    }
+}
+
+impl<D: BladeRF> TriggerSlave<Arc<D>, D> {
+    /// Same as [TriggerSlave::new()], but for a device shared through an [Arc].
+    pub fn new_arc(dev: Arc<D>, channel: Channel, signal: TriggerSignal) -> Result<Self> {
+        // Safety: trigger_init only fills out the default trigger configuration
+        let trigger = unsafe { dev.trigger_init(channel, signal) }?;
+        Self::arm(dev, trigger)
+    }
+
+    /// Same as [TriggerSlave::from_trigger()], but for a device shared through an [Arc].
+    pub fn from_trigger_arc(dev: Arc<D>, trigger: Trigger) -> Result<Self> {
+        Self::arm(dev, trigger)
+    }
+}
+
+impl<T: Borrow<D>, D: BladeRF> TriggerSlave<T, D> {
+    fn arm(dev: T, trigger: Trigger) -> Result<Self> {
+        let slave = Self {
+            dev,
+            trigger: Trigger {
+                role: TriggerRole::Slave,
+                ..trigger
+            },
+            _devtype: PhantomData,
+        };
+        // Safety: The trigger signal is an input for slaves
+        unsafe { slave.dev.borrow().trigger_arm(&slave.trigger, true) }?;
+        Ok(slave)
+    }
+
+    /// Queries the current state of the trigger from the device.
+    pub fn state(&self) -> Result<TriggerState> {
+        // Safety: Only reads the trigger state
+        unsafe { self.dev.borrow().trigger_state(&self.trigger) }.map(Into::into)
+    }
+
+    /// The trigger configuration as initialized on the device.
+    pub fn trigger(&self) -> &Trigger {
+        &self.trigger
+    }
+}
+
+impl<T: Borrow<D>, D: BladeRF> ArmedTrigger for TriggerSlave<T, D> {
+    fn trigger(&self) -> &Trigger {
+        &self.trigger
+    }
+
+    fn state(&self) -> Result<TriggerState> {
+        TriggerSlave::state(self)
+    }
+
+    fn device_id(&self) -> usize {
+        self.dev.borrow().device_id()
+    }
+}
+
+impl<T: Borrow<D>, D: BladeRF> Drop for TriggerSlave<T, D> {
+    fn drop(&mut self) {
+        disable_trigger(self.dev.borrow(), &self.trigger);
+    }
+}
+
+/// Disarms the trigger and clears any fire request, ignoring errors.
+fn disable_trigger<D: BladeRF>(dev: &D, trigger: &Trigger) {
+    let disabled = Trigger {
+        role: TriggerRole::Disabled,
+        ..*trigger
+    };
+    // Safety: Disabling the trigger turns the trigger signal back into an input
+    let _ = unsafe { dev.trigger_arm(&disabled, false) };
+}
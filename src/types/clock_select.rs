@@ -0,0 +1,28 @@
+// Allow clippy::unnecessary_cast since the cast is needed for when bindgen runs on windows. The enum variants get cast to i32 on windows.
+#![allow(clippy::unnecessary_cast)]
+use strum::FromRepr;
+
+use crate::{sys::*, Error, Result};
+
+/// Selects the source of the 38.4 MHz reference clock of the bladeRF 2.0
+///
+/// Only for use with [BladeRf2][crate::BladeRf2] see [BladeRF::set_clock_select][crate::BladeRF::set_clock_select]
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___c_l_o_c_k___s_e_l_e_c_t.html>
+#[derive(Copy, Clone, Debug, FromRepr, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ClockSelect {
+    /// Use the onboard VCTCXO
+    Onboard = bladerf_clock_select_CLOCK_SELECT_ONBOARD as u32,
+    /// Use the clock provided on the CLKIN connector (J94)
+    External = bladerf_clock_select_CLOCK_SELECT_EXTERNAL as u32,
+}
+
+impl TryFrom<bladerf_clock_select> for ClockSelect {
+    type Error = Error;
+
+    fn try_from(value: bladerf_clock_select) -> Result<Self> {
+        Self::from_repr(value as u32)
+            .ok_or_else(|| Error::msg(format!("Invalid ClockSelect value: {value}")))
+    }
+}
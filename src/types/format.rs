@@ -30,13 +30,28 @@ pub enum Format {
     Sc16Q11 = bladerf_format_BLADERF_FORMAT_SC16_Q11 as u32,
     #[doc = "[`bladerf_format_BLADERF_FORMAT_SC8_Q7`]"]
     Sc8Q7 = bladerf_format_BLADERF_FORMAT_SC8_Q7 as u32,
-    // TODO: implement meta parsing
-    // #[doc = "[`bladerf_format_BLADERF_FORMAT_SC16_Q11_META`]"]
-    // Sc16Q11Meta = bladerf_format_BLADERF_FORMAT_SC16_Q11_META,
+    #[doc = "[`bladerf_format_BLADERF_FORMAT_SC16_Q11_META`]"]
+    Sc16Q11Meta = bladerf_format_BLADERF_FORMAT_SC16_Q11_META as u32,
+    #[doc = "[`bladerf_format_BLADERF_FORMAT_SC8_Q7_META`]"]
+    Sc8Q7Meta = bladerf_format_BLADERF_FORMAT_SC8_Q7_META as u32,
+    // TODO: implement packet parsing
     // #[doc = "[`bladerf_format_BLADERF_FORMAT_PACKET_META`]"]
     // PacketMeta = bladerf_format_BLADERF_FORMAT_PACKET_META,
-    // #[doc = "[`bladerf_format_BLADERF_FORMAT_SC8_Q7_META`]"]
-    // Sc8Q7Meta = bladerf_format_BLADERF_FORMAT_SC8_Q7_META,
+}
+
+impl Format {
+    /// Tests if samples in this format are accompanied by [Metadata](crate::Metadata)
+    pub fn is_meta(&self) -> bool {
+        matches!(self, Format::Sc16Q11Meta | Format::Sc8Q7Meta)
+    }
+
+    /// The variant of this format which is accompanied by [Metadata](crate::Metadata)
+    pub const fn with_meta(self) -> Format {
+        match self {
+            Format::Sc16Q11 | Format::Sc16Q11Meta => Format::Sc16Q11Meta,
+            Format::Sc8Q7 | Format::Sc8Q7Meta => Format::Sc8Q7Meta,
+        }
+    }
 }
 
 impl TryFrom<bladerf_format> for Format {
@@ -53,12 +68,50 @@ impl TryFrom<bladerf_format> for Format {
 /// # Safety
 /// `is_compatible` must only return true if it is valid to re-interpret bytes from the device as `Self`.
 ///
+/// `FORMAT` and `META_FORMAT` must describe the same sample layout, only differing in whether metadata is used.
+///
+/// If `CONVERTED` is set, `FORMAT` must be `Format::Sc16Q11` and `META_FORMAT` must be `Format::Sc16Q11Meta`.
+///
 /// Currently this is only implemented for:
-/// - `Format::Sc16Q11` => [ComplexI16]
-/// - `Format::Sc8Q7` => [ComplexI8]
-/// - `Format::Sc16Q11` => [ComplexI12]
+/// - `Format::Sc16Q11`/`Format::Sc16Q11Meta` => [ComplexI16]
+/// - `Format::Sc8Q7`/`Format::Sc8Q7Meta` => [ComplexI8]
+/// - `Format::Sc16Q11`/`Format::Sc16Q11Meta` => [ComplexI12]
+/// - `Format::Sc16Q11`/`Format::Sc16Q11Meta` => [Complex32], converted by the stream
+/// - [MimoFrame]s of each of the above
+///
+/// If `CHANNELS` is greater than one, a value must consist of `CHANNELS` samples of the format as laid out by the device.
 pub unsafe trait SampleFormat: Sized {
     const FORMAT: Format;
+    /// The variant of [SampleFormat::FORMAT] used when the stream is configured to exchange [Metadata](crate::Metadata).
+    ///
+    /// Defaults to [Format::with_meta()] of [SampleFormat::FORMAT], which fits every sample layout of the device.
+    const META_FORMAT: Format = Self::FORMAT.with_meta();
+
+    /// Number of interleaved channels a single value holds a sample of.
+    ///
+    /// This is 1 for plain samples, which can be used with any layout, and 2 for [MimoFrame]s, which can only be used
+    /// with the MIMO layouts.
+    const CHANNELS: usize = 1;
+
+    /// Set for types which are not exchanged with the device as is, but converted from and to [ComplexI16] by the
+    /// [RxSyncStream](crate::RxSyncStream) and [TxSyncStream](crate::TxSyncStream) using a conversion buffer.
+    ///
+    /// Such types can not be used with the async streams.
+    const CONVERTED: bool = false;
+
+    /// Converts samples received from the device, only called if [SampleFormat::CONVERTED] is set.
+    ///
+    /// `raw` holds [SampleFormat::CHANNELS] samples for each of `samples`.
+    fn from_ci16(_raw: &[ComplexI16], _samples: &mut [Self]) {
+        unreachable!("{} is not converted", std::any::type_name::<Self>())
+    }
+
+    /// Converts samples to be transmitted by the device, only called if [SampleFormat::CONVERTED] is set.
+    ///
+    /// `raw` holds [SampleFormat::CHANNELS] samples for each of `samples`.
+    fn to_ci16(_samples: &[Self], _raw: &mut [ComplexI16]) {
+        unreachable!("{} is not converted", std::any::type_name::<Self>())
+    }
 
     /// Returns true if this data type is commutable with the given format enum
     fn is_compatible(format: Format) -> bool;
@@ -75,12 +128,62 @@ pub unsafe trait SampleFormat: Sized {
     }
 }
 
+/// Checks that values of `F` can be exchanged with a stream of `channels` interleaved channels.
+pub(crate) fn check_channels<F: SampleFormat>(channels: usize) -> Result<()> {
+    if F::CHANNELS == 1 || F::CHANNELS == channels {
+        Ok(())
+    } else {
+        Err(Error::msg(format!(
+            "{} holds samples of {} channels, but the stream is configured for {channels}",
+            std::any::type_name::<F>(),
+            F::CHANNELS
+        )))
+    }
+}
+
+/// Allocates a buffer of `len` samples with all bits zero.
+pub(crate) fn zeroed_samples<F: SampleFormat>(len: usize) -> Vec<F> {
+    let mut samples = Vec::with_capacity(len);
+    // Safety: Sample formats are plain data, for which all zero bytes are a valid value
+    samples.resize_with(len, || unsafe { std::mem::zeroed() });
+    samples
+}
+
+/// Converts samples in the device format to `F`.
+///
+/// `src` needs to hold [SampleFormat::CHANNELS] samples for each value of `dst`.
+pub(crate) fn ci16_to_format<F: SampleFormat>(src: &[ComplexI16], dst: &mut [F]) {
+    assert_eq!(src.len(), dst.len() * F::CHANNELS);
+    if F::CONVERTED {
+        F::from_ci16(src, dst);
+        return;
+    }
+
+    // Safety: The SampleFormat contract guarantees that F consists of `F::CHANNELS` samples in the device format
+    match F::FORMAT {
+        Format::Sc8Q7 | Format::Sc8Q7Meta => {
+            let flat = unsafe {
+                std::slice::from_raw_parts_mut(dst.as_mut_ptr().cast::<ComplexI8>(), src.len())
+            };
+            for (d, s) in flat.iter_mut().zip(src) {
+                *d = ComplexI8::new((s.re >> 4) as i8, (s.im >> 4) as i8);
+            }
+        }
+        Format::Sc16Q11 | Format::Sc16Q11Meta => {
+            let flat = unsafe {
+                std::slice::from_raw_parts_mut(dst.as_mut_ptr().cast::<ComplexI16>(), src.len())
+            };
+            flat.copy_from_slice(src);
+        }
+    }
+}
+
 // Implementations for supported types
 unsafe impl SampleFormat for ComplexI16 {
     const FORMAT: Format = Format::Sc16Q11;
 
     fn is_compatible(format: Format) -> bool {
-        matches!(format, Format::Sc16Q11)
+        matches!(format, Format::Sc16Q11 | Format::Sc16Q11Meta)
     }
 }
 
@@ -88,7 +191,7 @@ unsafe impl SampleFormat for ComplexI8 {
     const FORMAT: Format = Format::Sc8Q7;
 
     fn is_compatible(format: Format) -> bool {
-        matches!(format, Format::Sc8Q7)
+        matches!(format, Format::Sc8Q7 | Format::Sc8Q7Meta)
     }
 }
 
@@ -96,7 +199,103 @@ unsafe impl SampleFormat for ComplexI12 {
     const FORMAT: Format = Format::Sc16Q11;
 
     fn is_compatible(format: Format) -> bool {
-        matches!(format, Format::Sc16Q11)
+        matches!(format, Format::Sc16Q11 | Format::Sc16Q11Meta)
+    }
+}
+
+/// Always exchanged with the device as [Format::Sc16Q11], for the full 12 bit resolution of the ADC/DAC.
+///
+/// Converting through [Format::Sc8Q7] to halve the USB bandwidth is not supported, use [ComplexI8] for that.
+unsafe impl SampleFormat for Complex32 {
+    const FORMAT: Format = Format::Sc16Q11;
+    const CONVERTED: bool = true;
+
+    fn is_compatible(format: Format) -> bool {
+        matches!(format, Format::Sc16Q11 | Format::Sc16Q11Meta)
+    }
+
+    fn from_ci16(raw: &[ComplexI16], samples: &mut [Self]) {
+        brf_ci16_to_cf32_slice(raw, samples);
+    }
+
+    fn to_ci16(samples: &[Self], raw: &mut [ComplexI16]) {
+        brf_cf32_to_ci16_slice(samples, raw);
+    }
+}
+
+/// One sample of each channel of a MIMO stream, in the order the device interleaves them (Rx0/Tx0 first).
+///
+/// Using frames as the [SampleFormat] of a stream with [ChannelLayoutRx::MIMO](crate::ChannelLayoutRx::MIMO) or
+/// [ChannelLayoutTx::MIMO](crate::ChannelLayoutTx::MIMO) keeps the channels of a sample together, so a buffer of `n`
+/// frames holds `n` samples of each channel.
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutRx, ComplexI16, MimoFrame, StreamConfig};
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let rx_stream = dev
+///     .rx_streamer::<MimoFrame<ComplexI16>>(StreamConfig::default(), ChannelLayoutRx::MIMO)
+///     .unwrap();
+/// rx_stream.enable().unwrap();
+///
+/// let mut frames = vec![[ComplexI16::ZERO; 2]; 4096];
+/// rx_stream.read(&mut frames, Duration::from_secs(1)).unwrap();
+/// let [rx0, rx1] = frames[0];
+/// ```
+pub type MimoFrame<S> = [S; 2];
+
+macro_rules! impl_mimo_frame {
+    ($($sample:ty),*) => {
+        $(
+            unsafe impl SampleFormat for MimoFrame<$sample> {
+                const FORMAT: Format = <$sample as SampleFormat>::FORMAT;
+                const CHANNELS: usize = 2;
+                const CONVERTED: bool = <$sample as SampleFormat>::CONVERTED;
+
+                fn from_ci16(raw: &[ComplexI16], samples: &mut [Self]) {
+                    <$sample as SampleFormat>::from_ci16(raw, samples.as_flattened_mut());
+                }
+
+                fn to_ci16(samples: &[Self], raw: &mut [ComplexI16]) {
+                    <$sample as SampleFormat>::to_ci16(samples.as_flattened(), raw);
+                }
+
+                fn is_compatible(format: Format) -> bool {
+                    <$sample as SampleFormat>::is_compatible(format)
+                }
+            }
+        )*
+    };
+}
+
+impl_mimo_frame!(ComplexI16, ComplexI8, ComplexI12, Complex32);
+
+/// Splits interleaved MIMO samples into the samples of each channel.
+///
+/// # Panics
+/// If `interleaved` does not hold exactly two samples for each sample of `ch0` and `ch1`.
+pub fn deinterleave_mimo<S: Copy>(interleaved: &[S], ch0: &mut [S], ch1: &mut [S]) {
+    assert!(
+        ch0.len() == ch1.len() && interleaved.len() == ch0.len() * 2,
+        "Interleaved and channel lengths differ"
+    );
+    for ((frame, s0), s1) in interleaved.chunks_exact(2).zip(ch0).zip(ch1) {
+        *s0 = frame[0];
+        *s1 = frame[1];
+    }
+}
+
+/// Interleaves the samples of two channels the way a MIMO stream exchanges them.
+///
+/// # Panics
+/// If `interleaved` does not hold exactly two samples for each sample of `ch0` and `ch1`.
+pub fn interleave_mimo<S: Copy>(ch0: &[S], ch1: &[S], interleaved: &mut [S]) {
+    assert!(
+        ch0.len() == ch1.len() && interleaved.len() == ch0.len() * 2,
+        "Interleaved and channel lengths differ"
+    );
+    for ((frame, s0), s1) in interleaved.chunks_exact_mut(2).zip(ch0).zip(ch1) {
+        frame[0] = *s0;
+        frame[1] = *s1;
     }
 }
 
@@ -158,6 +357,50 @@ pub fn brf_cf32_to_ci16(sample: Complex32) -> ComplexI16 {
     Complex::new(re, im)
 }
 
+/// Converts a slice of `Complex<i16>` into `Complex<f32>` the same way as [brf_ci16_to_cf32()].
+///
+/// The components are converted as flat slices, which lets the compiler vectorize the loop.
+///
+/// # Panics
+/// If `src` and `dst` have different lengths.
+pub fn brf_ci16_to_cf32_slice(src: &[ComplexI16], dst: &mut [Complex32]) {
+    assert_eq!(
+        src.len(),
+        dst.len(),
+        "Source and destination lengths differ"
+    );
+    // Safety: `Complex` is `repr(C)`, so a slice of them is a slice of twice as many components
+    let src = unsafe { std::slice::from_raw_parts(src.as_ptr().cast::<i16>(), src.len() * 2) };
+    let dst =
+        unsafe { std::slice::from_raw_parts_mut(dst.as_mut_ptr().cast::<f32>(), dst.len() * 2) };
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d = f32::from(*s) * (1.0 / BRF_CI16_SCALAR);
+    }
+}
+
+/// Converts a slice of `Complex<f32>` into `Complex<i16>` the same way as [brf_cf32_to_ci16()].
+///
+/// Unlike [brf_cf32_to_ci16()], values outside of [-1.0, 1.0) are clamped to [-2048, 2047] instead of wrapping around on the device.
+/// The components are converted as flat slices, which lets the compiler vectorize the loop.
+///
+/// # Panics
+/// If `src` and `dst` have different lengths.
+pub fn brf_cf32_to_ci16_slice(src: &[Complex32], dst: &mut [ComplexI16]) {
+    assert_eq!(
+        src.len(),
+        dst.len(),
+        "Source and destination lengths differ"
+    );
+    // Safety: `Complex` is `repr(C)`, so a slice of them is a slice of twice as many components
+    let src = unsafe { std::slice::from_raw_parts(src.as_ptr().cast::<f32>(), src.len() * 2) };
+    let dst =
+        unsafe { std::slice::from_raw_parts_mut(dst.as_mut_ptr().cast::<i16>(), dst.len() * 2) };
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d = (*s * BRF_CI16_SCALAR).clamp(BRF_CI16_SAMPLE_MIN as f32, BRF_CI16_SAMPLE_MAX as f32)
+            as i16;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,4 +437,53 @@ mod tests {
         let y = brf_cf32_to_ci16(x);
         assert_eq!(y, ComplexI16::new(-2048, 1024));
     }
+
+    #[test]
+    fn slice_conversions() {
+        let raw: Vec<_> = (-2048..2048)
+            .step_by(7)
+            .map(|i| ComplexI16::new(i, -i.max(-2047)))
+            .collect();
+        let mut samples = vec![Complex32::default(); raw.len()];
+        brf_ci16_to_cf32_slice(&raw, &mut samples);
+        for (x, y) in raw.iter().zip(&samples) {
+            assert_eq!(*y, brf_ci16_to_cf32(*x));
+        }
+
+        let mut round_trip = vec![ComplexI16::ZERO; raw.len()];
+        brf_cf32_to_ci16_slice(&samples, &mut round_trip);
+        assert_eq!(round_trip, raw);
+
+        // Out of range values are clamped
+        brf_cf32_to_ci16_slice(&[Complex32::new(1.5, -1.5)], &mut round_trip[..1]);
+        assert_eq!(
+            round_trip[0],
+            ComplexI16::new(BRF_CI16_SAMPLE_MAX, BRF_CI16_SAMPLE_MIN)
+        );
+    }
+
+    #[test]
+    fn mimo_interleaving() {
+        let ch0: Vec<_> = (0..8).map(|i| ComplexI16::new(i, 0)).collect();
+        let ch1: Vec<_> = (0..8).map(|i| ComplexI16::new(0, i)).collect();
+        let mut interleaved = vec![ComplexI16::ZERO; 16];
+        interleave_mimo(&ch0, &ch1, &mut interleaved);
+        assert_eq!(interleaved[2], ch0[1]);
+        assert_eq!(interleaved[3], ch1[1]);
+
+        let mut split = (vec![ComplexI16::ZERO; 8], vec![ComplexI16::ZERO; 8]);
+        deinterleave_mimo(&interleaved, &mut split.0, &mut split.1);
+        assert_eq!(split, (ch0, ch1));
+
+        // Frames convert the same way as the flat samples
+        let frames = [[ComplexI16::new(-2048, 0), ComplexI16::new(0, 1024)]; 4];
+        let mut converted = [[Complex32::default(); 2]; 4];
+        <MimoFrame<Complex32>>::from_ci16(frames.as_flattened(), &mut converted);
+        assert_eq!(
+            converted[3],
+            [Complex32::new(-1.0, 0.0), Complex32::new(0.0, 0.5)]
+        );
+        assert!(check_channels::<MimoFrame<Complex32>>(1).is_err());
+        assert!(check_channels::<ComplexI16>(2).is_ok());
+    }
 }
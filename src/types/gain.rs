@@ -11,6 +11,11 @@ pub type Gain = i32;
 ///
 /// Relevant `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___g_a_i_n.html#gae7632e9f6b3a5a182ef012c214be0f78>
 #[derive(Copy, Clone, Debug, FromRepr, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 #[repr(i32)]
 pub enum GainMode {
     /// Device-specific default (automatic, when available)
@@ -46,6 +46,15 @@ impl ChannelLayout {
     pub fn is_mimo(&self) -> bool {
         matches!(self, ChannelLayout::RxMIMO | ChannelLayout::TxMIMO)
     }
+
+    /// Number of channels whose samples are interleaved in a stream with this layout
+    pub fn num_channels(&self) -> usize {
+        if self.is_mimo() {
+            2
+        } else {
+            1
+        }
+    }
 }
 
 impl TryFrom<bladerf_channel_layout> for ChannelLayout {
@@ -76,6 +85,11 @@ impl ChannelLayoutRx {
         matches!(self, Self::MIMO)
     }
 
+    /// Number of channels whose samples are interleaved in a stream with this layout
+    pub fn num_channels(&self) -> usize {
+        ChannelLayout::from(*self).num_channels()
+    }
+
     /// Tests if the layout is a SISO configuration
     pub fn is_siso(&self) -> bool {
         matches!(self, Self::SISO(_))
@@ -110,6 +124,11 @@ impl ChannelLayoutTx {
         matches!(self, Self::MIMO)
     }
 
+    /// Number of channels whose samples are interleaved in a stream with this layout
+    pub fn num_channels(&self) -> usize {
+        ChannelLayout::from(*self).num_channels()
+    }
+
     /// Tests if the layout is a SISO configuration
     pub fn is_siso(&self) -> bool {
         matches!(self, Self::SISO(_))
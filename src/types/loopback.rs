@@ -9,6 +9,11 @@ use strum::FromRepr;
 ///
 /// Relevant `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___l_o_o_p_b_a_c_k.html#ga29a7d4f25eec5aab329cdae462f8ba47>
 #[derive(Copy, Clone, Debug, FromRepr, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 #[repr(u32)]
 pub enum Loopback {
     /// Disables loopback and returns to normal operation.
@@ -1,11 +1,31 @@
 use crate::sys::*;
 
-/// Additional types for Metadata
-#[derive(Clone, Debug)]
+/// Metadata exchanged with `libbladerf` when streaming with one of the metadata [Format](crate::Format)s.
+///
+/// On reception it describes the samples that were read, on transmission it controls when and how the samples are sent.
+///
+/// More fields may be added, so create it with [Metadata::rx_now()], [Metadata::rx_at()] or [Default] and set the
+/// fields from there:
+///
+/// ```
+/// use bladerf::Metadata;
+///
+/// let mut meta = Metadata::default();
+/// meta.timestamp = 1_000_000;
+/// ```
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/structbladerf__metadata.html>
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Metadata {
+    /// Device timestamp (in samples) of the first sample in the buffer.
     pub timestamp: u64,
+    /// Raw `BLADERF_META_FLAG_*` bits.
     pub flags: u32,
-    // Add other fields as necessary
+    /// Status reported by `libbladerf` once the operation completed.
+    pub status: MetadataStatus,
+    /// Number of samples actually read or written, which can be less than requested when the stream overran.
+    pub actual_count: u32,
 }
 
 impl Default for Metadata {
@@ -15,12 +35,40 @@ impl Default for Metadata {
 }
 
 impl Metadata {
+    /// Creates empty [Metadata] with all fields zeroed.
     pub fn new() -> Self {
         Self {
             timestamp: 0,
             flags: 0,
+            status: MetadataStatus::default(),
+            actual_count: 0,
+        }
+    }
+
+    /// Metadata requesting a read of the next available samples, regardless of their timestamp.
+    ///
+    /// This sets `BLADERF_META_FLAG_RX_NOW`.
+    pub fn rx_now() -> Self {
+        Self {
+            flags: BLADERF_META_FLAG_RX_NOW,
+            ..Self::new()
         }
     }
+
+    /// Metadata requesting a read starting at the given device timestamp.
+    ///
+    /// Samples before the timestamp are discarded, reading at a timestamp that has already passed fails with [Error::TimePast](crate::Error::TimePast).
+    pub fn rx_at(timestamp: u64) -> Self {
+        Self {
+            timestamp,
+            ..Self::new()
+        }
+    }
+
+    /// True if the hardware reported an RX underflow (`BLADERF_META_FLAG_RX_HW_UNDERFLOW`) for the samples read.
+    pub fn rx_hw_underflow(&self) -> bool {
+        self.flags & BLADERF_META_FLAG_RX_HW_UNDERFLOW != 0
+    }
 }
 
 impl From<&bladerf_metadata> for Metadata {
@@ -28,6 +76,8 @@ impl From<&bladerf_metadata> for Metadata {
         Self {
             timestamp: meta.timestamp,
             flags: meta.flags,
+            status: MetadataStatus::from_bits(meta.status),
+            actual_count: meta.actual_count,
         }
     }
 }
@@ -37,9 +87,82 @@ impl From<&Metadata> for bladerf_metadata {
         bladerf_metadata {
             timestamp: val.timestamp,
             flags: val.flags,
-            status: 0,
-            actual_count: 0,
+            status: val.status.to_bits(),
+            actual_count: val.actual_count,
             reserved: [0u8; 32],
         }
     }
 }
+
+/// Decoded `BLADERF_META_STATUS_*` bits of a [Metadata].
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___s_t_r_e_a_m_i_n_g___f_o_r_m_a_t.html>
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetadataStatus {
+    /// Samples were dropped by the device or host before they could be read (`BLADERF_META_STATUS_OVERRUN`).
+    pub overrun: bool,
+    /// The device ran out of samples to transmit (`BLADERF_META_STATUS_UNDERRUN`).
+    ///
+    /// Reserved for future use by `libbladerf`, which does not set it yet.
+    pub underrun: bool,
+}
+
+impl MetadataStatus {
+    /// Decodes the raw status bits, ignoring any unknown bits.
+    pub fn from_bits(status: u32) -> Self {
+        Self {
+            overrun: status & BLADERF_META_STATUS_OVERRUN != 0,
+            underrun: status & BLADERF_META_STATUS_UNDERRUN != 0,
+        }
+    }
+
+    /// Encodes the status back into the raw status bits.
+    pub fn to_bits(self) -> u32 {
+        let mut status = 0;
+        if self.overrun {
+            status |= BLADERF_META_STATUS_OVERRUN;
+        }
+        if self.underrun {
+            status |= BLADERF_META_STATUS_UNDERRUN;
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_bits_round_trip() {
+        let status = MetadataStatus::from_bits(BLADERF_META_STATUS_OVERRUN);
+        assert!(status.overrun);
+        assert!(!status.underrun);
+        assert_eq!(status.to_bits(), BLADERF_META_STATUS_OVERRUN);
+
+        let status = MetadataStatus::from_bits(u32::MAX);
+        assert_eq!(
+            status,
+            MetadataStatus {
+                overrun: true,
+                underrun: true
+            }
+        );
+    }
+
+    #[test]
+    fn ffi_conversion() {
+        let meta = Metadata {
+            timestamp: 123_456,
+            flags: BLADERF_META_FLAG_RX_NOW,
+            status: MetadataStatus {
+                overrun: true,
+                underrun: false,
+            },
+            actual_count: 4096,
+        };
+        let ffi = bladerf_metadata::from(&meta);
+        assert_eq!(ffi.status, BLADERF_META_STATUS_OVERRUN);
+        assert_eq!(Metadata::from(&ffi), meta);
+    }
+}
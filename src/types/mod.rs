@@ -55,6 +55,9 @@ pub use gain::*;
 mod range;
 pub use range::*;
 
+mod units;
+pub use units::*;
+
 mod correction;
 pub use correction::*;
 
@@ -67,6 +70,9 @@ pub use layout::*;
 mod smb_mode;
 pub use smb_mode::*;
 
+mod clock_select;
+pub use clock_select::*;
+
 mod expansion_module;
 pub use expansion_module::*;
 
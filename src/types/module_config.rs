@@ -1,9 +1,38 @@
+use super::{Bandwidth, Db, Hz, SampleRate};
+
 /// BladeRF module config object
 #[derive(Clone, Debug)]
 pub struct ModuleConfig {
+    /// Frequency in Hz
     pub frequency: u64,
+    /// Sample rate in samples per second
     pub sample_rate: u32,
+    /// Bandwidth in Hz
     pub bandwidth: u32,
     /// Set overall system gain
     pub gain: i32,
 }
+
+impl ModuleConfig {
+    /// Creates a config from typed units, which keeps a sample rate from being passed as the bandwidth.
+    ///
+    /// ```
+    /// use bladerf::{kHz, Db, MHz, ModuleConfig, SampleRate};
+    ///
+    /// let config = ModuleConfig::new(MHz(915.0), SampleRate(2_000_000), kHz(1_500.0), Db(30));
+    /// assert_eq!(config.bandwidth, 1_500_000);
+    /// ```
+    pub fn new(
+        frequency: impl Into<Hz>,
+        sample_rate: impl Into<SampleRate>,
+        bandwidth: impl Into<Bandwidth>,
+        gain: impl Into<Db>,
+    ) -> Self {
+        Self {
+            frequency: frequency.into().0,
+            sample_rate: sample_rate.into().0,
+            bandwidth: bandwidth.into().0,
+            gain: gain.into().0,
+        }
+    }
+}
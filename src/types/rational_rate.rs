@@ -1,11 +1,31 @@
-use crate::sys::*;
+use std::cmp::Ordering;
+
+use crate::{sys::*, Error, Result};
 
 /// Rational sample rate representation
 ///
 /// `rate = integer + (num/den)`
 ///
+/// Rates compare by value, so `1 + 2/4` equals `1 + 1/2`. An invalid zero `den` compares as if there was no fractional
+/// part.
+///
+/// [RationalRate::best_fit()] finds the closest rate libbladeRF accepts for an exact target, such as the 23.04 MHz of
+/// 30.72 MHz * 3/4 or the 64/7 MHz of DVB-T:
+///
+/// ```
+/// use bladerf::RationalRate;
+///
+/// let rate = RationalRate::best_fit(64_000_000, 7).unwrap();
+/// assert_eq!(rate, RationalRate::new(9_142_857, 1, 7).unwrap());
+/// ```
+///
 /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/structbladerf__rational__rate.html>
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
 pub struct RationalRate {
     /// Integer portion
     pub integer: u64,
@@ -15,6 +35,134 @@ pub struct RationalRate {
     pub den: u64,
 }
 
+impl RationalRate {
+    /// Largest denominator [RationalRate::best_fit()] and [RationalRate::from_f64()] produce.
+    ///
+    /// The fractional part of a rate ends up in the 30 bit denominator of the Si5338 multisynth of the bladeRF 1.
+    pub const MAX_DEN: u64 = (1 << 30) - 1;
+
+    /// Creates the rate `integer + num/den` in its [normalized][RationalRate::normalize()] form.
+    ///
+    /// # Errors
+    /// [Error::Inval] if `den` is zero.
+    pub fn new(integer: u64, num: u64, den: u64) -> Result<Self> {
+        if den == 0 {
+            return Err(Error::Inval);
+        }
+        Ok(Self { integer, num, den }.normalize())
+    }
+
+    /// Moves whole Hz of the fraction into `integer` and reduces `num/den` to lowest terms.
+    ///
+    /// A rate without a fractional part has a `den` of 1. A zero `den` is left as is.
+    pub fn normalize(self) -> Self {
+        if self.den == 0 {
+            return self;
+        }
+        let divisor = gcd(self.num % self.den, self.den);
+        Self {
+            integer: self.integer.saturating_add(self.num / self.den),
+            num: self.num % self.den / divisor,
+            den: self.den / divisor,
+        }
+    }
+
+    /// The rate in Hz, rounded to the nearest [f64].
+    pub fn as_f64(&self) -> f64 {
+        let fraction = if self.den == 0 {
+            0.0
+        } else {
+            self.num as f64 / self.den as f64
+        };
+        self.integer as f64 + fraction
+    }
+
+    /// Finds the simplest rate which is `value` Hz as an [f64], with a denominator of at most [RationalRate::MAX_DEN].
+    ///
+    /// An [f64] can not hold most fractions exactly, so this picks the fraction with the smallest denominator that
+    /// converts back to `value`, turning `10e6 / 3.0` into `3333333 1/3`. If there is none within the limit, the
+    /// closest fraction is used. Targets which are known as a fraction are better served by [RationalRate::best_fit()].
+    ///
+    /// # Errors
+    /// [Error::Range] if `value` is negative, not finite or too large for a [u64].
+    pub fn from_f64(value: f64) -> Result<Self> {
+        if !(0.0..u64::MAX as f64).contains(&value) {
+            return Err(Error::Range);
+        }
+        const DEN: u64 = 1 << 52;
+        let integer = value.trunc() as u64;
+        // Values of 1 Hz and more have at most 52 bits of fraction, and below 1 Hz further bits are not worth keeping
+        let fraction = (value.fract() * DEN as f64).round() as u64;
+        let (num, den) = convergents(fraction, DEN)
+            .take_while(|&(_, den)| den <= Self::MAX_DEN)
+            .find(|&(num, den)| integer as f64 + num as f64 / den as f64 == value)
+            .unwrap_or_else(|| best_fraction(fraction, DEN, Self::MAX_DEN));
+        Ok(Self { integer, num, den }.normalize())
+    }
+
+    /// Finds the closest rate to `num/den` Hz with a denominator of at most [RationalRate::MAX_DEN].
+    ///
+    /// The fractional part is approximated with the convergents and semiconvergents of its continued fraction, which
+    /// gives the best approximation for any limit on the denominator. Targets whose reduced denominator is within the
+    /// limit are met exactly.
+    ///
+    /// ```
+    /// use bladerf::RationalRate;
+    ///
+    /// // LTE 20 MHz at 3/4 of the usual 30.72 MHz
+    /// let rate = RationalRate::best_fit(30_720_000 * 3, 4).unwrap();
+    /// assert_eq!(rate, RationalRate::new(23_040_000, 0, 1).unwrap());
+    /// ```
+    ///
+    /// # Errors
+    /// [Error::Inval] if `den` is zero.
+    pub fn best_fit(num: u64, den: u64) -> Result<Self> {
+        if den == 0 {
+            return Err(Error::Inval);
+        }
+        let (fraction_num, fraction_den) = best_fraction(num % den, den, Self::MAX_DEN);
+        Ok(Self {
+            integer: num / den,
+            num: fraction_num,
+            den: fraction_den,
+        }
+        .normalize())
+    }
+
+    /// The fractional part in lowest terms, with a zero `den` counting as no fractional part.
+    fn fraction(&self) -> (u128, u128) {
+        match self.normalize() {
+            Self { den: 0, .. } => (0, 1),
+            rate => (rate.num.into(), rate.den.into()),
+        }
+    }
+}
+
+impl PartialEq for RationalRate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RationalRate {}
+
+impl PartialOrd for RationalRate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RationalRate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (num, den) = self.fraction();
+        let (other_num, other_den) = other.fraction();
+        self.normalize()
+            .integer
+            .cmp(&other.normalize().integer)
+            .then((num * other_den).cmp(&(other_num * den)))
+    }
+}
+
 impl From<bladerf_rational_rate> for RationalRate {
     fn from(rate: bladerf_rational_rate) -> Self {
         Self {
@@ -34,3 +182,145 @@ impl From<RationalRate> for bladerf_rational_rate {
         }
     }
 }
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Convergents of the continued fraction of `num/den`, which must be below 1, ending with `num/den` in lowest terms.
+fn convergents(num: u64, den: u64) -> impl Iterator<Item = (u64, u64)> {
+    let (mut h0, mut k0, mut h1, mut k1) = (0u64, 1u64, 1u64, 0u64);
+    let (mut p, mut q) = (num, den);
+    std::iter::from_fn(move || {
+        if q == 0 {
+            return None;
+        }
+        let a = p / q;
+        // Denominators grow at least like the Fibonacci numbers, saturating only far beyond any limit in use
+        (h0, k0, h1, k1) = (
+            h1,
+            k1,
+            a.saturating_mul(h1).saturating_add(h0),
+            a.saturating_mul(k1).saturating_add(k0),
+        );
+        (p, q) = (q, p - a * q);
+        Some((h1, k1))
+    })
+}
+
+/// Closest fraction to `num/den`, which must be below 1, with a denominator of at most `max_den`.
+///
+/// Walks the continued fraction of `num/den` until the next convergent's denominator exceeds `max_den`. The best
+/// approximation is then either the last convergent or the largest semiconvergent within the limit.
+fn best_fraction(num: u64, den: u64, max_den: u64) -> (u64, u64) {
+    let (num, den, max_den) = (u128::from(num), u128::from(den), u128::from(max_den));
+    // Convergents h/k before the last and last one
+    let (mut h0, mut k0, mut h1, mut k1) = (0u128, 1u128, 1u128, 0u128);
+    let (mut p, mut q) = (num, den);
+    while q != 0 {
+        let a = p / q;
+        let k2 = a * k1 + k0;
+        if k2 > max_den {
+            // Largest semiconvergent within the limit, compared to the last convergent exactly via
+            // |num/den - h/k| = |num * k - h * den| / (den * k)
+            let t = (max_den - k0) / k1;
+            let (hs, ks) = (t * h1 + h0, t * k1 + k0);
+            let error = |h: u128, k: u128| (num * k).abs_diff(h * den);
+            if error(hs, ks) * k1 < error(h1, k1) * ks {
+                (h1, k1) = (hs, ks);
+            }
+            break;
+        }
+        (h0, k0, h1, k1) = (h1, k1, a * h1 + h0, k2);
+        (p, q) = (q, p - a * q);
+    }
+    // Both are bounded by max_den, since h/k <= 1
+    (h1 as u64, k1 as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        let rate = RationalRate {
+            integer: 1,
+            num: 10,
+            den: 4,
+        };
+        let normalized = rate.normalize();
+        assert_eq!(
+            (normalized.integer, normalized.num, normalized.den),
+            (3, 1, 2)
+        );
+        let whole = RationalRate::new(5, 3, 3).unwrap();
+        assert_eq!((whole.integer, whole.num, whole.den), (6, 0, 1));
+        assert_eq!(RationalRate::new(5, 3, 0), Err(Error::Inval));
+    }
+
+    #[test]
+    fn compare() {
+        let half = RationalRate::new(1, 1, 2).unwrap();
+        let unreduced = RationalRate {
+            integer: 0,
+            num: 6,
+            den: 4,
+        };
+        assert_eq!(half, unreduced);
+        assert!(RationalRate::new(1, 1, 3).unwrap() < half);
+        assert!(RationalRate::new(1, 2, 3).unwrap() > half);
+        assert!(RationalRate::new(2, 0, 1).unwrap() > half);
+        // Fractions this close do not differ as f64
+        let a = RationalRate::new(1, u64::MAX - 1, u64::MAX).unwrap();
+        let b = RationalRate::new(1, u64::MAX - 2, u64::MAX - 1).unwrap();
+        assert_eq!(a.as_f64(), b.as_f64());
+        assert!(a > b);
+    }
+
+    #[test]
+    fn best_fraction_limits() {
+        // pi - 3 approximated by 1/7, 15/106, 16/113
+        let (num, den) = (141_592_653_589_793, 1_000_000_000_000_000);
+        assert_eq!(best_fraction(num, den, 7), (1, 7));
+        assert_eq!(best_fraction(num, den, 112), (15, 106));
+        assert_eq!(best_fraction(num, den, 113), (16, 113));
+        assert_eq!(best_fraction(0, 5, 100), (0, 1));
+        assert_eq!(best_fraction(3, 9, 100), (1, 3));
+        // Rounding up to a whole Hz
+        assert_eq!(best_fraction(999, 1000, 10), (1, 1));
+    }
+
+    #[test]
+    fn best_fit() {
+        let rate = RationalRate::best_fit(30_720_000 * 3, 4).unwrap();
+        assert_eq!((rate.integer, rate.num, rate.den), (23_040_000, 0, 1));
+
+        // A denominator beyond the limit is approximated
+        let den = RationalRate::MAX_DEN + 2;
+        let rate = RationalRate::best_fit(2_000_000 * den + 1, den).unwrap();
+        assert!(rate.den <= RationalRate::MAX_DEN);
+        assert_eq!(rate.integer, 2_000_000);
+        assert!((rate.as_f64() - (2e6 + 1.0 / den as f64)).abs() < 1e-12);
+
+        let rate = RationalRate::best_fit(u64::MAX, 1).unwrap();
+        assert_eq!(rate.integer, u64::MAX);
+        assert_eq!(RationalRate::best_fit(1, 0), Err(Error::Inval));
+    }
+
+    #[test]
+    fn from_f64() {
+        let rate = RationalRate::from_f64(10e6 / 3.0).unwrap();
+        assert_eq!(rate, RationalRate::new(3_333_333, 1, 3).unwrap());
+        assert_eq!(rate.as_f64(), 10e6 / 3.0);
+        assert_eq!(
+            RationalRate::from_f64(520_833.25).unwrap(),
+            RationalRate::new(520_833, 1, 4).unwrap()
+        );
+        assert_eq!(RationalRate::from_f64(-1.0), Err(Error::Range));
+        assert_eq!(RationalRate::from_f64(f64::NAN), Err(Error::Range));
+    }
+}
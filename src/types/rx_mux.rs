@@ -9,6 +9,11 @@ use crate::{sys::*, Error, Result};
 ///
 /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___r_e_c_e_i_v_e___m_u_x.html#gae7706e9b73a8ba4e9d6eaa74018aa114>
 #[derive(Copy, Clone, Debug, FromRepr, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 #[repr(i32)]
 pub enum RxMux {
     /// An invalid rx mux mode selection
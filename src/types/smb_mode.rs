@@ -19,6 +19,11 @@ pub const SMB_FREQUENCY_MIN: u32 = BLADERF_SMB_FREQUENCY_MIN;
 ///
 /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___s_m_b___c_l_o_c_k.html#gad289c8e261a1f7342e9280f22a844563>
 #[derive(Copy, Clone, Debug, FromRepr, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 #[repr(i32)]
 pub enum SmbMode {
     /// Invalid selection
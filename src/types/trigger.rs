@@ -75,6 +75,7 @@ impl TryFrom<bladerf_trigger_signal> for TriggerSignal {
 /// Trigger configuration
 ///
 /// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/structbladerf__trigger.html>
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Trigger {
     /// The channel associated with this trigger
     pub channel: Channel,
@@ -98,3 +99,37 @@ impl TryFrom<bladerf_trigger> for Trigger {
         })
     }
 }
+
+impl From<&Trigger> for bladerf_trigger {
+    fn from(t: &Trigger) -> Self {
+        bladerf_trigger {
+            channel: t.channel as bladerf_channel,
+            role: t.role as bladerf_trigger_role,
+            signal: t.signal as bladerf_trigger_signal,
+            options: t.options,
+        }
+    }
+}
+
+/// State of a trigger as reported by the device
+///
+/// Related `libbladerf` docs: <https://www.nuand.com/libbladeRF-doc/v2.5.0/group___f_n___t_r_i_g.html#ga63c07df2a4c7a533824d0faaeedc3a1a>
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TriggerState {
+    /// The trigger is armed and waiting to fire.
+    pub armed: bool,
+    /// The trigger has fired.
+    pub fired: bool,
+    /// A fire request has been made on the master and is waiting to be serviced.
+    pub fire_requested: bool,
+}
+
+impl From<(bool, bool, bool)> for TriggerState {
+    fn from((armed, fired, fire_requested): (bool, bool, bool)) -> Self {
+        Self {
+            armed,
+            fired,
+            fire_requested,
+        }
+    }
+}
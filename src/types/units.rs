@@ -0,0 +1,277 @@
+use std::fmt;
+
+use super::Range;
+
+/// Frequency, in hertz (Hz)
+///
+/// Accepted by [BladeRF::set_frequency()](crate::BladeRF::set_frequency()), the other tuning functions, the
+/// [FrequencyHopper](crate::FrequencyHopper) and the sweeper, which also take a bare `u64` in Hz. The getters still
+/// return bare integers so existing callers keep working, wrap their result to use the units, e.g.
+/// `Hz(device.get_frequency(channel)?)`.
+///
+/// The unit constructors [kHz()], [MHz()] and [GHz()] round to the nearest Hz:
+///
+/// ```
+/// use bladerf::{Hz, MHz};
+///
+/// assert_eq!(MHz(433.92), Hz(433_920_000));
+/// assert_eq!(MHz(433.92).to_string(), "433.92 MHz");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Hz(pub u64);
+
+/// Sample rate, in samples per second
+///
+/// Accepted by [BladeRF::set_sample_rate()](crate::BladeRF::set_sample_rate()), which also takes a bare `u32`. A
+/// [Hz] does not convert into a sample rate, so a frequency can not be passed by mistake.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct SampleRate(pub u32);
+
+/// Analog filter bandwidth, in hertz (Hz)
+///
+/// Accepted by [BladeRF::set_bandwidth()](crate::BladeRF::set_bandwidth()), which also takes a bare `u32`. A [Hz]
+/// converts into a bandwidth, but a [SampleRate] does not, so the two can not be mixed up.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Bandwidth(pub u32);
+
+/// Gain, in decibels (dB)
+///
+/// Accepted by [BladeRF::set_gain()](crate::BladeRF::set_gain()) and
+/// [BladeRF::set_gain_stage()](crate::BladeRF::set_gain_stage()), which also take a bare [Gain](crate::Gain).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Db(pub i32);
+
+/// Frequency of `value` kilohertz, rounded to the nearest Hz
+#[allow(non_snake_case)]
+pub fn kHz(value: f64) -> Hz {
+    Hz::from_f64(value * 1e3)
+}
+
+/// Frequency of `value` megahertz, rounded to the nearest Hz
+#[allow(non_snake_case)]
+pub fn MHz(value: f64) -> Hz {
+    Hz::from_f64(value * 1e6)
+}
+
+/// Frequency of `value` gigahertz, rounded to the nearest Hz
+#[allow(non_snake_case)]
+pub fn GHz(value: f64) -> Hz {
+    Hz::from_f64(value * 1e9)
+}
+
+/// A quantity which can be limited to the [Range] reported by the device, see [Range::clamp()] and [Range::round()].
+pub trait RangeUnit: Copy {
+    /// The value in the units of the [Range], such as Hz or dB.
+    fn to_f64(self) -> f64;
+
+    /// Creates a value from the units of the [Range], rounding to the nearest representable value.
+    fn from_f64(value: f64) -> Self;
+}
+
+macro_rules! unit {
+    ($unit:ident, $inner:ty, $suffix:literal) => {
+        impl RangeUnit for $unit {
+            fn to_f64(self) -> f64 {
+                self.0 as f64
+            }
+
+            fn from_f64(value: f64) -> Self {
+                // Float to int casts saturate, so values out of range end up at the nearest bound
+                Self(value.round() as $inner)
+            }
+        }
+
+        impl From<$inner> for $unit {
+            fn from(value: $inner) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$unit> for $inner {
+            fn from(value: $unit) -> Self {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $unit {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt_scaled(f, self.0 as f64, $suffix)
+            }
+        }
+    };
+}
+
+unit!(Hz, u64, "Hz");
+unit!(SampleRate, u32, "S/s");
+unit!(Bandwidth, u32, "Hz");
+
+impl Hz {
+    /// Creates a frequency from `value` Hz, rounding to the nearest Hz.
+    pub fn from_f64(value: f64) -> Self {
+        RangeUnit::from_f64(value)
+    }
+}
+
+impl From<Hz> for Bandwidth {
+    /// Saturates at [u32::MAX], far beyond any bandwidth the hardware supports.
+    fn from(value: Hz) -> Self {
+        Self(value.0.try_into().unwrap_or(u32::MAX))
+    }
+}
+
+impl RangeUnit for Db {
+    fn to_f64(self) -> f64 {
+        self.0 as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        Self(value.round() as i32)
+    }
+}
+
+impl From<i32> for Db {
+    fn from(value: i32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Db> for i32 {
+    fn from(value: Db) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Db {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} dB", self.0)
+    }
+}
+
+/// Writes `value` with the largest SI prefix keeping it at or above 1, e.g. `2.4 GHz`.
+fn fmt_scaled(f: &mut fmt::Formatter<'_>, value: f64, unit: &str) -> fmt::Result {
+    let (scale, prefix) = [(1e9, "G"), (1e6, "M"), (1e3, "k")]
+        .into_iter()
+        .find(|(scale, _)| value >= *scale)
+        .unwrap_or((1.0, ""));
+    write!(f, "{} {prefix}{unit}", value / scale)
+}
+
+impl Range {
+    /// Limits `value` to `min..=max`.
+    ///
+    /// ```
+    /// use bladerf::{Db, Range};
+    ///
+    /// let range = Range { min: -15.0, max: 60.0, step: 1.0 };
+    /// assert_eq!(range.clamp(Db(80)), Db(60));
+    /// ```
+    pub fn clamp<U: RangeUnit>(&self, value: U) -> U {
+        self.bounded(value.to_f64())
+    }
+
+    /// Rounds `value` to the nearest step of the hardware within `min..=max`.
+    ///
+    /// The result is a setting the device can take exactly, so reading it back returns the same value.
+    ///
+    /// ```
+    /// use bladerf::{Bandwidth, Range};
+    ///
+    /// let range = Range { min: 200_000.0, max: 56_000_000.0, step: 1_000_000.0 };
+    /// assert_eq!(range.round(Bandwidth(1_600_000)), Bandwidth(1_200_000));
+    /// assert_eq!(range.round(Bandwidth(100_000_000)), Bandwidth(55_200_000));
+    /// ```
+    pub fn round<U: RangeUnit>(&self, value: U) -> U {
+        if self.step <= 0.0 {
+            return self.clamp(value);
+        }
+        // Small tolerance so a `max` on the last step is not lost to rounding errors
+        let last = ((self.max - self.min) / self.step + 1e-9).floor().max(0.0);
+        let steps = ((value.to_f64() - self.min) / self.step)
+            .round()
+            .clamp(0.0, last);
+        self.bounded(self.min + steps * self.step)
+    }
+
+    fn bounded<U: RangeUnit>(&self, value: f64) -> U {
+        let unit = U::from_f64(value.clamp(self.min, self.max));
+        // Units are whole numbers, which may round to just outside fractional bounds
+        if unit.to_f64() < self.min {
+            U::from_f64(self.min.ceil())
+        } else if unit.to_f64() > self.max {
+            U::from_f64(self.max.floor())
+        } else {
+            unit
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_constructors() {
+        assert_eq!(kHz(1.5), Hz(1_500));
+        assert_eq!(MHz(915.0), Hz(915_000_000));
+        assert_eq!(GHz(2.4), Hz(2_400_000_000));
+        assert_eq!(MHz(-1.0), Hz(0));
+        assert_eq!(Bandwidth::from(GHz(10.0)), Bandwidth(u32::MAX));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Hz(915_000_000).to_string(), "915 MHz");
+        assert_eq!(Hz(2_400_000_000).to_string(), "2.4 GHz");
+        assert_eq!(Hz(999).to_string(), "999 Hz");
+        assert_eq!(SampleRate(520_834).to_string(), "520.834 kS/s");
+        assert_eq!(Bandwidth(1_500_000).to_string(), "1.5 MHz");
+        assert_eq!(Db(-10).to_string(), "-10 dB");
+    }
+
+    #[test]
+    fn range_round() {
+        let range = Range {
+            min: 70e6,
+            max: 6e9,
+            step: 2.0,
+        };
+        assert_eq!(range.round(Hz(915_000_001)), Hz(915_000_002));
+        assert_eq!(range.round(Hz(1)), Hz(70_000_000));
+        assert_eq!(range.clamp(GHz(7.0)), Hz(6_000_000_000));
+
+        let gain = Range {
+            min: -89.75,
+            max: 0.0,
+            step: 0.25,
+        };
+        assert_eq!(gain.round(Db(-100)), Db(-89));
+        assert_eq!(gain.round(Db(3)), Db(0));
+
+        let fixed = Range {
+            min: 10.0,
+            max: 10.0,
+            step: 0.0,
+        };
+        assert_eq!(fixed.round(Db(3)), Db(10));
+    }
+}
@@ -0,0 +1,433 @@
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use num_complex::Complex32;
+
+use crate::brf_cf32_to_ci16;
+use crate::ci16_to_format;
+use crate::rng::Rng;
+use crate::ComplexI16;
+use crate::Error;
+use crate::Result;
+use crate::SampleFormat;
+use crate::BRF_CI16_SAMPLE_MAX;
+
+/// A source of baseband samples to transmit, such as the test signals in this module.
+///
+/// Every call continues where the previous one ended, so the output is phase-continuous no matter how it is split into
+/// blocks. Amplitudes are relative to full scale, samples outside of `-1.0..1.0` are clipped when converted to the
+/// device format.
+///
+/// [TxSyncStream::transmit_from()](crate::TxSyncStream::transmit_from) streams a waveform for a given time:
+/// ```no_run
+/// use std::time::Duration;
+/// use bladerf::{BladeRfAny, ChannelLayoutTx, ComplexI16, StreamConfig, Tone, TxChannel};
+///
+/// let dev = BladeRfAny::open_first().unwrap();
+/// let tx_stream = dev
+///     .tx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutTx::SISO(TxChannel::Tx0))
+///     .unwrap();
+/// tx_stream.enable().unwrap();
+///
+/// // A carrier 100 kHz above the center frequency for ten seconds, at 2 MHz
+/// let mut tone = Tone::new(100e3, 2e6, 0.5);
+/// tx_stream.transmit_from(&mut tone, Duration::from_secs(10)).unwrap();
+/// ```
+pub trait Waveform {
+    /// Fills `block` with the next samples of the waveform.
+    fn generate(&mut self, block: &mut [Complex32]);
+
+    /// Fills `block` with the next samples of the waveform in the sample format `F`.
+    ///
+    /// Every channel of a [MimoFrame](crate::MimoFrame) gets the same sample.
+    fn fill<F: SampleFormat>(&mut self, block: &mut [F])
+    where
+        Self: Sized,
+    {
+        let mut samples = vec![Complex32::ZERO; block.len()];
+        let mut raw = Vec::new();
+        fill_format(self, &mut samples, F::CHANNELS, &mut raw, block);
+    }
+}
+
+impl<W: Waveform + ?Sized> Waveform for &mut W {
+    fn generate(&mut self, block: &mut [Complex32]) {
+        (**self).generate(block)
+    }
+}
+
+impl<W: Waveform + ?Sized> Waveform for Box<W> {
+    fn generate(&mut self, block: &mut [Complex32]) {
+        (**self).generate(block)
+    }
+}
+
+/// Generates `samples.len()` samples of `waveform` and converts them to `dst`, repeating each for `channels` channels.
+///
+/// `dst` needs to hold `samples.len() * channels / F::CHANNELS` values, `raw` is a scratch buffer.
+pub(crate) fn fill_format<W: Waveform + ?Sized, F: SampleFormat>(
+    waveform: &mut W,
+    samples: &mut [Complex32],
+    channels: usize,
+    raw: &mut Vec<ComplexI16>,
+    dst: &mut [F],
+) {
+    waveform.generate(samples);
+    raw.clear();
+    raw.extend(samples.iter().flat_map(|sample| {
+        let clipped = Complex32::new(sample.re.clamp(-1.0, MAX), sample.im.clamp(-1.0, MAX));
+        std::iter::repeat_n(brf_cf32_to_ci16(clipped), channels)
+    }));
+    ci16_to_format(raw, dst);
+}
+
+/// Highest value the device format can represent.
+const MAX: f32 = BRF_CI16_SAMPLE_MAX as f32 / 2048.0;
+
+/// Phase accumulator in cycles, kept within `0.0..1.0` so it does not lose precision over long runs.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct Phase(f64);
+
+impl Phase {
+    /// Returns the unit phasor of the current phase and advances it by `step` cycles.
+    fn advance(&mut self, step: f64) -> Complex32 {
+        let (sin, cos) = (self.0 * TAU).sin_cos();
+        self.0 = (self.0 + step).rem_euclid(1.0);
+        Complex32::new(cos as f32, sin as f32)
+    }
+}
+
+/// A continuous wave at an offset from the center frequency.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tone {
+    phase: Phase,
+    step: f64,
+    amplitude: f32,
+}
+
+impl Tone {
+    /// Tone `offset` Hz from the center frequency, which may be negative, for samples at `sample_rate` Hz.
+    pub fn new(offset: f64, sample_rate: f64, amplitude: f32) -> Self {
+        Self {
+            phase: Phase::default(),
+            step: offset / sample_rate,
+            amplitude,
+        }
+    }
+}
+
+impl Waveform for Tone {
+    fn generate(&mut self, block: &mut [Complex32]) {
+        for sample in block {
+            *sample = self.phase.advance(self.step) * self.amplitude;
+        }
+    }
+}
+
+/// Two continuous waves of equal amplitude, as used for intermodulation measurements.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TwoTone {
+    first: Tone,
+    second: Tone,
+}
+
+impl TwoTone {
+    /// Tones at `first` and `second` Hz from the center frequency, whose sum peaks at `amplitude`.
+    pub fn new(first: f64, second: f64, sample_rate: f64, amplitude: f32) -> Self {
+        Self {
+            first: Tone::new(first, sample_rate, amplitude / 2.0),
+            second: Tone::new(second, sample_rate, amplitude / 2.0),
+        }
+    }
+}
+
+impl Waveform for TwoTone {
+    fn generate(&mut self, block: &mut [Complex32]) {
+        for sample in block {
+            let mut tones = [Complex32::ZERO; 2];
+            self.first.generate(&mut tones[..1]);
+            self.second.generate(&mut tones[1..]);
+            *sample = tones[0] + tones[1];
+        }
+    }
+}
+
+/// A linear frequency sweep, repeating every period.
+///
+/// The frequency jumps back to the start at the end of a period, but the phase stays continuous.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Chirp {
+    phase: Phase,
+    /// Frequency at the start of a period in cycles per sample.
+    start: f64,
+    /// Frequency change per sample.
+    rate: f64,
+    period: u64,
+    /// Samples generated in the current period.
+    position: u64,
+    amplitude: f32,
+}
+
+impl Chirp {
+    /// Chirp from `start` to `stop` Hz from the center frequency within `period`, for samples at `sample_rate` Hz.
+    ///
+    /// # Errors
+    /// `period` is shorter than one sample.
+    pub fn new(
+        start: f64,
+        stop: f64,
+        period: Duration,
+        sample_rate: f64,
+        amplitude: f32,
+    ) -> Result<Self> {
+        let period = (period.as_secs_f64() * sample_rate).round() as u64;
+        if period == 0 {
+            return Err(Error::msg("Chirp period needs to be at least one sample"));
+        }
+        let start = start / sample_rate;
+        Ok(Self {
+            phase: Phase::default(),
+            start,
+            rate: (stop / sample_rate - start) / period as f64,
+            period,
+            position: 0,
+            amplitude,
+        })
+    }
+}
+
+impl Waveform for Chirp {
+    fn generate(&mut self, block: &mut [Complex32]) {
+        for sample in block {
+            let frequency = self.start + self.rate * self.position as f64;
+            *sample = self.phase.advance(frequency) * self.amplitude;
+            self.position = (self.position + 1) % self.period;
+        }
+    }
+}
+
+/// Complex white gaussian noise.
+///
+/// The noise comes from a seeded generator, so the same seed always produces the same samples.
+#[derive(Clone, Debug)]
+pub struct Noise {
+    rng: Rng,
+    std_dev: f32,
+}
+
+impl Noise {
+    /// Noise with an RMS amplitude of `rms`, spread evenly over I and Q.
+    pub fn new(rms: f32, seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            std_dev: rms / std::f32::consts::SQRT_2,
+        }
+    }
+}
+
+impl Waveform for Noise {
+    fn generate(&mut self, block: &mut [Complex32]) {
+        for sample in block {
+            *sample = self.rng.gaussian(self.std_dev);
+        }
+    }
+}
+
+/// On-off keying of a carrier with a repeating bit pattern, which also covers pulsed signals.
+///
+/// ```
+/// use bladerf::{OokPattern, Waveform};
+/// use num_complex::Complex32;
+///
+/// // 10 µs pulses every 100 µs, at 2 MHz
+/// let mut pulses = OokPattern::new("1000000000", 20, 0.8).unwrap();
+/// let mut block = vec![Complex32::ZERO; 400];
+/// pulses.generate(&mut block);
+/// assert_eq!(block[19], Complex32::new(0.8, 0.0));
+/// assert_eq!(block[20], Complex32::ZERO);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct OokPattern {
+    bits: Vec<bool>,
+    samples_per_bit: usize,
+    carrier: Tone,
+    /// Samples generated of the current pass through the pattern.
+    position: usize,
+}
+
+impl OokPattern {
+    /// Keys the carrier with `bits`, a string of `0` and `1` which may be grouped by whitespace and `_`.
+    ///
+    /// The carrier is at the center frequency, see [OokPattern::with_carrier()].
+    ///
+    /// # Errors
+    /// - `bits` contains other characters or no bits at all.
+    /// - `samples_per_bit` is zero.
+    pub fn new(bits: &str, samples_per_bit: usize, amplitude: f32) -> Result<Self> {
+        let bits = bits
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '_')
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(Error::msg(format!("Invalid bit '{c}' in OOK pattern"))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_bits(bits, samples_per_bit, amplitude)
+    }
+
+    /// Keys the carrier with the bits of `bytes`, most significant bit first.
+    pub fn from_bytes(bytes: &[u8], samples_per_bit: usize, amplitude: f32) -> Result<Self> {
+        let bits = bytes
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |bit| byte >> bit & 1 == 1))
+            .collect();
+        Self::from_bits(bits, samples_per_bit, amplitude)
+    }
+
+    fn from_bits(bits: Vec<bool>, samples_per_bit: usize, amplitude: f32) -> Result<Self> {
+        if bits.is_empty() {
+            return Err(Error::msg("OOK pattern needs at least one bit"));
+        }
+        if samples_per_bit == 0 {
+            return Err(Error::msg("OOK bits need at least one sample"));
+        }
+        Ok(Self {
+            bits,
+            samples_per_bit,
+            carrier: Tone::new(0.0, 1.0, amplitude),
+            position: 0,
+        })
+    }
+
+    /// Moves the carrier `offset` Hz from the center frequency, for samples at `sample_rate` Hz.
+    ///
+    /// The carrier keeps running while it is keyed off, so all pulses are coherent.
+    pub fn with_carrier(mut self, offset: f64, sample_rate: f64) -> Self {
+        self.carrier = Tone::new(offset, sample_rate, self.carrier.amplitude);
+        self
+    }
+
+    /// The bits of the pattern.
+    pub fn bits(&self) -> &[bool] {
+        &self.bits
+    }
+
+    /// Number of samples of a single pass through the pattern.
+    pub fn period(&self) -> usize {
+        self.bits.len() * self.samples_per_bit
+    }
+}
+
+impl Waveform for OokPattern {
+    fn generate(&mut self, block: &mut [Complex32]) {
+        self.carrier.generate(block);
+        for sample in block {
+            if !self.bits[self.position / self.samples_per_bit] {
+                *sample = Complex32::ZERO;
+            }
+            self.position = (self.position + 1) % self.period();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ComplexI8;
+    use crate::MimoFrame;
+
+    fn generate(waveform: &mut impl Waveform, len: usize) -> Vec<Complex32> {
+        let mut block = vec![Complex32::ZERO; len];
+        waveform.generate(&mut block);
+        block
+    }
+
+    fn assert_close(actual: &[Complex32], expected: &[Complex32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).norm() < 1e-5, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn phase_continuity() {
+        let waveforms = || -> [Box<dyn Waveform>; 5] {
+            [
+                Box::new(Tone::new(123_456.0, 1e6, 0.5)),
+                Box::new(TwoTone::new(-10e3, 30e3, 1e6, 0.5)),
+                Box::new(Chirp::new(-100e3, 100e3, Duration::from_micros(300), 1e6, 0.5).unwrap()),
+                Box::new(Noise::new(0.5, 1)),
+                Box::new(
+                    OokPattern::new("1011 0", 7, 0.5)
+                        .unwrap()
+                        .with_carrier(1e3, 1e6),
+                ),
+            ]
+        };
+        for (mut whole, mut split) in waveforms().into_iter().zip(waveforms()) {
+            let expected = generate(&mut whole, 1000);
+            let mut blocks = Vec::new();
+            for len in [1, 333, 666] {
+                blocks.extend(generate(&mut split, len));
+            }
+            assert_close(&blocks, &expected);
+        }
+    }
+
+    #[test]
+    fn reference_waveforms() {
+        let quarter = [
+            Complex32::new(1.0, 0.0),
+            Complex32::new(0.0, 1.0),
+            Complex32::new(-1.0, 0.0),
+            Complex32::new(0.0, -1.0),
+        ];
+        assert_close(&generate(&mut Tone::new(250.0, 1000.0, 1.0), 4), &quarter);
+
+        // Tones at ±fs/4 cancel out in Q
+        let expected = [1.0, 0.0, -1.0, 0.0].map(|re| Complex32::new(re, 0.0));
+        assert_close(
+            &generate(&mut TwoTone::new(250.0, -250.0, 1000.0, 1.0), 4),
+            &expected,
+        );
+
+        // Sweeping through 0, fs/4, fs/2 and 3fs/4 accumulates phases of 0, 0, 1/4, 3/4 and 1/2 cycles
+        let mut chirp = Chirp::new(0.0, 1000.0, Duration::from_millis(4), 1000.0, 1.0).unwrap();
+        let expected = [quarter[0], quarter[0], quarter[1], quarter[3], quarter[2]];
+        assert_close(&generate(&mut chirp, 5), &expected);
+
+        let mut ook = OokPattern::from_bytes(&[0b1010_0000], 2, 1.0).unwrap();
+        let on: Vec<_> = generate(&mut ook, 20).iter().map(|s| s.re > 0.5).collect();
+        let bits = [1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0];
+        assert_eq!(on, bits.map(|bit| bit == 1));
+        assert!(OokPattern::new("10x", 2, 1.0).is_err());
+        assert!(OokPattern::new(" _ ", 2, 1.0).is_err());
+    }
+
+    #[test]
+    fn noise_statistics() {
+        let samples = generate(&mut Noise::new(0.1, 7), 100_000);
+        let power = samples.iter().map(|s| s.norm_sqr()).sum::<f32>() / samples.len() as f32;
+        let mean = samples.iter().sum::<Complex32>() / samples.len() as f32;
+        assert!((power - 0.01).abs() < 0.0005, "{power}");
+        assert!(mean.norm() < 0.002, "{mean}");
+        assert_eq!(generate(&mut Noise::new(0.1, 7), 10), samples[..10]);
+    }
+
+    #[test]
+    fn sample_formats() {
+        let mut tone = Tone::new(250.0, 1000.0, 0.5);
+        let mut ci16 = [ComplexI16::ZERO; 2];
+        tone.fill(&mut ci16);
+        assert_eq!(ci16, [ComplexI16::new(1024, 0), ComplexI16::new(0, 1024)]);
+
+        let mut ci8 = [ComplexI8::ZERO; 2];
+        tone.fill(&mut ci8);
+        assert_eq!(ci8, [ComplexI8::new(-64, 0), ComplexI8::new(0, -64)]);
+
+        let mut frames = [[ComplexI16::ZERO; 2]; 1];
+        Tone::new(0.0, 1000.0, 2.0).fill::<MimoFrame<ComplexI16>>(&mut frames);
+        assert_eq!(frames, [[ComplexI16::new(2047, 0); 2]]);
+    }
+}
@@ -0,0 +1,47 @@
+//! Setup shared by the tests against a [SimulatedBladeRf].
+
+// Every test file only uses some of the helpers
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bladerf::{
+    BladeRF, ChannelLayoutRx, ChannelLayoutTx, Loopback, Result, RxChannel, RxSyncStream,
+    SampleFormat, SimulatedBladeRf, StreamConfig, TxChannel, TxSyncStream,
+};
+
+pub const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A simulated device without noise, so only what was transmitted or added as a tone is received.
+pub fn quiet_device() -> SimulatedBladeRf {
+    let device = SimulatedBladeRf::new();
+    device.set_noise_level(0.0);
+    device
+}
+
+/// A [quiet_device()] which receives what it transmits.
+pub fn loopback_device() -> Result<SimulatedBladeRf> {
+    let device = quiet_device();
+    unsafe { device.set_loopback(Loopback::Firmware) }?;
+    Ok(device)
+}
+
+/// Opens and enables a stream receiving on [RxChannel::Rx0].
+pub fn rx0<F: SampleFormat>(
+    device: &SimulatedBladeRf,
+    config: StreamConfig,
+) -> Result<RxSyncStream<&SimulatedBladeRf, F, SimulatedBladeRf>> {
+    let stream = device.rx_streamer(config, ChannelLayoutRx::SISO(RxChannel::Rx0))?;
+    stream.enable()?;
+    Ok(stream)
+}
+
+/// Opens and enables a stream transmitting on [TxChannel::Tx0].
+pub fn tx0<F: SampleFormat>(
+    device: &SimulatedBladeRf,
+    config: StreamConfig,
+) -> Result<TxSyncStream<&SimulatedBladeRf, F, SimulatedBladeRf>> {
+    let stream = device.tx_streamer(config, ChannelLayoutTx::SISO(TxChannel::Tx0))?;
+    stream.enable()?;
+    Ok(stream)
+}
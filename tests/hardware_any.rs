@@ -1,10 +1,19 @@
 #![cfg(feature = "hwtest_any")]
 
-use std::{sync::Arc, thread, time::Duration};
+use std::{
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
 
 use bladerf::{
-    BladeRF, BladeRfAny, ChannelLayoutRx, ComplexI12, ComplexI16, Error, Result, RxChannel,
-    StreamConfig,
+    BladeRF, BladeRfAny, Channel, ChannelLayoutRx, ChannelLayoutTx, ComplexI12, ComplexI16,
+    Direction, Error, Result, RxChannel, StreamConfig, SyncCoordinator, TriggerMaster,
+    TriggerSignal, TxChannel,
 };
 use serial_test::serial;
 
@@ -279,3 +288,154 @@ fn rx_streamer_arced() -> Result<()> {
         Err(err) => Err(err),
     }
 }
+
+#[test]
+#[serial]
+fn async_rx_streamer() -> Result<()> {
+    let device = Arc::new(BladeRfAny::open_first()?);
+    let buffers_received = Arc::new(AtomicUsize::new(0));
+
+    let counter = buffers_received.clone();
+    let rx_streamer = BladeRfAny::rx_async_streamer::<ComplexI16, _>(
+        device,
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+        move |samples| {
+            assert_eq!(samples.len(), 8192);
+            // Stop on our own after a few buffers
+            if counter.fetch_add(1, Ordering::Relaxed) < 10 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        },
+    )?;
+
+    thread::sleep(Duration::from_secs(1));
+    assert!(!rx_streamer.is_running());
+    rx_streamer.stop()?;
+
+    assert_eq!(buffers_received.load(Ordering::Relaxed), 11);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn rx_read_with_meta() -> Result<()> {
+    let device = BladeRfAny::open_first()?;
+    let rx_streamer = device.rx_streamer::<ComplexI16>(
+        StreamConfig::default().with_metadata(true),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    )?;
+    rx_streamer.enable()?;
+
+    let mut buffer = [ComplexI16::ZERO; 4096];
+    let meta = rx_streamer.read_with_meta(&mut buffer, None, Duration::from_secs(1))?;
+    assert_eq!(meta.actual_count, 4096);
+
+    // Schedule a read a bit in the future and make sure it starts exactly where we asked.
+    let scheduled = meta.timestamp + 100_000;
+    let meta = rx_streamer.read_with_meta(&mut buffer, Some(scheduled), Duration::from_secs(2))?;
+    assert_eq!(meta.timestamp, scheduled);
+
+    // Reading in the past is an error
+    let past = rx_streamer.read_with_meta(&mut buffer, Some(0), Duration::from_secs(1));
+    assert_eq!(past, Err(Error::TimePast));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn tx_write_burst() -> Result<()> {
+    let device = BladeRfAny::open_first()?;
+    let tx_streamer = device.tx_streamer::<ComplexI16>(
+        StreamConfig::default().with_metadata(true),
+        ChannelLayoutTx::SISO(TxChannel::Tx0),
+    )?;
+    tx_streamer.enable()?;
+
+    let samples = [ComplexI16::new(512, 0); 3000];
+    tx_streamer.write_burst(&samples, None, Duration::from_secs(1))?;
+
+    let now = device.get_timestamp(Direction::TX)?;
+    let meta = tx_streamer.write_burst(&samples, Some(now + 200_000), Duration::from_secs(1))?;
+    assert!(!meta.status.underrun);
+
+    let past = tx_streamer.write_burst(&samples, Some(1), Duration::from_secs(1));
+    assert_eq!(past, Err(Error::TimePast));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn rx_streamer_triggered() -> Result<()> {
+    let device = BladeRfAny::open_first()?;
+    let trigger = TriggerMaster::new(&device, Channel::Rx0, TriggerSignal::MiniExp1)?;
+    assert!(trigger.state()?.armed);
+
+    // Only one master per device
+    assert!(TriggerMaster::new(&device, Channel::Rx0, TriggerSignal::MiniExp1).is_err());
+
+    let rx_streamer = device.rx_streamer::<ComplexI16>(
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    )?;
+    rx_streamer.enable_armed(&trigger)?;
+
+    let mut buffer = [ComplexI16::ZERO; 1024];
+    let res = rx_streamer.read(&mut buffer, Duration::from_millis(500));
+    assert_eq!(res, Err(Error::Timeout));
+
+    trigger.fire()?;
+    rx_streamer.read(&mut buffer, Duration::from_secs(1))?;
+    assert!(trigger.state()?.fired);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn sync_coordinator_single_device() -> Result<()> {
+    let device = Arc::new(BladeRfAny::open_first()?);
+    let coordinator = SyncCoordinator::new(vec![device], 0)?;
+
+    let capture = coordinator.start_rx::<ComplexI16>(
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+        Duration::from_secs(1),
+    )?;
+
+    let mut buffers = vec![vec![ComplexI16::ZERO; 4096]];
+    let metas = capture.read_at(8192, &mut buffers, Duration::from_secs(1))?;
+    assert_eq!(metas[0].timestamp, 8192);
+    Ok(())
+}
+
+#[cfg(feature = "seify")]
+#[test]
+#[serial]
+fn seify_rx_streamer() -> std::result::Result<(), seify::Error> {
+    use bladerf::SeifyBladeRf;
+    use num_complex::Complex32;
+    use seify::{Device, Direction, RxStreamer};
+
+    assert!(!SeifyBladeRf::probe(&seify::Args::new())?.is_empty());
+    let device = Device::from_impl(SeifyBladeRf::open(seify::Args::new())?);
+    device.set_frequency(Direction::Rx, 0, 915e6)?;
+    device.set_sample_rate(Direction::Rx, 0, 2e6)?;
+    assert_eq!(device.sample_rate(Direction::Rx, 0)?, 2e6);
+
+    let mut rx_streamer = device.rx_streamer(&[0])?;
+    assert!(device.rx_streamer(&[0]).is_err());
+    rx_streamer.activate()?;
+    let mut buffer = vec![Complex32::default(); rx_streamer.mtu()?];
+    let n = rx_streamer.read(&mut [&mut buffer], 1_000_000)?;
+    assert_eq!(n, buffer.len());
+
+    // Dropping the streamer releases the device for a new one
+    drop(rx_streamer);
+    let mut rx_streamer = device.rx_streamer(&[0])?;
+    rx_streamer.activate()?;
+    let n = rx_streamer.read(&mut [&mut buffer], 1_000_000)?;
+    assert_eq!(n, buffer.len());
+    Ok(())
+}
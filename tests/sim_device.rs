@@ -0,0 +1,109 @@
+use bladerf::{
+    Bandwidth, BladeRF, Channel, Db, Error, GHz, Hz, MHz, ModuleConfig, RationalRate, Result,
+    SampleRate, SimulatedBladeRf,
+};
+
+#[test]
+fn identity() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    let other = SimulatedBladeRf::new();
+    assert_eq!(device.get_board_name(), "simulated");
+    assert_eq!(device.info()?.serial(), device.get_serial()?);
+    assert_ne!(device.get_serial()?, other.get_serial()?);
+    assert_ne!(device.device_id(), other.device_id());
+    // There is no libbladerf handle to hand out
+    assert!(device.get_device_ptr().is_null());
+    assert!(device.is_fpga_configured()?);
+    Ok(())
+}
+
+#[test]
+fn ranges() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+
+    let range = device.get_frequency_range(Channel::Rx0)?;
+    device.set_frequency(Channel::Rx0, range.min as u64)?;
+    assert_eq!(device.get_frequency(Channel::Rx0)?, range.min as u64);
+    // Both channels share the oscillator
+    assert_eq!(device.get_frequency(Channel::Rx1)?, range.min as u64);
+    assert_eq!(
+        device.set_frequency(Channel::Rx0, range.max as u64 + 1),
+        Err(Error::Range)
+    );
+    assert_eq!(
+        device.set_frequency(Channel::Tx0, 10_000_000),
+        Err(Error::Range)
+    );
+
+    assert_eq!(device.set_sample_rate(Channel::Rx0, 100), Err(Error::Range));
+    assert_eq!(
+        device.set_sample_rate(Channel::Rx0, 10_000_000)?,
+        10_000_000
+    );
+    assert_eq!(device.get_sample_rate(Channel::Rx0)?, 10_000_000);
+
+    let range = device.get_bandwidth_range(Channel::Rx0)?;
+    assert_eq!(device.set_bandwidth(Channel::Rx0, 1)?, range.min as u32);
+
+    let range = device.get_gain_range(Channel::Tx0)?;
+    device.set_gain(Channel::Tx0, 1000)?;
+    assert_eq!(device.get_gain(Channel::Tx0)?, range.max as i32);
+    Ok(())
+}
+
+#[test]
+fn rational_sample_rate() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+
+    // DVB-T 8 MHz channels sample at 64/7 MHz
+    let rate = RationalRate::best_fit(64_000_000, 7)?;
+    let actual = device.set_rational_sample_rate(Channel::Rx0, rate.into())?;
+    assert_eq!(actual, rate);
+    assert_eq!(device.get_sample_rate(Channel::Rx0)?, 9_142_857);
+
+    let unreduced = RationalRate {
+        integer: 9_142_856,
+        num: 16,
+        den: 14,
+    };
+    device.set_rational_sample_rate(Channel::Rx0, unreduced.into())?;
+    assert_eq!(device.get_rational_sample_rate(Channel::Rx0)?, rate);
+    assert!(device.get_rational_sample_rate(Channel::Rx0)? < RationalRate::from_f64(9_142_857.5)?);
+    Ok(())
+}
+
+#[test]
+fn typed_units() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+
+    device.set_frequency(Channel::Rx0, MHz(433.92))?;
+    assert_eq!(Hz(device.get_frequency(Channel::Rx0)?), Hz(433_920_000));
+    let range = device.get_frequency_range(Channel::Rx0)?;
+    device.set_frequency(Channel::Rx0, range.clamp(GHz(7.0)))?;
+    assert_eq!(device.get_frequency(Channel::Rx0)?, range.max as u64);
+
+    assert_eq!(
+        device.set_sample_rate(Channel::Rx0, SampleRate(2_000_000))?,
+        2_000_000
+    );
+    let range = device.get_bandwidth_range(Channel::Rx0)?;
+    let bandwidth = range.round(Bandwidth(1_234_567));
+    assert_eq!(device.set_bandwidth(Channel::Rx0, bandwidth)?, bandwidth.0);
+
+    let range = device.get_gain_range(Channel::Rx0)?;
+    device.set_gain(Channel::Rx0, range.clamp(Db(-100)))?;
+    assert_eq!(Db(device.get_gain(Channel::Rx0)?), Db(range.min as i32));
+
+    device.configure_module(
+        Channel::Tx0,
+        ModuleConfig::new(GHz(2.4), SampleRate(4_000_000), MHz(3.0), Db(20)),
+    )?;
+    assert_eq!(
+        Hz(device.get_frequency(Channel::Tx0)?).to_string(),
+        "2.4 GHz"
+    );
+    assert_eq!(device.get_sample_rate(Channel::Tx0)?, 4_000_000);
+    assert_eq!(device.get_bandwidth(Channel::Tx0)?, 3_000_000);
+    assert_eq!(device.get_gain(Channel::Tx0)?, 20);
+    Ok(())
+}
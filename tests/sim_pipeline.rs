@@ -0,0 +1,157 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use bladerf::{
+    BladeRF, Channel, ChannelLayoutRx, ComplexI16, OverflowPolicy, Result, RxBroadcast,
+    RxBroadcastConfig, RxChannel, RxPipeline, RxPipelineConfig, SimulatedBladeRf, StreamConfig,
+};
+#[cfg(feature = "futures")]
+use bladerf::{ChannelLayoutTx, Error, TxChannel};
+
+mod common;
+use common::*;
+
+#[test]
+fn rx_pipeline_blocking() -> Result<()> {
+    let device = Arc::new(SimulatedBladeRf::new());
+    let rx_streamer = SimulatedBladeRf::rx_streamer_arc::<ComplexI16>(
+        device.clone(),
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    )?;
+    rx_streamer.enable()?;
+
+    let config = RxPipelineConfig::new(4, 1024)?.with_realtime_priority(false);
+    let pipeline = RxPipeline::spawn(rx_streamer, config)?;
+    for _ in 0..16 {
+        let block = pipeline.recv(TIMEOUT)?;
+        assert_eq!(block.len(), 1024);
+    }
+    // The reader waits for room instead of dropping
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(pipeline.len(), 4);
+    assert_eq!(pipeline.stats().blocks_dropped, 0);
+    pipeline.stop()?;
+    Ok(())
+}
+
+#[test]
+fn rx_pipeline_overflow() -> Result<()> {
+    for policy in [OverflowPolicy::DropOldest, OverflowPolicy::DropNewest] {
+        let device = quiet_device();
+        device.set_frequency(Channel::Rx0, 915_000_000)?;
+        device.set_sample_rate(Channel::Rx0, 1_000_000)?;
+        device.add_tone(915_001_000, 0.5);
+
+        let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default())?;
+        let config = RxPipelineConfig::new(4, 1024)?
+            .with_overflow_policy(policy)
+            .with_realtime_priority(false);
+
+        std::thread::scope(|scope| -> Result<()> {
+            let pipeline = RxPipeline::spawn_scoped(scope, rx_streamer, config)?;
+            while pipeline.stats().blocks_dropped == 0 {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            assert_eq!(pipeline.len(), 4);
+            assert!(pipeline.stats().blocks_read > 4);
+
+            let block = pipeline.recv(TIMEOUT)?;
+            if policy == OverflowPolicy::DropNewest {
+                // Still the very first block, where the tone starts with a phase of zero
+                assert_eq!(block[0], ComplexI16::new(1024, 0));
+            }
+            drop(block);
+            pipeline.stop()
+        })?;
+    }
+    Ok(())
+}
+
+#[test]
+fn rx_broadcast() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default())?;
+    let config = RxBroadcastConfig::new(1024)?.with_realtime_priority(false);
+
+    std::thread::scope(|scope| -> Result<()> {
+        let broadcast = RxBroadcast::spawn_scoped(scope, rx_streamer, config)?;
+        let fast = broadcast.subscribe(4096)?;
+        let slow = broadcast.subscribe(2)?;
+        assert!(broadcast.subscribe(0).is_err());
+        assert_eq!(broadcast.num_subscribers(), 2);
+
+        // Both subscribers see the same blocks without copies
+        let shared = slow.recv(TIMEOUT)?;
+        let mut found = false;
+        while !found {
+            found = std::sync::Arc::ptr_eq(&fast.recv(TIMEOUT)?, &shared);
+        }
+        drop(shared);
+
+        let late = broadcast.subscribe(1024)?;
+        for _ in 0..64 {
+            assert_eq!(fast.recv(TIMEOUT)?.len(), 1024);
+            late.recv(TIMEOUT)?;
+        }
+        assert!(slow.lagged() > 0);
+        assert_eq!(slow.len(), 2);
+
+        drop(late);
+        while broadcast.num_subscribers() != 2 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        broadcast.stop()?;
+
+        // The queued blocks can still be taken, then the subscriber learns that the broadcast stopped
+        while slow.try_recv().is_some() {}
+        assert!(slow.recv(TIMEOUT).is_err());
+        Ok(())
+    })
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn futures_adapters() -> Result<()> {
+    use bladerf::{RxSampleStream, TxSampleSink};
+    use futures::{executor::block_on, SinkExt, StreamExt};
+
+    let device = Arc::new(loopback_device()?);
+    let tx_streamer = SimulatedBladeRf::tx_streamer_arc::<ComplexI16>(
+        device.clone(),
+        StreamConfig::default(),
+        ChannelLayoutTx::SISO(TxChannel::Tx0),
+    )?;
+    let rx_streamer = SimulatedBladeRf::rx_streamer_arc::<ComplexI16>(
+        device.clone(),
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    )?;
+    tx_streamer.enable()?;
+    rx_streamer.enable()?;
+
+    let samples: Vec<_> = (0..1024).map(|i| ComplexI16::new(i, -i)).collect();
+    let mut sink = TxSampleSink::spawn(tx_streamer, TIMEOUT)?;
+    block_on(async {
+        sink.send(samples.clone()).await?;
+        sink.close().await?;
+
+        let mut stream = RxSampleStream::spawn(rx_streamer, 512, TIMEOUT)?;
+        assert_eq!(stream.next().await.unwrap()?, samples[..512]);
+        assert_eq!(stream.next().await.unwrap()?, samples[512..]);
+        stream.stop().await;
+
+        // Timeouts are passed on without ending the stream
+        // Never enabled, so reads time out
+        let disabled = SimulatedBladeRf::rx_streamer_arc::<ComplexI16>(
+            Arc::new(SimulatedBladeRf::new()),
+            StreamConfig::default(),
+            ChannelLayoutRx::SISO(RxChannel::Rx0),
+        )?;
+        let mut stream = RxSampleStream::spawn(disabled, 512, Duration::from_millis(10))?;
+        assert_eq!(stream.next().await.unwrap(), Err(Error::Timeout));
+        assert_eq!(stream.next().await.unwrap(), Err(Error::Timeout));
+        stream.stop().await;
+        Ok(())
+    })
+}
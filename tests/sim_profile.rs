@@ -0,0 +1,144 @@
+use bladerf::{
+    BladeRF, Channel, ChannelProfile, CorrectionDcOffsetI, CorrectionPhase, DeviceProfile,
+    DeviceSnapshot, Error, GainMode, Loopback, ProfileCorrections, RationalRate, Result,
+    SimulatedBladeRf, TuningMode,
+};
+
+#[test]
+fn device_profile() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    let rate = RationalRate {
+        integer: 2_000_000,
+        num: 1,
+        den: 3,
+    };
+    let mut profile = DeviceProfile {
+        tuning_mode: Some(TuningMode::Host),
+        loopback: Some(Loopback::Firmware),
+        ..Default::default()
+    };
+    *profile.channel_mut(Channel::Rx0) = ChannelProfile {
+        frequency: Some(915_000_000),
+        sample_rate: Some(4_000_000.into()),
+        bandwidth: Some(3_000_000),
+        gain_mode: Some(GainMode::Manual),
+        gain_stages: [("full".to_owned(), 20)].into(),
+        ..Default::default()
+    };
+    *profile.channel_mut(Channel::Tx1) = ChannelProfile {
+        sample_rate: Some(rate.into()),
+        gain: Some(10),
+        bias_tee: Some(true),
+        corrections: ProfileCorrections {
+            dc_offset_i: Some(-100),
+            phase: Some(300),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    unsafe { profile.apply(&device) }?;
+
+    assert_eq!(device.get_tuning_mode()?, TuningMode::Host);
+    assert_eq!(device.get_loopback()?, Loopback::Firmware);
+    assert_eq!(device.get_frequency(Channel::Rx0)?, 915_000_000);
+    assert_eq!(device.get_sample_rate(Channel::Rx0)?, 4_000_000);
+    assert_eq!(device.get_bandwidth(Channel::Rx0)?, 3_000_000);
+    assert_eq!(device.get_gain_mode(Channel::Rx0)?, GainMode::Manual);
+    assert_eq!(device.get_gain(Channel::Rx0)?, 20);
+    assert_eq!(device.get_rational_sample_rate(Channel::Tx1)?, rate);
+    assert_eq!(device.get_gain(Channel::Tx1)?, 10);
+    assert!(device.get_bias_tee(Channel::Tx1)?);
+    assert!(!device.get_bias_tee(Channel::Tx0)?);
+    assert_eq!(
+        device
+            .get_correction::<CorrectionDcOffsetI>(Channel::Tx1)?
+            .0,
+        -100
+    );
+    assert_eq!(
+        device.get_correction::<CorrectionPhase>(Channel::Tx1)?.0,
+        300
+    );
+
+    // Every problem is reported and nothing is applied
+    let invalid = DeviceProfile {
+        loopback: Some(Loopback::RfLna1),
+        rx1: Some(ChannelProfile {
+            frequency: Some(10_000_000),
+            gain_stages: [("lna".to_owned(), 3)].into(),
+            ..Default::default()
+        }),
+        tx0: Some(ChannelProfile {
+            frequency: Some(433_000_000),
+            gain_mode: Some(GainMode::Manual),
+            corrections: ProfileCorrections {
+                gain: Some(5000),
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let msg = match unsafe { invalid.apply(&device) } {
+        Err(Error::Msg(msg)) => msg,
+        other => panic!("invalid profile was applied: {other:?}"),
+    };
+    for setting in [
+        "loopback",
+        "rx1.frequency",
+        "rx1.gain_stages",
+        "tx0.gain_mode",
+        "tx0.corrections.gain",
+    ] {
+        assert!(msg.contains(setting), "{setting} missing in {msg}");
+    }
+    assert!(!msg.contains("tx0.frequency"));
+    assert_ne!(device.get_frequency(Channel::Tx0)?, 433_000_000);
+    assert_eq!(device.get_loopback()?, Loopback::Firmware);
+    Ok(())
+}
+
+#[test]
+fn snapshot_restore() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    device.set_gain_mode(Channel::Rx1, GainMode::Manual)?;
+    device.set_gain(Channel::Rx1, 12)?;
+    let before = DeviceSnapshot::capture(&device)?;
+    let profile = before.profile();
+    assert_eq!(profile.smb_mode, None);
+    assert_eq!(profile.rx0.as_ref().unwrap().gain, None);
+    assert_eq!(profile.rx1.as_ref().unwrap().gain_stages["full"], 12);
+    assert_eq!(profile.tx0.as_ref().unwrap().bias_tee, Some(false));
+
+    device.set_frequency(Channel::Tx1, 433_920_000)?;
+    device.set_gain(Channel::Rx1, 30)?;
+    device.set_bias_tee(Channel::Tx0, true)?;
+    device.set_correction(Channel::Rx0, CorrectionPhase(-25))?;
+    unsafe { device.set_loopback(Loopback::RficBist) }?;
+    let after = DeviceSnapshot::capture(&device)?;
+
+    let changes: Vec<_> = before
+        .diff(&after)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            "loopback: None -> RficBist",
+            "rx0.corrections.phase: 0 -> -25",
+            "rx1.gain: 12 -> 30",
+            "rx1.gain_stages.full: 12 -> 30",
+            // Both transmit channels share the oscillator
+            "tx0.frequency: 2400000000 -> 433920000",
+            "tx0.bias_tee: false -> true",
+            "tx1.frequency: 2400000000 -> 433920000",
+        ]
+    );
+
+    unsafe { before.restore(&device) }?;
+    let restored = DeviceSnapshot::capture(&device)?;
+    assert!(before.diff(&restored).is_empty());
+    assert_eq!(restored, before);
+    Ok(())
+}
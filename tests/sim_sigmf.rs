@@ -0,0 +1,112 @@
+#![cfg(feature = "sigmf")]
+
+use bladerf::{
+    BladeRF, Channel, ComplexI16, ComplexI8, IqFileFormat, Playback, Result, SigMfRecorder,
+    SimulatedBladeRf, StreamConfig, TxChannel,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn sigmf_recording() -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("capture");
+
+    let device = SimulatedBladeRf::new();
+    device.set_frequency(Channel::Rx0, 915_000_000)?;
+    device.set_sample_rate(Channel::Rx0, 2_000_000)?;
+    // The segments start exactly where the device changed with the timestamps from the metadata
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default().with_metadata(true))?;
+
+    let mut recorder =
+        SigMfRecorder::create(rx_streamer, &base)?.with_description("simulated \"capture\"");
+    let mut buffer = [ComplexI16::ZERO; 1024];
+    recorder.record(&mut buffer, TIMEOUT)?;
+    recorder.retune(868_000_000)?;
+    recorder.record(&mut buffer, TIMEOUT)?;
+    // Nothing changed, so no new segment
+    assert!(!recorder.update_capture()?);
+    device.set_gain(Channel::Rx0, 30)?;
+    assert!(recorder.update_capture()?);
+    recorder.record(&mut buffer, TIMEOUT)?;
+
+    let captures = recorder.captures().to_vec();
+    assert_eq!(captures.len(), 3);
+    assert_eq!(captures[1].sample_start, 1024);
+    assert_eq!(captures[1].frequency, 868_000_000);
+    assert_eq!(captures[2].sample_start, 2048);
+    assert_eq!(captures[2].gain, 30);
+    recorder.finish()?;
+
+    let data = std::fs::read(dir.path().join("capture.sigmf-data")).unwrap();
+    assert_eq!(data.len(), 3 * 1024 * 4);
+    let meta = std::fs::read_to_string(dir.path().join("capture.sigmf-meta")).unwrap();
+    assert!(meta.contains("\"core:datatype\": \"ci16_le\""));
+    assert!(meta.contains("\"core:sample_rate\": 2000000"));
+    assert!(meta.contains("\"core:frequency\": 868000000"));
+    assert!(meta.contains("\"core:description\": \"simulated \\\"capture\\\"\""));
+    assert!(meta.contains("FPGA v0.15.0"));
+    Ok(())
+}
+
+#[test]
+fn sigmf_buffered_capture() -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("capture");
+
+    let device = SimulatedBladeRf::new();
+    device.set_frequency(Channel::Rx0, 915_000_000)?;
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default())?;
+    let mut recorder = SigMfRecorder::create(rx_streamer, &base)?;
+    let mut buffer = [ComplexI16::ZERO; 1024];
+    recorder.record(&mut buffer, TIMEOUT)?;
+    recorder.retune(868_000_000)?;
+    recorder.set_gain(30)?;
+    recorder.record(&mut buffer, TIMEOUT)?;
+
+    // Without metadata the buffers of the stream are assumed to hold samples from before the retune
+    let captures = recorder.captures().to_vec();
+    assert_eq!(captures.len(), 2);
+    assert_eq!(captures[1].sample_start, 1024 + 16 * 8192);
+    assert_eq!(captures[1].gain, 30);
+    let meta = recorder.metadata();
+    assert!(meta.contains("\"core:frequency\": 915000000"));
+    assert!(!meta.contains("\"core:frequency\": 868000000"));
+    Ok(())
+}
+
+#[test]
+fn sigmf_playback() -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("capture");
+
+    let device = SimulatedBladeRf::new();
+    device.set_frequency(Channel::Rx0, 433_920_000)?;
+    device.set_sample_rate(Channel::Rx0, 1_000_000)?;
+    let rx_streamer = rx0::<ComplexI8>(&device, StreamConfig::default())?;
+    let mut recorder = SigMfRecorder::create(rx_streamer, &base)?;
+    let mut recorded = vec![ComplexI8::ZERO; 2048];
+    recorder.record(&mut recorded, TIMEOUT)?;
+    recorder.finish()?;
+
+    let mut playback = Playback::open_sigmf(base.with_extension("sigmf-meta"))?;
+    assert_eq!(playback.format(), IqFileFormat::Cs8);
+    assert_eq!(playback.frequency(), Some(433_920_000));
+    assert_eq!(playback.sample_rate(), Some(1_000_000));
+    playback.configure(&device, TxChannel::Tx0)?;
+    assert_eq!(device.get_frequency(Channel::Tx0)?, 433_920_000);
+    assert_eq!(device.get_sample_rate(Channel::Tx0)?, 1_000_000);
+
+    device.set_tx_capture(true);
+    let tx_streamer = tx0::<ComplexI8>(&device, StreamConfig::default())?;
+    playback.play(&tx_streamer, TIMEOUT)?;
+
+    let sent = device.take_tx_samples(TxChannel::Tx0);
+    assert_eq!(sent.len(), recorded.len());
+    for (sent, recorded) in sent.iter().zip(&recorded) {
+        assert_eq!(sent.re, recorded.re as f32 / 128.0);
+        assert_eq!(sent.im, recorded.im as f32 / 128.0);
+    }
+    Ok(())
+}
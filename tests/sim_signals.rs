@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use num_complex::Complex32;
+
+use bladerf::{
+    BladeRF, Channel, ChannelLayoutTx, ComplexI16, IqFileFormat, MimoFrame, OokConfig,
+    OokDemodulator, OokModulator, OokPattern, Playback, Result, SimulatedBladeRf, StreamConfig,
+    Tone, TxChannel,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn transmit_waveforms() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    device.set_tx_capture(true);
+    device.set_sample_rate(Channel::Tx0, 1_000_000)?;
+    let tx_streamer = tx0::<ComplexI16>(&device, StreamConfig::default())?;
+
+    let mut tone = Tone::new(250e3, 1e6, 0.5);
+    let written = tx_streamer.transmit_from(&mut tone, Duration::from_millis(10))?;
+    assert_eq!(written, 10_000);
+    // Another call continues the phase, 10k samples being a multiple of the period
+    tx_streamer.transmit_from(&mut tone, Duration::from_micros(3))?;
+    let sent = device.take_tx_samples(TxChannel::Tx0);
+    assert_eq!(sent.len(), 10_003);
+    assert_eq!(&sent[10_000..], &sent[..3]);
+    assert_eq!(sent[1], Complex32::new(0.0, 0.5));
+
+    let device = SimulatedBladeRf::new();
+    device.set_tx_capture(true);
+    device.set_sample_rate(Channel::Tx0, 1_000_000)?;
+    let tx_streamer = device.tx_streamer::<MimoFrame<Complex32>>(
+        StreamConfig::default().with_metadata(true),
+        ChannelLayoutTx::MIMO,
+    )?;
+    tx_streamer.enable()?;
+
+    let mut pulses = OokPattern::new("1100", 1000, 0.5)?;
+    assert_eq!(
+        tx_streamer.transmit_from(&mut pulses, Duration::from_millis(5))?,
+        5000
+    );
+    let tx0 = device.take_tx_samples(TxChannel::Tx0);
+    assert_eq!(tx0, device.take_tx_samples(TxChannel::Tx1));
+    // Padded to the end of the burst
+    assert!(tx0.len() >= 5000);
+    assert!(tx0[..2000].iter().all(|sample| sample.re == 0.5));
+    assert!(tx0[2000..4000].iter().all(|sample| sample.re == 0.0));
+    assert!(tx0[4000..5000].iter().all(|sample| sample.re == 0.5));
+    assert!(tx0[5000..].iter().all(|sample| sample.re == 0.0));
+    Ok(())
+}
+
+#[test]
+fn ook_loopback() -> Result<()> {
+    let device = loopback_device()?;
+    let tx_streamer = tx0::<ComplexI16>(&device, StreamConfig::default())?;
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default())?;
+
+    let config = OokConfig::new(20)?;
+    let mut modulator = OokModulator::new(config.with_amplitude(1000));
+    let mut demodulator = OokDemodulator::new(config);
+    modulator.send(b"Hello")?;
+    modulator.send(b"bladeRF")?;
+
+    let mut block = vec![ComplexI16::ZERO; 1024];
+    let mut frames = Vec::new();
+    for _ in 0..8 {
+        modulator.modulate(&mut block);
+        tx_streamer.write(&block, TIMEOUT)?;
+        rx_streamer.read(&mut block, TIMEOUT)?;
+        frames.extend(demodulator.process(&block));
+    }
+    assert!(modulator.is_idle());
+    let payloads: Vec<_> = frames
+        .iter()
+        .map(|frame| frame.payload.as_slice())
+        .collect();
+    assert_eq!(payloads, [&b"Hello"[..], b"bladeRF"]);
+    assert_eq!(demodulator.stats().crc_errors, 0);
+    Ok(())
+}
+
+#[test]
+fn raw_file_playback() -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("samples.cf32");
+    let samples: Vec<_> = (0..3000)
+        .map(|i| Complex32::new((i % 2048) as f32 / 2048.0, -0.25))
+        .collect();
+    let bytes: Vec<u8> = samples
+        .iter()
+        .flat_map(|s| [s.re.to_le_bytes(), s.im.to_le_bytes()].concat())
+        .collect();
+    std::fs::write(&path, bytes).unwrap();
+
+    let device = SimulatedBladeRf::new();
+    device.set_tx_capture(true);
+    let tx_streamer = tx0::<ComplexI16>(&device, StreamConfig::default())?;
+
+    let mut playback = Playback::open_raw(&path, IqFileFormat::Cf32)?.with_repeat(3);
+    assert_eq!(playback.num_samples(), 3000);
+    assert_eq!(playback.play(&tx_streamer, TIMEOUT)?, 9000);
+
+    // The repetitions follow each other without gaps
+    let sent = device.take_tx_samples(TxChannel::Tx0);
+    assert_eq!(sent.len(), 9000);
+    for (sent, sample) in sent.iter().zip(samples.iter().cycle()) {
+        assert_eq!(sent, sample);
+    }
+
+    let mut looping = Playback::open_raw(&path, IqFileFormat::Cf32)?.looping();
+    let written = looping.play_until(&tx_streamer, TIMEOUT, |written| written >= 20_000)?;
+    assert!(written >= 20_000);
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use num_complex::Complex32;
+
+use bladerf::{
+    BladeRF, Channel, ChannelLayoutRx, ChannelLayoutTx, ComplexI16, Direction, Error, Loopback,
+    MimoFrame, Result, RxChannel, SimulatedBladeRf, StreamConfig, Transceiver, TriggerMaster,
+    TriggerSignal, TxChannel,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn rx_streamer_noise() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    let rx_streamer = device.rx_streamer::<ComplexI16>(
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    )?;
+
+    let mut buffer = [ComplexI16::ZERO; 4096];
+    // Nothing arrives from disabled channels
+    assert_eq!(rx_streamer.read(&mut buffer, TIMEOUT), Err(Error::Timeout));
+
+    rx_streamer.enable()?;
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    assert!(buffer.iter().any(|sample| *sample != ComplexI16::ZERO));
+    assert!(buffer.iter().all(|sample| sample.re.abs() < 512));
+
+    // Only one streamer at a time
+    assert!(device
+        .rx_streamer::<ComplexI16>(
+            StreamConfig::default(),
+            ChannelLayoutRx::SISO(RxChannel::Rx0)
+        )
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn rx_tone_visibility() -> Result<()> {
+    let device = quiet_device();
+    device.set_frequency(Channel::Rx0, 915_000_000)?;
+    device.set_sample_rate(Channel::Rx0, 2_000_000)?;
+    device.set_bandwidth(Channel::Rx0, 1_500_000)?;
+    device.add_tone(915_250_000, 0.5);
+
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default())?;
+
+    let mut buffer = [ComplexI16::ZERO; 1024];
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    for sample in buffer {
+        let magnitude = (sample.re as f32).hypot(sample.im as f32) / 2048.0;
+        assert!((magnitude - 0.5).abs() < 0.01, "{sample:?}");
+    }
+
+    // Outside of the bandwidth
+    device.set_frequency(Channel::Rx0, 914_000_000)?;
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    assert!(buffer.iter().all(|sample| *sample == ComplexI16::ZERO));
+    Ok(())
+}
+
+#[test]
+fn loopback_round_trip() -> Result<()> {
+    let device = loopback_device()?;
+    assert_eq!(
+        unsafe { device.set_loopback(Loopback::BbTxlpfRxvga2) },
+        Err(Error::Unsupported)
+    );
+
+    let tx_streamer = tx0::<ComplexI16>(&device, StreamConfig::default())?;
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default())?;
+
+    let samples: Vec<_> = (0..2048)
+        .map(|i| ComplexI16::new(i - 1024, 1024 - i))
+        .collect();
+    tx_streamer.write(&samples, TIMEOUT)?;
+
+    let mut buffer = vec![ComplexI16::ZERO; samples.len()];
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    assert_eq!(buffer, samples);
+    Ok(())
+}
+
+#[test]
+fn mimo_frames() -> Result<()> {
+    let device = loopback_device()?;
+
+    // Frames hold both channels, so they only fit a MIMO layout
+    let siso = device.rx_streamer::<MimoFrame<ComplexI16>>(
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    );
+    assert!(siso.is_err());
+    let siso = device.tx_streamer::<MimoFrame<ComplexI16>>(
+        StreamConfig::default(),
+        ChannelLayoutTx::SISO(TxChannel::Tx0),
+    );
+    assert!(siso.is_err());
+
+    // The rejected streams do not keep the device from streaming
+    let tx_streamer =
+        device.tx_streamer::<ComplexI16>(StreamConfig::default(), ChannelLayoutTx::MIMO)?;
+    let rx_streamer = device
+        .rx_streamer::<MimoFrame<ComplexI16>>(StreamConfig::default(), ChannelLayoutRx::MIMO)?;
+    tx_streamer.enable()?;
+    rx_streamer.enable()?;
+
+    let tx0: Vec<_> = (0..1024).map(|i| ComplexI16::new(i, 0)).collect();
+    let tx1: Vec<_> = (0..1024).map(|i| ComplexI16::new(0, -i)).collect();
+    tx_streamer.write_mimo(&tx0, &tx1, TIMEOUT)?;
+    assert!(tx_streamer.write_mimo(&tx0, &tx1[1..], TIMEOUT).is_err());
+
+    let mut frames = vec![[ComplexI16::ZERO; 2]; 1024];
+    rx_streamer.read(&mut frames, TIMEOUT)?;
+    assert!(frames
+        .iter()
+        .zip(tx0.iter().zip(&tx1))
+        .all(|(frame, (s0, s1))| *frame == [*s0, *s1]));
+    assert_eq!(rx_streamer.stats().samples, 2048);
+    Ok(())
+}
+
+#[test]
+fn complex_f32_loopback() -> Result<()> {
+    let device = loopback_device()?;
+
+    let tx_streamer = tx0::<Complex32>(&device, StreamConfig::default())?;
+    let rx_streamer = rx0::<Complex32>(&device, StreamConfig::default())?;
+
+    let samples: Vec<_> = (0..1024)
+        .map(|i| Complex32::new(i as f32 / 1024.0, -0.5))
+        .collect();
+    tx_streamer.write(&samples, TIMEOUT)?;
+
+    let mut buffer = vec![Complex32::default(); samples.len()];
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    assert_eq!(buffer, samples);
+
+    // Out of range samples are clamped instead of wrapping around
+    tx_streamer.write(&[Complex32::new(2.0, -2.0)], TIMEOUT)?;
+    rx_streamer.read(&mut buffer[..1], TIMEOUT)?;
+    assert_eq!(buffer[0], Complex32::new(2047.0 / 2048.0, -1.0));
+    Ok(())
+}
+
+#[test]
+fn rx_read_with_meta() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default().with_metadata(true))?;
+
+    let mut buffer = [ComplexI16::ZERO; 4096];
+    let meta = rx_streamer.read_with_meta(&mut buffer, None, TIMEOUT)?;
+    assert_eq!(meta.timestamp, 0);
+    assert_eq!(meta.actual_count, 4096);
+    assert_eq!(device.get_timestamp(Direction::RX)?, 4096);
+
+    let scheduled = meta.timestamp + 100_000;
+    let meta = rx_streamer.read_with_meta(&mut buffer, Some(scheduled), TIMEOUT)?;
+    assert_eq!(meta.timestamp, scheduled);
+
+    let past = rx_streamer.read_with_meta(&mut buffer, Some(0), TIMEOUT);
+    assert_eq!(past, Err(Error::TimePast));
+    Ok(())
+}
+
+#[test]
+fn stream_stats() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    device.set_sample_rate(Channel::Rx0, 2_000_000)?;
+    let rx_streamer = device.rx_streamer::<ComplexI16>(
+        StreamConfig::default().with_metadata(true),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    )?;
+
+    let mut buffer = [ComplexI16::ZERO; 4096];
+    // Not enabled yet
+    let timeout = rx_streamer.read(&mut buffer, Duration::from_millis(10));
+    assert_eq!(timeout, Err(Error::Timeout));
+
+    rx_streamer.enable()?;
+    for _ in 0..4 {
+        rx_streamer.read(&mut buffer, TIMEOUT)?;
+    }
+    let meta = rx_streamer.read_with_meta(&mut buffer, None, TIMEOUT)?;
+    // Scheduled reads skip ahead without being a discontinuity
+    rx_streamer.read_with_meta(&mut buffer, Some(meta.timestamp + 100_000), TIMEOUT)?;
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+
+    let stats = rx_streamer.stats();
+    assert_eq!(stats.transfers, 7);
+    assert_eq!(stats.samples, 7 * 4096);
+    assert_eq!(stats.timeouts, 1);
+    assert_eq!(stats.discontinuities, 0);
+    assert!(!stats.has_lost_samples());
+    assert_eq!(stats.configured_sample_rate, Some(2_000_000));
+    assert!(stats.achieved_sample_rate.is_some());
+    assert!(stats.latency.p50 <= stats.latency.max);
+
+    // The sample rate is cached until the statistics are reset
+    device.set_sample_rate(Channel::Rx0, 1_000_000)?;
+    assert_eq!(rx_streamer.stats().configured_sample_rate, Some(2_000_000));
+    rx_streamer.reset_stats();
+    assert_eq!(rx_streamer.stats().transfers, 0);
+    assert_eq!(rx_streamer.stats().configured_sample_rate, Some(1_000_000));
+
+    let tx_streamer = tx0::<ComplexI16>(&device, StreamConfig::default())?;
+    tx_streamer.write(&buffer, TIMEOUT)?;
+    let stats = tx_streamer.stats();
+    assert_eq!(stats.samples, 4096);
+    assert_eq!(stats.underruns, 0);
+    Ok(())
+}
+
+#[test]
+fn tx_write_burst() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    device.set_tx_capture(true);
+    let tx_streamer = tx0::<ComplexI16>(&device, StreamConfig::default().with_metadata(true))?;
+
+    let samples = [ComplexI16::new(1024, 0); 3000];
+    tx_streamer.write_burst(&samples, None, TIMEOUT)?;
+    let sent = device.take_tx_samples(TxChannel::Tx0);
+    // Padded to the buffer size
+    assert_eq!(sent.len() % 4096, 0);
+    assert!(sent[..3000].iter().all(|sample| sample.re == 0.5));
+    assert!(sent[3000..].iter().all(|sample| sample.re == 0.0));
+
+    let now = device.get_timestamp(Direction::TX)?;
+    tx_streamer.write_burst(&samples, Some(now + 1000), TIMEOUT)?;
+    let sent = device.take_tx_samples(TxChannel::Tx0);
+    assert!(sent[..1000].iter().all(|sample| sample.re == 0.0));
+    assert_eq!(sent[1000].re, 0.5);
+
+    let past = tx_streamer.write_burst(&samples, Some(1), TIMEOUT);
+    assert_eq!(past, Err(Error::TimePast));
+
+    // Plain writes on a metadata stream are sent as bursts
+    tx_streamer.write(&samples, TIMEOUT)?;
+    let sent = device.take_tx_samples(TxChannel::Tx0);
+    assert_eq!(sent.len(), 8192);
+    assert_eq!(sent[0].re, 0.5);
+    Ok(())
+}
+
+#[test]
+fn rx_streamer_triggered() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    let trigger = TriggerMaster::new(&device, Channel::Rx0, TriggerSignal::MiniExp1)?;
+    let rx_streamer = device.rx_streamer::<ComplexI16>(
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    )?;
+    rx_streamer.enable_armed(&trigger)?;
+
+    let mut buffer = [ComplexI16::ZERO; 1024];
+    assert_eq!(rx_streamer.read(&mut buffer, TIMEOUT), Err(Error::Timeout));
+
+    trigger.fire()?;
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    assert!(trigger.state()?.fired);
+    Ok(())
+}
+
+#[test]
+fn transceiver_reply() -> Result<()> {
+    let conf = StreamConfig::default().with_metadata(true);
+    for (rx_layout, tx_layout) in [
+        (
+            ChannelLayoutRx::SISO(RxChannel::Rx0),
+            ChannelLayoutTx::SISO(TxChannel::Tx0),
+        ),
+        (ChannelLayoutRx::MIMO, ChannelLayoutTx::MIMO),
+    ] {
+        let device = Arc::new(SimulatedBladeRf::new());
+        device.set_tx_capture(true);
+        let rx = SimulatedBladeRf::rx_streamer_arc::<ComplexI16>(device.clone(), conf, rx_layout)?;
+        let tx = SimulatedBladeRf::tx_streamer_arc::<ComplexI16>(device.clone(), conf, tx_layout)?;
+        let trx = Transceiver::new(rx, tx)?;
+        trx.enable()?;
+        assert_eq!(trx.timestamp_offset(), 0);
+
+        let mut buffer = [ComplexI16::ZERO; 4096];
+        let meta = trx.receive(&mut buffer, TIMEOUT)?;
+        let reply = [ComplexI16::new(1024, 0); 2000];
+        trx.reply(&reply, meta.timestamp, 10_000, TIMEOUT)?;
+
+        let sent = device.take_tx_samples(TxChannel::Tx0);
+        assert!(sent[..10_000].iter().all(|sample| sample.re == 0.0));
+        assert_eq!(sent[10_000].re, 0.5);
+
+        // The reply has to start after what was already transmitted
+        let late = trx.reply(&reply, meta.timestamp, 10_000, TIMEOUT);
+        assert_eq!(late, Err(Error::TimePast));
+        assert!(matches!(
+            trx.reply_burst(meta.timestamp, 0),
+            Err(Error::TimePast)
+        ));
+        assert!(device.take_tx_samples(TxChannel::Tx0).is_empty());
+        trx.disable()?;
+    }
+
+    let device = SimulatedBladeRf::new();
+    let other = SimulatedBladeRf::new();
+    let rx = device.rx_streamer::<ComplexI16>(conf, ChannelLayoutRx::SISO(RxChannel::Rx0))?;
+    let tx = other.tx_streamer::<ComplexI16>(conf, ChannelLayoutTx::SISO(TxChannel::Tx0))?;
+    assert!(Transceiver::new(rx, tx).is_err());
+    Ok(())
+}
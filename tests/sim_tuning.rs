@@ -0,0 +1,108 @@
+use bladerf::{BladeRF, Channel, ComplexI16, MHz, Result, SimulatedBladeRf, StreamConfig};
+
+mod common;
+use common::*;
+
+#[test]
+fn scheduled_retune() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default())?;
+
+    device.schedule_retune(Channel::Rx0, 1000, 100_000_000, None)?;
+    assert_ne!(device.get_frequency(Channel::Rx0)?, 100_000_000);
+
+    let mut buffer = [ComplexI16::ZERO; 2048];
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    assert_eq!(device.get_frequency(Channel::Rx0)?, 100_000_000);
+    Ok(())
+}
+
+#[test]
+fn frequency_hopping() -> Result<()> {
+    let device = SimulatedBladeRf::new();
+    let rx_streamer = rx0::<ComplexI16>(&device, StreamConfig::default().with_metadata(true))?;
+
+    let frequencies = [MHz(900.0), MHz(910.0), MHz(920.0)];
+    let initial = device.get_frequency(Channel::Rx0)?;
+    let mut hopper = rx_streamer.frequency_hopper(&frequencies, 4096)?;
+    // Computing the quick tunes leaves the channel where it was
+    assert_eq!(device.get_frequency(Channel::Rx0)?, initial);
+    assert!(hopper.schedule().is_err());
+
+    hopper.start(1024)?;
+    let mut buffer = [ComplexI16::ZERO; 4096];
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    assert_eq!(device.get_frequency(Channel::Rx0)?, 900_000_000);
+    assert_eq!(hopper.frequency_at(1023), None);
+    assert_eq!(hopper.frequency_at(5119), Some(900_000_000));
+    rx_streamer.read(&mut buffer, TIMEOUT)?;
+    assert_eq!(device.get_frequency(Channel::Rx0)?, 910_000_000);
+    assert_eq!(hopper.schedule()?, 2);
+    assert!(hopper.late_retunes().is_empty());
+
+    // Falling behind skips the hops in between, only the ones still ahead are scheduled
+    rx_streamer.read_with_meta(&mut buffer, Some(40_000), TIMEOUT)?;
+    assert_eq!(hopper.schedule()?, 4);
+    let late = hopper.take_late_retunes();
+    assert_eq!(late.len(), 5);
+    assert_eq!(late[0].hop, 6);
+    assert_eq!(late[0].frequency, 900_000_000);
+    assert!(late
+        .iter()
+        .all(|late| late.timestamp < late.device_timestamp));
+
+    let meta = rx_streamer.read_with_meta(&mut buffer, None, TIMEOUT)?;
+    assert_eq!(
+        Some(device.get_frequency(Channel::Rx0)?),
+        hopper.frequency_at(meta.timestamp + 4095)
+    );
+    hopper.stop()?;
+    Ok(())
+}
+
+#[cfg(feature = "sweep")]
+#[test]
+fn power_sweep() -> Result<()> {
+    use bladerf::{ChannelLayoutRx, RxChannel, SampleRate, SweepConfig, Sweeper};
+    use num_complex::Complex32;
+
+    for metadata in [false, true] {
+        let device = SimulatedBladeRf::new();
+        device.set_noise_level(0.001);
+        device.add_tone(911_300_000, 0.5);
+        let rx_streamer =
+            rx0::<Complex32>(&device, StreamConfig::default().with_metadata(metadata))?;
+
+        let config = SweepConfig::new(MHz(900.0), MHz(920.0), SampleRate(2_000_000), 256)?;
+        let mut sweeper = Sweeper::new(&rx_streamer, config, TIMEOUT)?;
+        // 192 bins of 7812.5 Hz per step
+        assert_eq!(sweeper.num_steps(), 14);
+
+        let spectrum = sweeper.sweep()?;
+        assert_eq!(spectrum.start, 900_000_000.0);
+        assert_eq!(spectrum.power_db.len(), 2560);
+        let (frequency, power) = spectrum.peak().unwrap();
+        assert!(
+            (frequency - 911_300_000.0).abs() <= sweeper.bin_width(),
+            "{frequency}"
+        );
+        assert!((power + 6.0).abs() < 3.0, "{power}");
+
+        let lines: Vec<_> = sweeper.lines().take(15).collect::<Result<_>>()?;
+        assert_eq!(lines[0].sweep, 1);
+        assert_eq!(lines[14].sweep, 2);
+        assert_eq!(lines[13].hz_high, 920_000_000.0);
+        assert!(lines[1]
+            .to_csv()
+            .contains(", 901500000, 903000000, 7812.50, 256, "));
+    }
+
+    let device = SimulatedBladeRf::new();
+    let rx_streamer = device.rx_streamer::<Complex32>(
+        StreamConfig::default(),
+        ChannelLayoutRx::SISO(RxChannel::Rx0),
+    )?;
+    let config = SweepConfig::new(10_000_000, 50_000_000, 2_000_000, 256)?;
+    assert!(Sweeper::new(&rx_streamer, config, TIMEOUT).is_err());
+    Ok(())
+}